@@ -0,0 +1,826 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::consts::NOP;
+use crate::cpu::Variant;
+use crate::opcodes::{self, Mode};
+use crate::{Byte, Word};
+
+/// Bytes assembled at consecutive addresses starting at `start`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub start: Word,
+    pub bytes: Vec<Byte>,
+}
+
+/// The output of `assemble`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Assembly {
+    /// A new segment starts at every `.org`, empty ones are dropped
+    pub segments: Vec<Segment>,
+    /// Labels and constants. Local labels are stored as `global@local`
+    pub symbols: BTreeMap<String, Word>,
+}
+
+impl Assembly {
+    pub fn symbol(&self, name: &str) -> Option<Word> {
+        self.symbols.get(name).copied()
+    }
+
+    /// Every assembled byte with its address, collect them into a `Memory` or feed them to `MemoryMap::poke`
+    pub fn bytes(&self) -> impl Iterator<Item = (Word, Byte)> + '_ {
+        self.segments.iter().flat_map(|segment| {
+            segment.bytes.iter().enumerate().map(|(offset, byte)| (segment.start.wrapping_add(offset as Word), *byte))
+        })
+    }
+}
+
+/// Why `assemble` failed, `line` counts from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsmError {
+    /// The line could not be parsed
+    Syntax { line: usize, message: String },
+    /// No opcode of the variant is called `mnemonic`
+    UnknownMnemonic { line: usize, mnemonic: String },
+    /// `mnemonic` exists but not with the addressing mode the operand asks for
+    InvalidMode { line: usize, mnemonic: String },
+    UndefinedSymbol { line: usize, name: String },
+    DuplicateSymbol { line: usize, name: String },
+    /// `value` does not fit in the byte or word it is stored in
+    OutOfRange { line: usize, value: i64 },
+    /// `target` is further than a signed byte away from the end of the branch
+    BranchOutOfRange { line: usize, target: Word },
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsmError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            AsmError::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "line {}: unknown instruction {}", line, mnemonic)
+            }
+            AsmError::InvalidMode { line, mnemonic } => {
+                write!(f, "line {}: {} does not support this addressing mode", line, mnemonic)
+            }
+            AsmError::UndefinedSymbol { line, name } => write!(f, "line {}: undefined symbol {}", line, name),
+            AsmError::DuplicateSymbol { line, name } => write!(f, "line {}: {} is already defined", line, name),
+            AsmError::OutOfRange { line, value } => write!(f, "line {}: value {} is out of range", line, value),
+            AsmError::BranchOutOfRange { line, target } => {
+                write!(f, "line {}: branch target ${:04X} is out of range", line, target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// Assembles `source` for `variant` in two passes, the first one sizes every line and the
+/// second one emits the bytes with all labels known.
+///
+/// Supported syntax:
+/// - `label:` and local `@label:`, which belong to the closest global label above them
+/// - `name = expression` constants
+/// - `.org`, `.byte` (numbers and `"strings"`) and `.word` directives
+/// - every addressing mode in the usual syntax, `ASL` and `ASL A` both being accumulator mode.
+///   Zero page modes are used when the operand is known to fit in the first pass
+/// - expressions with `$hex`, `%binary`, decimal and `'c'` literals, `*` for the current address,
+///   `+ - * / & | ^ << >>`, parentheses and the unary `-`, `~`, `<` (low byte) and `>` (high byte)
+/// - comments starting with `;`
+///
+/// ```
+/// use emulator_6502::asm;
+/// use emulator_6502::cpu::Variant;
+///
+/// let assembly = asm::assemble(
+///     "
+///         .org $E000
+///     start:
+///         LDX #5
+///     @loop:
+///         DEX
+///         BNE @loop
+///     ",
+///     Variant::Nmos6502,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(assembly.segments[0].bytes, [0xA2, 0x05, 0xCA, 0xD0, 0xFD]);
+/// assert_eq!(assembly.symbol("start@loop"), Some(0xE002));
+/// ```
+pub fn assemble(source: &str, variant: Variant) -> Result<Assembly, AsmError> {
+    let lines = parse(source)?;
+
+    let mut symbols = BTreeMap::new();
+    let mut modes = vec![None; lines.len()];
+    // constants that referred to later symbols, with the pc at their line
+    let mut deferred = Vec::new();
+
+    let mut pc: Word = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        let number = line.number;
+
+        if let Some(label) = &line.label {
+            define(&mut symbols, label, pc, number)?;
+        }
+
+        match &line.statement {
+            Statement::Empty => (),
+            Statement::Org(expr) => pc = word(evaluate(expr, &symbols, pc, number)?, number)?,
+            Statement::Bytes(data) => {
+                let len: usize = data
+                    .iter()
+                    .map(|datum| match datum {
+                        Datum::Expr(_) => 1,
+                        Datum::Text(text) => text.len(),
+                    })
+                    .sum();
+
+                pc = pc.wrapping_add(len as Word);
+            }
+            Statement::Words(exprs) => pc = pc.wrapping_add(2 * exprs.len() as Word),
+            Statement::Assign(name, expr) => match expr.evaluate(&symbols, pc) {
+                Ok(value) => define(&mut symbols, name, word(value, number)?, number)?,
+                Err(_) => deferred.push((index, pc)),
+            },
+            Statement::Instruction { mnemonic, operand } => {
+                let value = operand.first().and_then(|expr| expr.evaluate(&symbols, pc).ok());
+                let mode = select_mode(variant, mnemonic, operand, value, number)?;
+
+                modes[index] = Some(mode);
+                pc = pc.wrapping_add(1 + mode.operand_len());
+            }
+        }
+    }
+
+    // a constant can depend on another deferred one further down, keep going until nothing changes
+    loop {
+        let before = deferred.len();
+        let mut pending = Vec::new();
+
+        for (index, pc) in deferred {
+            let line = &lines[index];
+            let Statement::Assign(name, expr) = &line.statement else { unreachable!() };
+
+            match expr.evaluate(&symbols, pc) {
+                Ok(value) => define(&mut symbols, name, word(value, line.number)?, line.number)?,
+                Err(_) => pending.push((index, pc)),
+            }
+        }
+
+        deferred = pending;
+
+        if deferred.len() == before {
+            break;
+        }
+    }
+
+    if let Some(&(index, pc)) = deferred.first() {
+        let Statement::Assign(_, expr) = &lines[index].statement else { unreachable!() };
+        evaluate(expr, &symbols, pc, lines[index].number)?;
+    }
+
+    let mut segments = vec![Segment { start: 0, bytes: Vec::new() }];
+    pc = 0;
+
+    for (index, line) in lines.iter().enumerate() {
+        let number = line.number;
+        let mut bytes = Vec::new();
+
+        match &line.statement {
+            Statement::Empty => (),
+            Statement::Org(expr) => {
+                pc = word(evaluate(expr, &symbols, pc, number)?, number)?;
+
+                match segments.last_mut() {
+                    Some(segment) if segment.bytes.is_empty() => segment.start = pc,
+                    _ => segments.push(Segment { start: pc, bytes: Vec::new() }),
+                }
+            }
+            Statement::Bytes(data) => {
+                for datum in data {
+                    match datum {
+                        Datum::Expr(expr) => bytes.push(byte(evaluate(expr, &symbols, pc, number)?, number)?),
+                        Datum::Text(text) => bytes.extend_from_slice(text),
+                    }
+                }
+            }
+            Statement::Words(exprs) => {
+                for expr in exprs {
+                    let value = evaluate(expr, &symbols, pc, number)?;
+                    // negative words are stored in two's complement
+                    let value = if (-0x8000..0).contains(&value) { value + 0x10000 } else { value };
+
+                    bytes.extend_from_slice(&word(value, number)?.to_le_bytes());
+                }
+            }
+            Statement::Assign(..) => (),
+            Statement::Instruction { mnemonic, operand } => {
+                let mode = modes[index].unwrap();
+                let next = pc.wrapping_add(1 + mode.operand_len());
+
+                bytes.push(find_opcode(variant, mnemonic, mode).unwrap());
+
+                match (mode, operand) {
+                    (Mode::Implied | Mode::Accumulator, _) => (),
+                    (Mode::Relative, Operand::Direct(target)) => {
+                        let target = word(evaluate(target, &symbols, pc, number)?, number)?;
+                        bytes.push(branch_offset(target, next, number)?);
+                    }
+                    (Mode::ZeroPageRelative, Operand::Pair(address, target)) => {
+                        let address = evaluate(address, &symbols, pc, number)?;
+                        let target = word(evaluate(target, &symbols, pc, number)?, number)?;
+
+                        bytes.push(zero_page(address, number)?);
+                        bytes.push(branch_offset(target, next, number)?);
+                    }
+                    (Mode::Immediate, Operand::Immediate(expr)) => {
+                        bytes.push(byte(evaluate(expr, &symbols, pc, number)?, number)?);
+                    }
+                    (_, operand) => {
+                        let value = evaluate(operand.first().unwrap(), &symbols, pc, number)?;
+
+                        if mode.operand_len() == 1 {
+                            bytes.push(zero_page(value, number)?);
+                        } else {
+                            bytes.extend_from_slice(&word(value, number)?.to_le_bytes());
+                        }
+                    }
+                }
+            }
+        }
+
+        pc = pc.wrapping_add(bytes.len() as Word);
+        segments.last_mut().unwrap().bytes.extend(bytes);
+    }
+
+    segments.retain(|segment| !segment.bytes.is_empty());
+
+    Ok(Assembly { segments, symbols })
+}
+
+fn define(symbols: &mut BTreeMap<String, Word>, name: &str, value: Word, line: usize) -> Result<(), AsmError> {
+    if symbols.insert(name.to_string(), value).is_some() {
+        return Err(AsmError::DuplicateSymbol { line, name: name.to_string() });
+    }
+
+    Ok(())
+}
+
+fn evaluate(expr: &Expr, symbols: &BTreeMap<String, Word>, pc: Word, line: usize) -> Result<i64, AsmError> {
+    expr.evaluate(symbols, pc).map_err(|error| match error {
+        EvalError::Undefined(name) => AsmError::UndefinedSymbol { line, name },
+        EvalError::DivisionByZero => AsmError::Syntax { line, message: "division by zero".to_string() },
+    })
+}
+
+/// A data or immediate byte, negative values are stored in two's complement
+fn byte(value: i64, line: usize) -> Result<Byte, AsmError> {
+    match value {
+        -128..=255 => Ok(value as Byte),
+        _ => Err(AsmError::OutOfRange { line, value }),
+    }
+}
+
+/// An operand that has to be a zero page address
+fn zero_page(value: i64, line: usize) -> Result<Byte, AsmError> {
+    Byte::try_from(value).map_err(|_| AsmError::OutOfRange { line, value })
+}
+
+fn word(value: i64, line: usize) -> Result<Word, AsmError> {
+    Word::try_from(value).map_err(|_| AsmError::OutOfRange { line, value })
+}
+
+fn branch_offset(target: Word, next: Word, line: usize) -> Result<Byte, AsmError> {
+    let offset = target.wrapping_sub(next) as i16;
+
+    i8::try_from(offset).map(|offset| offset as Byte).map_err(|_| AsmError::BranchOutOfRange { line, target })
+}
+
+/// Picks the addressing mode for `operand`, preferring zero page when the first pass already knows
+/// the operand fits
+fn select_mode(
+    variant: Variant,
+    mnemonic: &str,
+    operand: &Operand,
+    value: Option<i64>,
+    line: usize,
+) -> Result<Mode, AsmError> {
+    let candidates: &[Mode] = match operand {
+        Operand::None => &[Mode::Implied, Mode::Accumulator],
+        Operand::Accumulator => &[Mode::Accumulator],
+        Operand::Immediate(_) => &[Mode::Immediate],
+        Operand::Direct(_) => &[Mode::Relative, Mode::ZeroPage, Mode::Absolute],
+        Operand::IndexedX(_) => &[Mode::ZeroPageX, Mode::AbsoluteX],
+        Operand::IndexedY(_) => &[Mode::ZeroPageY, Mode::AbsoluteY],
+        Operand::Indirect(_) => &[Mode::ZeroPageIndirect, Mode::Indirect],
+        Operand::IndirectX(_) => &[Mode::IndirectX, Mode::AbsoluteIndirectX],
+        Operand::IndirectY(_) => &[Mode::IndirectY],
+        Operand::Pair(..) => &[Mode::ZeroPageRelative],
+    };
+
+    let available: Vec<Mode> =
+        candidates.iter().copied().filter(|mode| find_opcode(variant, mnemonic, *mode).is_some()).collect();
+
+    let fits_zero_page = matches!(value, Some(0..=0xFF));
+    let is_zero_page = |mode: Mode| {
+        matches!(mode, Mode::ZeroPage | Mode::ZeroPageX | Mode::ZeroPageY | Mode::ZeroPageIndirect | Mode::IndirectX)
+    };
+
+    if let Some(mode) = available.iter().find(|mode| fits_zero_page || !is_zero_page(**mode)) {
+        return Ok(*mode);
+    }
+
+    // only a zero page mode is left, a value that does not fit gets reported by the second pass
+    match available.first() {
+        Some(mode) => Ok(*mode),
+        None if (0..=255).any(|opcode| opcodes::lookup(variant, opcode).mnemonic == mnemonic) => {
+            Err(AsmError::InvalidMode { line, mnemonic: mnemonic.to_string() })
+        }
+        None => Err(AsmError::UnknownMnemonic { line, mnemonic: mnemonic.to_string() }),
+    }
+}
+
+fn find_opcode(variant: Variant, mnemonic: &str, mode: Mode) -> Option<Byte> {
+    // the NMOS chips have several implied NOPs, only $EA is official
+    if mnemonic == "NOP" && mode == Mode::Implied {
+        return Some(NOP);
+    }
+
+    (0..=255).find(|opcode| {
+        let metadata = opcodes::lookup(variant, *opcode);
+        metadata.mnemonic == mnemonic && metadata.mode == mode
+    })
+}
+
+struct Line {
+    number: usize,
+    label: Option<String>,
+    statement: Statement,
+}
+
+enum Statement {
+    Empty,
+    Org(Expr),
+    Bytes(Vec<Datum>),
+    Words(Vec<Expr>),
+    Assign(String, Expr),
+    Instruction { mnemonic: String, operand: Operand },
+}
+
+enum Datum {
+    Expr(Expr),
+    Text(Vec<Byte>),
+}
+
+/// An operand as written, `select_mode` turns it into an addressing mode
+enum Operand {
+    None,
+    /// `A`
+    Accumulator,
+    /// `#e`
+    Immediate(Expr),
+    /// `e`
+    Direct(Expr),
+    /// `e,X`
+    IndexedX(Expr),
+    /// `e,Y`
+    IndexedY(Expr),
+    /// `(e)`
+    Indirect(Expr),
+    /// `(e,X)`
+    IndirectX(Expr),
+    /// `(e),Y`
+    IndirectY(Expr),
+    /// `e,e`, the zero page address and target of BBR and BBS
+    Pair(Expr, Expr),
+}
+
+impl Operand {
+    fn first(&self) -> Option<&Expr> {
+        match self {
+            Operand::None | Operand::Accumulator => None,
+            Operand::Immediate(expr)
+            | Operand::Direct(expr)
+            | Operand::IndexedX(expr)
+            | Operand::IndexedY(expr)
+            | Operand::Indirect(expr)
+            | Operand::IndirectX(expr)
+            | Operand::IndirectY(expr)
+            | Operand::Pair(expr, _) => Some(expr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BinaryOp {
+    Or,
+    Xor,
+    And,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum UnaryOp {
+    Negate,
+    Not,
+    Low,
+    High,
+}
+
+enum Expr {
+    Number(i64),
+    Symbol(String),
+    /// `*`
+    Pc,
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+enum EvalError {
+    Undefined(String),
+    DivisionByZero,
+}
+
+impl Expr {
+    fn evaluate(&self, symbols: &BTreeMap<String, Word>, pc: Word) -> Result<i64, EvalError> {
+        Ok(match self {
+            Expr::Number(value) => *value,
+            Expr::Symbol(name) => match symbols.get(name) {
+                Some(value) => *value as i64,
+                None => return Err(EvalError::Undefined(name.clone())),
+            },
+            Expr::Pc => pc as i64,
+            Expr::Unary(op, expr) => {
+                let value = expr.evaluate(symbols, pc)?;
+
+                match op {
+                    UnaryOp::Negate => -value,
+                    UnaryOp::Not => !value,
+                    UnaryOp::Low => value & 0xFF,
+                    UnaryOp::High => (value >> 8) & 0xFF,
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = left.evaluate(symbols, pc)?;
+                let right = right.evaluate(symbols, pc)?;
+
+                match op {
+                    BinaryOp::Or => left | right,
+                    BinaryOp::Xor => left ^ right,
+                    BinaryOp::And => left & right,
+                    BinaryOp::ShiftLeft => left.wrapping_shl(right as u32),
+                    BinaryOp::ShiftRight => left.wrapping_shr(right as u32),
+                    BinaryOp::Add => left.wrapping_add(right),
+                    BinaryOp::Subtract => left.wrapping_sub(right),
+                    BinaryOp::Multiply => left.wrapping_mul(right),
+                    BinaryOp::Divide => left.checked_div(right).ok_or(EvalError::DivisionByZero)?,
+                }
+            }
+        })
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<Line>, AsmError> {
+    let mut lines = Vec::new();
+    // the global label local labels belong to
+    let mut scope = String::new();
+
+    for (index, text) in source.lines().enumerate() {
+        let number = index + 1;
+        let syntax = |message: String| AsmError::Syntax { line: number, message };
+
+        let mut text = strip_comment(text).trim();
+        let mut label = None;
+
+        if let Some(colon) = text.find(':') {
+            if is_identifier(&text[..colon]) {
+                label = Some(qualify(&text[..colon], &mut scope));
+                text = text[colon + 1..].trim();
+            }
+        }
+
+        let statement = if text.is_empty() {
+            Statement::Empty
+        } else if let Some((name, expr)) = text.split_once('=').filter(|(name, _)| is_identifier(name.trim())) {
+            let name = qualify(name.trim(), &mut scope);
+            Statement::Assign(name, parse_expr(expr, &scope).map_err(syntax)?)
+        } else if let Some(directive) = text.strip_prefix('.') {
+            let (name, arguments) = directive.split_once(char::is_whitespace).unwrap_or((directive, ""));
+            let arguments = split_commas(arguments);
+
+            match name.to_ascii_lowercase().as_str() {
+                "org" if arguments.len() == 1 => Statement::Org(parse_expr(arguments[0], &scope).map_err(syntax)?),
+                "byte" => Statement::Bytes(
+                    arguments
+                        .iter()
+                        .map(|argument| match argument.strip_prefix('"').and_then(|text| text.strip_suffix('"')) {
+                            Some(text) => Ok(Datum::Text(text.as_bytes().to_vec())),
+                            None => parse_expr(argument, &scope).map(Datum::Expr),
+                        })
+                        .collect::<Result<_, _>>()
+                        .map_err(syntax)?,
+                ),
+                "word" => Statement::Words(
+                    arguments.iter().map(|argument| parse_expr(argument, &scope)).collect::<Result<_, _>>().map_err(syntax)?,
+                ),
+                _ => return Err(syntax(format!("unknown directive .{}", name))),
+            }
+        } else {
+            let (mnemonic, operand) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+
+            Statement::Instruction {
+                mnemonic: mnemonic.to_ascii_uppercase(),
+                operand: parse_operand(operand.trim(), &scope).map_err(syntax)?,
+            }
+        };
+
+        lines.push(Line { number, label, statement });
+    }
+
+    Ok(lines)
+}
+
+/// Turns a local label into `global@local`, or makes a global label the new scope
+fn qualify(name: &str, scope: &mut String) -> String {
+    if name.starts_with('@') {
+        format!("{}{}", scope, name)
+    } else {
+        *scope = name.to_string();
+        name.to_string()
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    let text = text.strip_prefix('@').unwrap_or(text);
+    let mut chars = text.chars();
+
+    matches!(chars.next(), Some(first) if first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn strip_comment(text: &str) -> &str {
+    let bytes = text.as_bytes();
+    let mut in_string = false;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'"' => in_string = !in_string,
+            // skip the character of a 'c' literal, it may be a ; or a quote
+            b'\'' if !in_string => index += 2,
+            b';' if !in_string => return &text[..index],
+            _ => (),
+        }
+
+        index += 1;
+    }
+
+    text
+}
+
+/// Splits on the commas that are not inside parentheses, strings or character literals
+fn split_commas(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut start = 0;
+    let mut index = 0;
+
+    if text.trim().is_empty() {
+        return parts;
+    }
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'"' => in_string = !in_string,
+            b'\'' if !in_string => index += 2,
+            b'(' if !in_string => depth += 1,
+            b')' if !in_string => depth -= 1,
+            b',' if !in_string && depth == 0 => {
+                parts.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => (),
+        }
+
+        index += 1;
+    }
+
+    parts.push(text[start..].trim());
+    parts
+}
+
+fn parse_operand(text: &str, scope: &str) -> Result<Operand, String> {
+    if text.is_empty() {
+        return Ok(Operand::None);
+    }
+
+    if text.eq_ignore_ascii_case("A") {
+        return Ok(Operand::Accumulator);
+    }
+
+    if let Some(expr) = text.strip_prefix('#') {
+        return Ok(Operand::Immediate(parse_expr(expr, scope)?));
+    }
+
+    if text.starts_with('(') {
+        if let Some(close) = matching_paren(text) {
+            let inner = &text[1..close];
+            let rest = text[close + 1..].trim();
+
+            if rest.is_empty() {
+                return match split_commas(inner)[..] {
+                    [address] => Ok(Operand::Indirect(parse_expr(address, scope)?)),
+                    [address, index] if index.eq_ignore_ascii_case("X") => {
+                        Ok(Operand::IndirectX(parse_expr(address, scope)?))
+                    }
+                    _ => Err(format!("invalid operand {}", text)),
+                };
+            }
+
+            if let Some(index) = rest.strip_prefix(',') {
+                if index.trim().eq_ignore_ascii_case("Y") {
+                    return Ok(Operand::IndirectY(parse_expr(inner, scope)?));
+                }
+            }
+        }
+    }
+
+    match split_commas(text)[..] {
+        [address] => Ok(Operand::Direct(parse_expr(address, scope)?)),
+        [address, index] if index.eq_ignore_ascii_case("X") => Ok(Operand::IndexedX(parse_expr(address, scope)?)),
+        [address, index] if index.eq_ignore_ascii_case("Y") => Ok(Operand::IndexedY(parse_expr(address, scope)?)),
+        [address, target] => Ok(Operand::Pair(parse_expr(address, scope)?, parse_expr(target, scope)?)),
+        _ => Err(format!("invalid operand {}", text)),
+    }
+}
+
+/// Index of the parenthesis closing the one `text` starts with
+fn matching_paren(text: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+fn parse_expr(text: &str, scope: &str) -> Result<Expr, String> {
+    let mut parser = ExprParser { text: text.as_bytes(), position: 0, scope };
+    let expr = parser.binary(0)?;
+
+    parser.skip_spaces();
+    match parser.text.get(parser.position) {
+        None => Ok(expr),
+        Some(_) => Err(format!("unexpected `{}` in expression", &text[parser.position..])),
+    }
+}
+
+/// Binary operators from the loosest to the tightest binding
+const PRECEDENCE: &[&[(&str, BinaryOp)]] = &[
+    &[("|", BinaryOp::Or)],
+    &[("^", BinaryOp::Xor)],
+    &[("&", BinaryOp::And)],
+    &[("<<", BinaryOp::ShiftLeft), (">>", BinaryOp::ShiftRight)],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+    &[("*", BinaryOp::Multiply), ("/", BinaryOp::Divide)],
+];
+
+struct ExprParser<'a> {
+    text: &'a [u8],
+    position: usize,
+    scope: &'a str,
+}
+
+impl ExprParser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.text.get(self.position).is_some_and(u8::is_ascii_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_spaces();
+
+        if self.text[self.position..].starts_with(token.as_bytes()) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        let Some(operators) = PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+
+        let mut left = self.binary(level + 1)?;
+
+        'operators: loop {
+            for (token, op) in operators.iter() {
+                if self.eat(token) {
+                    let right = self.binary(level + 1)?;
+                    left = Expr::Binary(*op, Box::new(left), Box::new(right));
+                    continue 'operators;
+                }
+            }
+
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        for (token, op) in [("-", UnaryOp::Negate), ("~", UnaryOp::Not), ("<", UnaryOp::Low), (">", UnaryOp::High)] {
+            if self.eat(token) {
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        self.skip_spaces();
+
+        let start = self.position;
+        let Some(&first) = self.text.get(start) else {
+            return Err("missing expression".to_string());
+        };
+
+        match first {
+            b'(' => {
+                self.position += 1;
+                let expr = self.binary(0)?;
+
+                if !self.eat(")") {
+                    return Err("missing )".to_string());
+                }
+
+                Ok(expr)
+            }
+            b'*' => {
+                self.position += 1;
+                Ok(Expr::Pc)
+            }
+            b'\'' => match self.text.get(start + 1..start + 3) {
+                Some(&[c, b'\'']) => {
+                    self.position += 3;
+                    Ok(Expr::Number(c as i64))
+                }
+                _ => Err("invalid character literal".to_string()),
+            },
+            b'$' => self.number(16, 1),
+            b'%' => self.number(2, 1),
+            b'0'..=b'9' => self.number(10, 0),
+            b'@' | b'_' | b'a'..=b'z' | b'A'..=b'Z' => {
+                self.position += 1;
+                while self.text.get(self.position).is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_') {
+                    self.position += 1;
+                }
+
+                let name = String::from_utf8_lossy(&self.text[start..self.position]);
+                if name.starts_with('@') {
+                    Ok(Expr::Symbol(format!("{}{}", self.scope, name)))
+                } else {
+                    Ok(Expr::Symbol(name.into_owned()))
+                }
+            }
+            _ => Err(format!("unexpected `{}` in expression", first as char)),
+        }
+    }
+
+    /// Parses digits in `radix` after skipping a `prefix` long prefix
+    fn number(&mut self, radix: u32, prefix: usize) -> Result<Expr, String> {
+        let start = self.position + prefix;
+        let mut end = start;
+
+        while self.text.get(end).is_some_and(|c| (*c as char).is_digit(radix)) {
+            end += 1;
+        }
+
+        let digits = std::str::from_utf8(&self.text[start..end]).unwrap();
+        let value = i64::from_str_radix(digits, radix).map_err(|_| format!("invalid number `{}`", digits))?;
+
+        self.position = end;
+        Ok(Expr::Number(value))
+    }
+}
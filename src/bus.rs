@@ -0,0 +1,138 @@
+use std::fmt;
+
+use crate::snapshot::SnapshotError;
+use crate::{Byte, Word};
+
+/// Everything the CPU can see on its address bus.
+///
+/// `read` and `write` are called once per memory access the CPU makes, so an
+/// implementation can react to them (clear an interrupt flag when a status
+/// register is read, latch a value when an I/O port is written, ...).\
+/// The CPU makes one access on every cycle, so counting them is also how a
+/// device keeps time with it. There is no separate per-cycle callback.\
+/// `peek` must return the same value `read` would without any of those side
+/// effects, it is meant for debuggers and other tools looking at memory.
+pub trait Bus {
+    fn read(&mut self, address: Word) -> Byte;
+
+    fn write(&mut self, address: Word, value: Byte);
+
+    fn peek(&self, address: Word) -> Byte;
+
+    /// Changes what `peek` returns at `address` without any of the side effects of `write`, used by
+    /// `CPU::step_back` to undo writes. The default goes through `write`, which is only right for
+    /// buses where writing has no side effects
+    fn poke(&mut self, address: Word, value: Byte) {
+        self.write(address, value);
+    }
+
+    /// Called by the CPU after every instruction, returning a fault stops execution with
+    /// `EmulatorError::BusFault`
+    fn take_fault(&mut self) -> Option<BusFault> {
+        None
+    }
+
+    /// Whatever is needed to put the bus back in its current state with `load_state`, used by
+    /// `snapshot::Snapshot`. The default suits buses without state
+    fn save_state(&self) -> Vec<Byte> {
+        Vec::new()
+    }
+
+    /// Restores a state returned by `save_state`
+    fn load_state(&mut self, state: &[Byte]) -> Result<(), SnapshotError> {
+        if state.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotError::Mismatch { message: "the bus has no state to restore".to_string() })
+        }
+    }
+}
+
+/// A bus access that should not have happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BusFault {
+    /// `value` was written to read-only memory at `address`
+    ReadOnly { address: Word, value: Byte },
+    /// A device refused the access at `address`
+    Device { address: Word, reason: String },
+}
+
+impl fmt::Display for BusFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusFault::ReadOnly { address, value } => {
+                write!(f, "write of ${:02X} to read-only ${:04X}", value, address)
+            }
+            BusFault::Device { address, reason } => write!(f, "${:04X}: {}", address, reason),
+        }
+    }
+}
+
+/// Direction of a bus access
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// One cycle of bus activity, as recorded by `LoggingBus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusCycle {
+    pub address: Word,
+    pub value: Byte,
+    pub access: Access,
+}
+
+/// Wraps another bus and records every read and write going through it, in order.\
+/// Since the CPU makes exactly one access per cycle the log is a cycle by cycle trace
+pub struct LoggingBus<B: Bus> {
+    pub inner: B,
+    pub log: Vec<BusCycle>,
+}
+
+impl<B: Bus> LoggingBus<B> {
+    pub fn new(inner: B) -> Self {
+        LoggingBus { inner, log: Vec::new() }
+    }
+
+    /// Returns the accesses recorded since the last call
+    pub fn take_log(&mut self) -> Vec<BusCycle> {
+        std::mem::take(&mut self.log)
+    }
+}
+
+impl<B: Bus> Bus for LoggingBus<B> {
+    fn read(&mut self, address: Word) -> Byte {
+        let value = self.inner.read(address);
+        self.log.push(BusCycle { address, value, access: Access::Read });
+
+        value
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        self.inner.write(address, value);
+        self.log.push(BusCycle { address, value, access: Access::Write });
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        self.inner.peek(address)
+    }
+
+    /// Not logged, the CPU never makes this access
+    fn poke(&mut self, address: Word, value: Byte) {
+        self.inner.poke(address, value)
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.inner.take_fault()
+    }
+
+    /// The state of the inner bus, the log is not part of it
+    fn save_state(&self) -> Vec<Byte> {
+        self.inner.save_state()
+    }
+
+    fn load_state(&mut self, state: &[Byte]) -> Result<(), SnapshotError> {
+        self.inner.load_state(state)
+    }
+}
@@ -261,4 +261,252 @@ pub const BRK: Byte = 0x00;
 pub const NOP: Byte = 0xEA;
 
 // Return from Interrupt
-pub const RTI: Byte = 0x40;
\ No newline at end of file
+pub const RTI: Byte = 0x40;
+
+// Undocumented opcodes, only executed by NMOS chips when `CPU::undocumented` is set
+
+// Shift Left then OR with Accumulator
+pub const SLO_ZP: Byte = 0x07;
+pub const SLO_ZPX: Byte = 0x17;
+pub const SLO_ABS: Byte = 0x0F;
+pub const SLO_ABSX: Byte = 0x1F;
+pub const SLO_ABSY: Byte = 0x1B;
+pub const SLO_INDX: Byte = 0x03;
+pub const SLO_INDY: Byte = 0x13;
+
+// Rotate Left then AND with Accumulator
+pub const RLA_ZP: Byte = 0x27;
+pub const RLA_ZPX: Byte = 0x37;
+pub const RLA_ABS: Byte = 0x2F;
+pub const RLA_ABSX: Byte = 0x3F;
+pub const RLA_ABSY: Byte = 0x3B;
+pub const RLA_INDX: Byte = 0x23;
+pub const RLA_INDY: Byte = 0x33;
+
+// Shift Right then EOR with Accumulator
+pub const SRE_ZP: Byte = 0x47;
+pub const SRE_ZPX: Byte = 0x57;
+pub const SRE_ABS: Byte = 0x4F;
+pub const SRE_ABSX: Byte = 0x5F;
+pub const SRE_ABSY: Byte = 0x5B;
+pub const SRE_INDX: Byte = 0x43;
+pub const SRE_INDY: Byte = 0x53;
+
+// Rotate Right then Add with Carry
+pub const RRA_ZP: Byte = 0x67;
+pub const RRA_ZPX: Byte = 0x77;
+pub const RRA_ABS: Byte = 0x6F;
+pub const RRA_ABSX: Byte = 0x7F;
+pub const RRA_ABSY: Byte = 0x7B;
+pub const RRA_INDX: Byte = 0x63;
+pub const RRA_INDY: Byte = 0x73;
+
+// Store Accumulator AND X Register
+pub const SAX_ZP: Byte = 0x87;
+pub const SAX_ZPY: Byte = 0x97;
+pub const SAX_ABS: Byte = 0x8F;
+pub const SAX_INDX: Byte = 0x83;
+
+// Load Accumulator and X Register
+pub const LAX_ZP: Byte = 0xA7;
+pub const LAX_ZPY: Byte = 0xB7;
+pub const LAX_ABS: Byte = 0xAF;
+pub const LAX_ABSY: Byte = 0xBF;
+pub const LAX_INDX: Byte = 0xA3;
+pub const LAX_INDY: Byte = 0xB3;
+
+// Decrement Memory then Compare
+pub const DCP_ZP: Byte = 0xC7;
+pub const DCP_ZPX: Byte = 0xD7;
+pub const DCP_ABS: Byte = 0xCF;
+pub const DCP_ABSX: Byte = 0xDF;
+pub const DCP_ABSY: Byte = 0xDB;
+pub const DCP_INDX: Byte = 0xC3;
+pub const DCP_INDY: Byte = 0xD3;
+
+// Increment Memory then Subtract with Carry
+pub const ISC_ZP: Byte = 0xE7;
+pub const ISC_ZPX: Byte = 0xF7;
+pub const ISC_ABS: Byte = 0xEF;
+pub const ISC_ABSX: Byte = 0xFF;
+pub const ISC_ABSY: Byte = 0xFB;
+pub const ISC_INDX: Byte = 0xE3;
+pub const ISC_INDY: Byte = 0xF3;
+
+// AND then copy Negative to Carry
+pub const ANC_IM: Byte = 0x0B;
+pub const ANC_IM_2B: Byte = 0x2B;
+
+// AND then Logical Shift Right
+pub const ALR_IM: Byte = 0x4B;
+
+// AND then Rotate Right
+pub const ARR_IM: Byte = 0x6B;
+
+// Subtract from Accumulator AND X Register into X Register
+pub const SBX_IM: Byte = 0xCB;
+
+// Subtract with Carry, same as SBC_IM
+pub const USBC_IM: Byte = 0xEB;
+
+// AND X Register and operand into Accumulator, unstable
+pub const ANE_IM: Byte = 0x8B;
+
+// Load Accumulator and X Register with operand, unstable
+pub const LXA_IM: Byte = 0xAB;
+
+// AND memory with Stack Pointer into Accumulator, X Register and Stack Pointer
+pub const LAS_ABSY: Byte = 0xBB;
+
+// Store Accumulator AND X Register into Stack Pointer and memory, unstable
+pub const TAS_ABSY: Byte = 0x9B;
+
+// Store Accumulator AND X Register AND high byte + 1, unstable
+pub const SHA_ABSY: Byte = 0x9F;
+pub const SHA_INDY: Byte = 0x93;
+
+// Store X Register AND high byte + 1, unstable
+pub const SHX_ABSY: Byte = 0x9E;
+
+// Store Y Register AND high byte + 1, unstable
+pub const SHY_ABSX: Byte = 0x9C;
+
+// No Operation, reading an operand
+pub const NOP_IMP_1A: Byte = 0x1A;
+pub const NOP_IMP_3A: Byte = 0x3A;
+pub const NOP_IMP_5A: Byte = 0x5A;
+pub const NOP_IMP_7A: Byte = 0x7A;
+pub const NOP_IMP_DA: Byte = 0xDA;
+pub const NOP_IMP_FA: Byte = 0xFA;
+pub const NOP_IM_80: Byte = 0x80;
+pub const NOP_IM_82: Byte = 0x82;
+pub const NOP_IM_89: Byte = 0x89;
+pub const NOP_IM_C2: Byte = 0xC2;
+pub const NOP_IM_E2: Byte = 0xE2;
+pub const NOP_ZP_04: Byte = 0x04;
+pub const NOP_ZP_44: Byte = 0x44;
+pub const NOP_ZP_64: Byte = 0x64;
+pub const NOP_ZPX_14: Byte = 0x14;
+pub const NOP_ZPX_34: Byte = 0x34;
+pub const NOP_ZPX_54: Byte = 0x54;
+pub const NOP_ZPX_74: Byte = 0x74;
+pub const NOP_ZPX_D4: Byte = 0xD4;
+pub const NOP_ZPX_F4: Byte = 0xF4;
+pub const NOP_ABS_0C: Byte = 0x0C;
+pub const NOP_ABSX_1C: Byte = 0x1C;
+pub const NOP_ABSX_3C: Byte = 0x3C;
+pub const NOP_ABSX_5C: Byte = 0x5C;
+pub const NOP_ABSX_7C: Byte = 0x7C;
+pub const NOP_ABSX_DC: Byte = 0xDC;
+pub const NOP_ABSX_FC: Byte = 0xFC;
+
+// Halts the CPU until reset
+pub const JAM_02: Byte = 0x02;
+pub const JAM_12: Byte = 0x12;
+pub const JAM_22: Byte = 0x22;
+pub const JAM_32: Byte = 0x32;
+pub const JAM_42: Byte = 0x42;
+pub const JAM_52: Byte = 0x52;
+pub const JAM_62: Byte = 0x62;
+pub const JAM_72: Byte = 0x72;
+pub const JAM_92: Byte = 0x92;
+pub const JAM_B2: Byte = 0xB2;
+pub const JAM_D2: Byte = 0xD2;
+pub const JAM_F2: Byte = 0xF2;
+
+// 65C02 opcodes, most of them reuse slots of undocumented NMOS opcodes
+
+// Branch Always
+pub const BRA: Byte = 0x80;
+
+// Push X Register / Push Y Register
+pub const PHX: Byte = 0xDA;
+pub const PHY: Byte = 0x5A;
+
+// Pull X Register / Pull Y Register
+pub const PLX: Byte = 0xFA;
+pub const PLY: Byte = 0x7A;
+
+// Store Zero
+pub const STZ_ZP: Byte = 0x64;
+pub const STZ_ZPX: Byte = 0x74;
+pub const STZ_ABS: Byte = 0x9C;
+pub const STZ_ABSX: Byte = 0x9E;
+
+// Test and Reset Bits
+pub const TRB_ZP: Byte = 0x14;
+pub const TRB_ABS: Byte = 0x1C;
+
+// Test and Set Bits
+pub const TSB_ZP: Byte = 0x04;
+pub const TSB_ABS: Byte = 0x0C;
+
+// Zero page indirect addressing
+pub const ORA_ZPIND: Byte = 0x12;
+pub const AND_ZPIND: Byte = 0x32;
+pub const EOR_ZPIND: Byte = 0x52;
+pub const ADC_ZPIND: Byte = 0x72;
+pub const STA_ZPIND: Byte = 0x92;
+pub const LDA_ZPIND: Byte = 0xB2;
+pub const CMP_ZPIND: Byte = 0xD2;
+pub const SBC_ZPIND: Byte = 0xF2;
+
+// Increment Accumulator / Decrement Accumulator
+pub const INC_A: Byte = 0x1A;
+pub const DEC_A: Byte = 0x3A;
+
+// Bit Test, extra addressing modes
+pub const BIT_IM: Byte = 0x89;
+pub const BIT_ZPX: Byte = 0x34;
+pub const BIT_ABSX: Byte = 0x3C;
+
+// Jump, absolute indexed indirect
+pub const JMP_INDX: Byte = 0x7C;
+
+// Rockwell and WDC 65C02 opcodes
+
+// Reset Memory Bit
+pub const RMB0: Byte = 0x07;
+pub const RMB1: Byte = 0x17;
+pub const RMB2: Byte = 0x27;
+pub const RMB3: Byte = 0x37;
+pub const RMB4: Byte = 0x47;
+pub const RMB5: Byte = 0x57;
+pub const RMB6: Byte = 0x67;
+pub const RMB7: Byte = 0x77;
+
+// Set Memory Bit
+pub const SMB0: Byte = 0x87;
+pub const SMB1: Byte = 0x97;
+pub const SMB2: Byte = 0xA7;
+pub const SMB3: Byte = 0xB7;
+pub const SMB4: Byte = 0xC7;
+pub const SMB5: Byte = 0xD7;
+pub const SMB6: Byte = 0xE7;
+pub const SMB7: Byte = 0xF7;
+
+// Branch on Bit Reset
+pub const BBR0: Byte = 0x0F;
+pub const BBR1: Byte = 0x1F;
+pub const BBR2: Byte = 0x2F;
+pub const BBR3: Byte = 0x3F;
+pub const BBR4: Byte = 0x4F;
+pub const BBR5: Byte = 0x5F;
+pub const BBR6: Byte = 0x6F;
+pub const BBR7: Byte = 0x7F;
+
+// Branch on Bit Set
+pub const BBS0: Byte = 0x8F;
+pub const BBS1: Byte = 0x9F;
+pub const BBS2: Byte = 0xAF;
+pub const BBS3: Byte = 0xBF;
+pub const BBS4: Byte = 0xCF;
+pub const BBS5: Byte = 0xDF;
+pub const BBS6: Byte = 0xEF;
+pub const BBS7: Byte = 0xFF;
+
+// Wait for Interrupt, WDC only
+pub const WAI: Byte = 0xCB;
+
+// Stop the Processor, WDC only
+pub const STP: Byte = 0xDB;
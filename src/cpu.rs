@@ -1,12 +1,18 @@
 use bitflags::bitflags;
-use crate::consts::LDA_INDY;
+use crate::bus::Bus;
+use crate::debug::{Debugger, Watch};
+use crate::error::EmulatorError;
+use crate::journal::Journal;
+use crate::snapshot::CpuState;
+use crate::trace::{TraceEvent, Tracer};
 use crate::{Byte, Word};
 use crate::consts::*;
 
 
 bitflags! {
     // bit 5 is unused
-    #[derive(Default, Debug)]
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Status: u8 {
         const C = 0b00000001; // Carry Flag
         const Z = 0b00000010; // Zero Flag
@@ -85,6 +91,98 @@ impl Status {
     }
 }
 
+const NMI_VECTOR: Word = 0xFFFA;
+const RESET_VECTOR: Word = 0xFFFC;
+const IRQ_VECTOR: Word = 0xFFFE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    Irq,
+    Nmi,
+}
+
+/// What a single call to `CPU::step` did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepInfo {
+    /// For interrupts this is BRK, which the CPU forces in place of the next opcode
+    pub opcode: Byte,
+    /// Address the opcode was fetched from
+    pub address: Word,
+    /// Cycles the instruction took, page crossing penalties included
+    pub cycles: u32,
+    /// Whether indexing or a taken branch crossed a page boundary
+    pub page_crossed: bool,
+    /// Set when the step serviced an interrupt instead of executing an instruction
+    pub interrupt: Option<Interrupt>,
+}
+
+/// The programmer visible registers, see `CPU::registers` and `CPU::set_registers`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub pc: Word,
+    pub sp: Byte,
+    pub a: Byte,
+    pub x: Byte,
+    pub y: Byte,
+    /// Status register as PHP would push it, B and bit 5 are always set
+    pub p: Byte,
+}
+
+/// Settings for the undocumented NMOS opcodes, see `CPU::undocumented`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Undocumented {
+    /// ANE computes A = (A | magic) & X & operand, the constant depends on the chip and even its temperature
+    pub ane_magic: Byte,
+    /// LXA computes A = X = (A | magic) & operand
+    pub lxa_magic: Byte,
+}
+
+impl Default for Undocumented {
+    fn default() -> Self {
+        Undocumented { ane_magic: 0xEE, lxa_magic: 0xEE }
+    }
+}
+
+/// Which chip the CPU behaves like
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    /// The original MOS 6502, including its decimal mode flag quirks
+    #[default]
+    Nmos6502,
+    /// The CMOS 65C02, with extra instructions, a working `JMP ($xxFF)` and valid N and Z flags
+    /// after decimal mode arithmetic. Opcodes it does not define are NOPs
+    Cmos65C02,
+    /// The Rockwell R65C02, a 65C02 with the RMB, SMB, BBR and BBS bit instructions
+    Rockwell65C02,
+    /// The WDC W65C02S, a Rockwell R65C02 with WAI and STP
+    Wdc65C02,
+    /// The NMOS core of the NES, with the decimal mode circuitry cut out. SED and CLD still
+    /// change the flag but ADC and SBC always do binary arithmetic
+    Ricoh2A03,
+}
+
+impl Variant {
+    pub const fn is_cmos(self) -> bool {
+        matches!(self, Variant::Cmos65C02 | Variant::Rockwell65C02 | Variant::Wdc65C02)
+    }
+
+    /// Whether ADC and SBC honour the decimal flag
+    pub const fn has_decimal_mode(self) -> bool {
+        !matches!(self, Variant::Ricoh2A03)
+    }
+
+    /// Whether RMB, SMB, BBR and BBS are available
+    pub const fn has_bit_instructions(self) -> bool {
+        matches!(self, Variant::Rockwell65C02 | Variant::Wdc65C02)
+    }
+}
+
+/// Execution is stepped by instruction: `step` always runs a whole instruction, or a whole interrupt
+/// entry, and nothing stops the CPU in the middle of one.\
+/// Inside a step every cycle is exactly one `Bus::read` or `Bus::write`, dummy accesses included, in
+/// the order the chip makes them. A device that has to advance every cycle does it from those calls
 #[derive(Default)]
 pub struct CPU {
     pub pc: Word,   // Program Counter
@@ -93,2022 +191,1923 @@ pub struct CPU {
     pub x: Byte,    // Index Register X
     pub y: Byte,    // Index Register Y
     pub p: Status,  // Processor Status
+    pub cycles: u64, // Cycles elapsed since creation
+    pub variant: Variant,
+    /// Executes the undocumented opcodes of NMOS chips instead of failing with `EmulatorError::IllegalOpcode`
+    pub undocumented: Option<Undocumented>,
+    /// Called before every instruction, see `NestestLogger` for a ready made one
+    pub tracer: Option<Box<dyn Tracer>>,
+    /// Breakpoints, watchpoints and conditions that make `step` fail with `EmulatorError::DebugStop`
+    pub debugger: Option<Debugger>,
+    /// Records what every step changes so `step_back` can undo it
+    pub journal: Option<Journal>,
+
+    page_crossed: bool,
+    irq_line: bool,
+    nmi_pending: bool,
+    // interrupt disable flag seen by the next interrupt poll when it differs from the current one
+    delayed_interrupt_flag: Option<bool>,
+    // WAI was executed and no interrupt came in yet
+    waiting: bool,
+    // STP was executed, only a reset gets the CPU going again
+    stopped: bool,
 }
 
 impl CPU {
-    pub fn reset(&mut self, memory: &[u8]) {
-        self.pc = memory[0xFFFC] as u16 | ((memory[0xFFFD] as u16) << 8);
-        self.sp = 0xFF; // goes between 0x0100 and 0x1FF in stack
+    pub fn new(variant: Variant) -> Self {
+        CPU { variant, ..Default::default() }
     }
 
-    /// takes 1 cycle
-    fn fetch_byte(&mut self, cycles: &mut u32, memory: &mut [u8]) -> Byte {
-        let byte = memory[self.pc as usize];
-        self.pc += 1;
-        *cycles -= 1;
-        
-        byte
+    /// Puts the CPU in the state it has when the chip is powered up, then runs the reset sequence.\
+    /// A, X, Y and the flags are cleared and the stack pointer ends up at $FD
+    pub fn power_on<B: Bus>(&mut self, bus: &mut B) {
+        self.a = 0;
+        self.x = 0;
+        self.y = 0;
+        self.sp = 0;
+        self.p = Status::empty();
+        self.cycles = 0;
+        self.irq_line = false;
+
+        self.reset(bus);
     }
 
-    /// takes 2 cycles
-    fn fetch_word(&mut self, cycles: &mut u32, memory: &mut [u8]) -> Word {
-        let low_byte = memory[self.pc as usize];
-        self.pc += 1;
-        *cycles -= 1;
-
-        let high_byte = memory[self.pc as usize];
-        self.pc += 1;
-        *cycles -= 1;
-
-        // little endian
-        let word = ((high_byte as u16) << 8) | low_byte as u16;
-        
-        word
+    /// Same as `power_on` but A, X, Y, SP and the flags start with garbage generated from `seed`,
+    /// the way they do on real hardware
+    pub fn power_on_randomized<B: Bus>(&mut self, bus: &mut B, seed: u64) {
+        // xorshift64, never seeded with 0 since it would only produce zeroes
+        let mut state = (seed ^ 0x9E37_79B9_7F4A_7C15).max(1);
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 32) as Byte
+        };
+
+        self.a = next();
+        self.x = next();
+        self.y = next();
+        self.sp = next();
+        self.p = Status::from_bits_truncate(next());
+        self.cycles = 0;
+        self.irq_line = false;
+
+        self.reset(bus);
     }
 
-    /// `effective_address` refers to the physical memory location\
-    /// takes 1 cycle
-    fn read_memory(&mut self, cycles: &mut u32, memory: &mut [u8], effective_address: usize) -> Byte {
-        let byte= memory[effective_address];
-        *cycles -= 1;
+    /// Warm reset, what happens when the RESET line is pulled low.\
+    /// The sequence is an interrupt whose stack writes are turned into reads, so SP goes down by 3
+    /// without touching memory, then I is set and PC is loaded from $FFFC. A, X, Y and the other
+    /// flags keep their values. The journal is cleared, there is no stepping back past a reset\
+    /// takes 7 cycles
+    pub fn reset<B: Bus>(&mut self, bus: &mut B) {
+        if let Some(journal) = &mut self.journal {
+            journal.clear();
+        }
 
-        byte
-    }
+        self.read_memory(bus, self.pc);
+        self.read_memory(bus, self.pc);
 
-    /// `effective_address` refers to the physical memory location\
-    /// takes 2 cycles
-    fn read_word_memory(&mut self, cycles: &mut u32, memory: &mut [u8], effective_address: usize) -> Word {
-        let low_byte = self.read_memory(cycles, memory, effective_address as usize);
+        for _ in 0..3 {
+            self.read_memory(bus, 0x0100 | self.sp as Word);
+            self.sp = self.sp.wrapping_sub(1);
+        }
 
-        // todo: fix what happens if high byte is at effective address greater than allowed
-        let high_byte = self.read_memory(cycles, memory, effective_address as usize + 1);
+        self.p.set_interrupt(true);
+        self.nmi_pending = false;
+        self.delayed_interrupt_flag = None;
+        self.waiting = false;
+        self.stopped = false;
 
-        (low_byte as u16) | ((high_byte as u16) << 8)
+        self.pc = self.read_word_memory(bus, RESET_VECTOR);
     }
 
     /// takes 1 cycle
-    fn zero_page_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u8 {
-        self.fetch_byte(cycles, memory)
+    fn fetch_byte<B: Bus>(&mut self, bus: &mut B) -> Byte {
+        let byte = self.read_memory(bus, self.pc);
+        self.pc = self.pc.wrapping_add(1);
+
+        byte
     }
 
     /// takes 2 cycles
-    fn zero_page_x_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u8 {
-        let address= self.fetch_byte(cycles, memory);
-        let effective_address = (self.x as u16 + address as u16) % 256; // % 256 wraps around so that the max is a byte
-        *cycles -= 1;
+    fn fetch_word<B: Bus>(&mut self, bus: &mut B) -> Word {
+        let low_byte = self.fetch_byte(bus);
+        let high_byte = self.fetch_byte(bus);
 
-        effective_address as u8
+        // little endian
+        ((high_byte as u16) << 8) | low_byte as u16
     }
 
-    /// takes 2 cycles
-    fn zero_page_y_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u8 {
-        let address= self.fetch_byte(cycles, memory);
-        let effective_address = (self.y as u16 + address as u16) % 256; // % 256 wraps around so that the max is a byte
-        *cycles -= 1;
+    /// `effective_address` refers to the physical memory location\
+    /// takes 1 cycle
+    fn read_memory<B: Bus>(&mut self, bus: &mut B, effective_address: Word) -> Byte {
+        let byte = bus.read(effective_address);
+        self.cycles += 1;
+        self.watch(effective_address, Watch::READ, byte);
 
-        effective_address as u8
+        byte
     }
 
-    /// takes 2 cycles
-    fn absolute_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u16 {
-        self.fetch_word(cycles, memory)
-    }
+    /// `effective_address` refers to the physical memory location\
+    /// takes 1 cycle
+    fn write_memory<B: Bus>(&mut self, bus: &mut B, effective_address: Word, value: Byte) {
+        let old = self.journal.as_ref().map(|_| bus.peek(effective_address));
 
-    /// takes 2-3 cycles depending on if page was crossed
-    fn absolute_x_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u16 {
-        let address = self.fetch_word(cycles, memory);
+        bus.write(effective_address, value);
+        self.cycles += 1;
+        self.watch(effective_address, Watch::WRITE, value);
 
-        let effective_address = self.x as u16 + address;
+        if let (Some(journal), Some(old)) = (&mut self.journal, old) {
+            let new = bus.peek(effective_address);
 
-        // checks if page was crossed (high byte of word are the same)
-        if (address & 0xFF00) != (effective_address & 0xFF00) {
-            *cycles -= 1;
+            if new != old {
+                journal.record_write(effective_address, old, new);
+            }
         }
-
-        effective_address
     }
 
-    /// takes 2-3 cycles depending on if page was crossed
-    fn absolute_y_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u16 {
-        let address = self.fetch_word(cycles, memory);
+    /// Hands a bus access to the read and write watchpoints of the debugger, if there is one
+    fn watch(&mut self, address: Word, kind: Watch, value: Byte) {
+        if self.debugger.as_ref().is_some_and(|debugger| debugger.watches(address)) {
+            let registers = self.registers();
 
-        let effective_address = self.y as u16 + address;
-
-        // checks if page was crossed (high byte of word are the same)
-        if (address & 0xFF00) != (effective_address & 0xFF00) {
-            *cycles -= 1;
+            if let Some(debugger) = &mut self.debugger {
+                debugger.check_access(&registers, address, kind, value);
+            }
         }
-
-        effective_address
     }
 
-    /// takes 4 cycles
-    fn indirect_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u16 {
-        let effective_address = self.fetch_word(cycles, memory);
-
-        let effective_address= self.read_word_memory(cycles, memory, effective_address as usize);
+    /// `effective_address` refers to the physical memory location\
+    /// takes 2 cycles
+    fn read_word_memory<B: Bus>(&mut self, bus: &mut B, effective_address: Word) -> Word {
+        let low_byte = self.read_memory(bus, effective_address);
+        let high_byte = self.read_memory(bus, effective_address.wrapping_add(1));
 
-        effective_address
+        (low_byte as u16) | ((high_byte as u16) << 8)
     }
 
-    /// takes 4 cycles
-    fn indirect_x_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u16 {
-        let address = self.fetch_byte(cycles, memory);
-
-        let effective_address = address.wrapping_add(self.x);
-        *cycles -= 1;
+    /// Pointers in zero page wrap around inside it, a pointer at $FF takes its high byte from $00\
+    /// takes 2 cycles
+    fn read_zero_page_word<B: Bus>(&mut self, bus: &mut B, address: Byte) -> Word {
+        let low_byte = self.read_memory(bus, address as Word);
+        let high_byte = self.read_memory(bus, address.wrapping_add(1) as Word);
 
-        self.read_word_memory(cycles, memory, effective_address as usize)
+        (low_byte as u16) | ((high_byte as u16) << 8)
     }
 
-    /// takes 3-4 cycles depending on if page was crossed
-    fn indirect_y_addressing(&mut self, cycles: &mut u32, memory: &mut [u8]) -> u16 {
-        let effective_address = self.fetch_byte(cycles, memory);
-
-        let address = self.read_word_memory(cycles, memory, effective_address as usize);
-        let effective_address = address + self.y as u16;
-        
-        // crosses a page
-        if (address & 0xFF00) != (effective_address & 0xFF00) {
-            *cycles -= 1;
+    /// Reads the value at `effective_address`, writes it back unchanged while `operation` computes
+    /// the new value, then writes the new value. The 65C02 reads the value a second time instead
+    /// of writing it back\
+    /// takes 3 cycles
+    fn read_modify_write<B: Bus>(&mut self, bus: &mut B, effective_address: Word, operation: fn(&mut Self, Byte) -> Byte) {
+        let value = self.read_memory(bus, effective_address);
+
+        if self.variant.is_cmos() {
+            self.read_memory(bus, effective_address);
+        } else {
+            self.write_memory(bus, effective_address, value);
         }
 
-        effective_address
+        let value = operation(self, value);
+        self.write_memory(bus, effective_address, value);
     }
 
-    pub fn execute(&mut self, mut cycles: u32, memory: &mut [u8]) {
-        while cycles > 0 {
-            let instruction = self.fetch_byte(&mut cycles, memory);
-
-            println!("instruction: {:02X}, cycles left: {}", instruction, cycles + 1);
-            println!("A: {:04X}", self.a);
-            println!("X: {:04X}", self.x);
-            println!("Y: {:04X}", self.y);
-            println!("flags: {:08b}", self.p.bits());
-
-            match instruction {
-                LDA_IM => {
-                    self.a = self.fetch_byte(&mut cycles, memory);
-                    self.set_lda_flags();
-                }
-                LDA_ZP | LDA_ZPX | LDA_ABS | LDA_ABSX | LDA_ABSY | LDA_INDX | LDA_INDY => {
-                    let effective_address = match instruction {
-                        LDA_ZP => self.zero_page_addressing(&mut cycles, memory) as usize,
-                        LDA_ZPX => self.zero_page_x_addressing(&mut cycles, memory) as usize,
-                        LDA_ABS => self.absolute_addressing(&mut cycles, memory) as usize,
-                        LDA_ABSX => self.absolute_x_addressing(&mut cycles, memory) as usize,
-                        LDA_ABSY => self.absolute_y_addressing(&mut cycles, memory) as usize,
-                        LDA_INDX => self.indirect_x_addressing(&mut cycles, memory) as usize,
-                        LDA_INDY => self.indirect_y_addressing(&mut cycles, memory) as usize,
-                        _ => panic!("Unexpected LDA instruction"),
-                    };
-
-                    self.a = self.read_memory(&mut cycles, memory, effective_address);
-
-                    self.set_lda_flags();
-                }
-                LDX_IM => {
-                    self.x = self.fetch_byte(&mut cycles, memory);
-
-                    self.set_ldx_flags();
-                }
-                LDX_ZP | LDX_ZPY | LDX_ABS | LDX_ABSY => {
-                    let effective_address = match instruction {
-                        LDX_ZP => self.zero_page_addressing(&mut cycles, memory) as usize,
-                        LDX_ZPY => self.zero_page_y_addressing(&mut cycles, memory) as usize,
-                        LDX_ABS => self.absolute_addressing(&mut cycles, memory) as usize,
-                        LDX_ABSY => self.absolute_y_addressing(&mut cycles, memory) as usize,
-                        _ => panic!("Unexpected LDX instruction"),
-                    };
-                    self.x = self.read_memory(&mut cycles, memory, effective_address);
-
-                    self.set_ldx_flags();
-                }
-                LDY_IM => {
-                    self.y = self.fetch_byte(&mut cycles, memory);
-
-                    self.set_ldy_flags();
-                }
-                LDY_ZP | LDY_ZPX | LDY_ABS | LDY_ABSX => {
-                    let effective_address = match instruction {
-                        LDY_ZP => self.zero_page_addressing(&mut cycles, memory) as usize,
-                        LDY_ZPX => self.zero_page_x_addressing(&mut cycles, memory) as usize,
-                        LDY_ABS => self.absolute_addressing(&mut cycles, memory) as usize,
-                        LDY_ABSX => self.absolute_x_addressing(&mut cycles, memory) as usize,
-                        _ => panic!("Unexpected LDY instruction"),
-                    };
-                    self.y = self.read_memory(&mut cycles, memory, effective_address);
-
-                    self.set_ldy_flags();
-                }
-                STA_ZP | STA_ZPX | STA_ABS | STA_ABSX | STA_ABSY | STA_INDX | STA_INDY => {
-                    let effective_address= match instruction {
-                        STA_ZP => self.zero_page_addressing(&mut cycles, memory) as usize,
-                        STA_ZPX => self.zero_page_x_addressing(&mut cycles, memory) as usize,
-                        STA_ABS => self.absolute_addressing(&mut cycles, memory) as usize,
-                        STA_ABSX => self.absolute_x_addressing(&mut cycles, memory) as usize,
-                        STA_ABSY => self.absolute_y_addressing(&mut cycles, memory) as usize,
-                        STA_INDX => self.indirect_x_addressing(&mut cycles, memory) as usize,
-                        STA_INDY => self.indirect_y_addressing(&mut cycles, memory) as usize,
-                        _ => panic!("Unexpected STA instruction"),
-                    };
-
-                    memory[effective_address as usize] = self.a;
-                }
-                STX_ZP | STX_ZPY | STX_ABS => {
-                    let effective_address= match instruction {
-                        STX_ZP => self.zero_page_addressing(&mut cycles, memory) as usize,
-                        STX_ZPY => self.zero_page_y_addressing(&mut cycles, memory) as usize,
-                        STX_ABS => self.absolute_addressing(&mut cycles, memory) as usize,
-                        _ => panic!("Unexpected STX instruction"),
-                    };
-
-                    memory[effective_address] = self.x;
-                }
-                STY_ZP | STY_ZPX | STY_ABS => {
-                    let effective_address= match instruction {
-                        STY_ZP => self.zero_page_addressing(&mut cycles, memory) as usize,
-                        STY_ZPX => self.zero_page_x_addressing(&mut cycles, memory) as usize,
-                        STY_ABS => self.absolute_addressing(&mut cycles, memory) as usize,
-                        _ => panic!("Unexpected STY instruction"),
-                    };
-
-                    memory[effective_address] = self.x;
-                }
-                TAX => {
-                    self.x = self.a;
-                    cycles -= 1;
-
-                    self.p.set_zero(self.x == 0);
-            
-                    self.p.set_negative(self.x & 0b10000000 == 0b10000000);
-                }
-                TAY => {
-                    self.y = self.a;
-                    cycles -= 1;
-
-                    self.p.set_zero(self.y == 0);
-            
-                    self.p.set_negative(self.y & 0b10000000 == 0b10000000);
-                }
-                TXA => {
-                    self.a = self.x;
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);  
-                }
-                TYA => {
-                    self.a = self.y;
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                TSX => {
-                    self.x = self.sp;
-                    cycles -= 1;
-
-                    self.p.set_zero(self.x == 0);
-            
-                    self.p.set_negative(self.x & 0b10000000 == 0b10000000);
-                }
-                TXS => {
-                    self.sp = self.x;
-                    cycles -= 1;
-                }
-                PHA => {
-                    // Discarded OP CODE (due to cpu design) that will be used on next cycle
-                    cycles -= 1;
-
-                    memory[self.sp as usize] = self.a;
-                    self.sp -= 1;
-                    cycles -= 1;
-                }
-                PHP => {
-                    // Discarded OP CODE (due to cpu design) that will be used on next cycle
-                    cycles -= 1;
-
-                    memory[self.sp as usize] = self.p.bits();
-                    self.sp -= 1;
-                    cycles -= 1;
-                }
-                PLA => {
-                    // Discarded OP CODE (due to cpu design) that will be used on next cycle
-                    cycles -= 1;
-
-                    // Discarded Stack Pointer Fetch (due to cpu design)
-                    cycles -= 1;
-
-                    self.sp += 1;
-                    self.a = memory[self.sp as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);  
-                }
-                PLP => {
-                    // Discarded OP CODE (due to cpu design) that will be used on next cycle
-                    cycles -= 1;
-
-                    // Discarded Stack Pointer Fetch (due to cpu design)
-                    cycles -= 1;
-
-                    self.sp += 1;
-                    self.p = Status::from_bits(memory[self.sp as usize]).unwrap();
-                    cycles -= 1;
-                }
-                AND_IM => {
-                    self.a &= self.fetch_byte(&mut cycles, memory);
-                
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);  
-                }
-                AND_ZP => {
-                    let effectve_address = self.zero_page_addressing(&mut cycles, memory); 
-                    self.a &= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                AND_ZPX => {
-                    let effectve_address = self.zero_page_x_addressing(&mut cycles, memory); 
-                    self.a &= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                AND_ABS => {
-                    let effectve_address = self.absolute_addressing(&mut cycles, memory); 
-                    self.a &= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                AND_ABSX => {
-                    let effectve_address = self.absolute_x_addressing(&mut cycles, memory); 
-                    self.a &= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                AND_ABSY => {
-                    let effectve_address = self.absolute_y_addressing(&mut cycles, memory); 
-                    self.a &= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                AND_INDX => {
-                    let effectve_address = self.indirect_x_addressing(&mut cycles, memory); 
-                    self.a &= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                AND_INDY => {
-                    let effectve_address = self.indirect_y_addressing(&mut cycles, memory); 
-                    self.a &= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                EOR_IM => {
-                    self.a ^= self.fetch_byte(&mut cycles, memory);
-                
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);  
-                }
-                EOR_ZP => {
-                    let effectve_address = self.zero_page_addressing(&mut cycles, memory); 
-                    self.a ^= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                EOR_ZPX => {
-                    let effectve_address = self.zero_page_x_addressing(&mut cycles, memory); 
-                    self.a ^= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                EOR_ABS => {
-                    let effectve_address = self.absolute_addressing(&mut cycles, memory); 
-                    self.a ^= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                EOR_ABSX => {
-                    let effectve_address = self.absolute_x_addressing(&mut cycles, memory); 
-                    self.a ^= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                EOR_ABSY => {
-                    let effectve_address = self.absolute_y_addressing(&mut cycles, memory); 
-                    self.a ^= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                EOR_INDX => {
-                    let effectve_address = self.indirect_x_addressing(&mut cycles, memory); 
-                    self.a ^= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                EOR_INDY => {
-                    let effectve_address = self.indirect_y_addressing(&mut cycles, memory); 
-                    self.a ^= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                ORA_IM => {
-                    self.a |= self.fetch_byte(&mut cycles, memory);
-                
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);  
-                }
-                ORA_ZP => {
-                    let effectve_address = self.zero_page_addressing(&mut cycles, memory); 
-                    self.a |= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                ORA_ZPX => {
-                    let effectve_address = self.zero_page_x_addressing(&mut cycles, memory); 
-                    self.a |= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                ORA_ABS => {
-                    let effectve_address = self.absolute_addressing(&mut cycles, memory); 
-                    self.a |= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                ORA_ABSX => {
-                    let effectve_address = self.absolute_x_addressing(&mut cycles, memory); 
-                    self.a |= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                ORA_ABSY => {
-                    let effectve_address = self.absolute_y_addressing(&mut cycles, memory); 
-                    self.a |= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                ORA_INDX => {
-                    let effectve_address = self.indirect_x_addressing(&mut cycles, memory); 
-                    self.a |= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                ORA_INDY => {
-                    let effectve_address = self.indirect_y_addressing(&mut cycles, memory); 
-                    self.a |= memory[effectve_address as usize];
-                    cycles -= 1;
-
-                    self.p.set_zero(self.a == 0);
-            
-                    self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-                }
-                BIT_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let bit_test = self.a & memory[effective_address as usize];
-                    cycles -= 1;
-                    
-                    self.p.set_zero(bit_test == 0);
-
-                    self.p &= Status::from_bits(bit_test & 0b11000000).unwrap();
-                }
-                BIT_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let bit_test = self.a & memory[effective_address as usize];
-                    
-                    self.p.set_zero(bit_test == 0);
-
-                    self.p &= Status::from_bits(bit_test & 0b11000000).unwrap();
-                }
-                ADC_IM => {
-                    let byte = self.fetch_byte(&mut cycles, memory);
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                ADC_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let byte = memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                ADC_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
-                    let byte = memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                ADC_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let byte = memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                ADC_ABSX => {
-                    let effective_address = self.absolute_x_addressing(&mut cycles, memory);
-                    let byte = memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                ADC_ABSY => {
-                    let effective_address = self.absolute_y_addressing(&mut cycles, memory);
-                    let byte = memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                ADC_INDX => {
-                    let effective_address = self.indirect_x_addressing(&mut cycles, memory);
-                    let byte = memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                ADC_INDY => {
-                    let effective_address = self.indirect_y_addressing(&mut cycles, memory);
-                    let byte = memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                // Same as ADC but with bit negation on the byte from memory
-                SBC_IM => {
-                    let byte = !self.fetch_byte(&mut cycles, memory);
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                SBC_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let byte = !memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                SBC_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
-                    let byte = !memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
-
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
-
-                    self.a = a;
-
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                SBC_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let byte = !memory[effective_address as usize];
-                    cycles -= 1;
-
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
-
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
+    /// Stores `value` at the top of the stack, which lives in page 1 ($0100-$01FF)\
+    /// takes 1 cycle
+    fn push<B: Bus>(&mut self, bus: &mut B, value: Byte) {
+        self.write_memory(bus, 0x0100 | self.sp as Word, value);
+        self.sp = self.sp.wrapping_sub(1);
+    }
 
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
+    /// Removes the byte at the top of the stack, which lives in page 1 ($0100-$01FF)\
+    /// takes 1 cycle
+    fn pull<B: Bus>(&mut self, bus: &mut B) -> Byte {
+        self.sp = self.sp.wrapping_add(1);
 
-                    self.a = a;
+        self.read_memory(bus, 0x0100 | self.sp as Word)
+    }
 
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                SBC_ABSX => {
-                    let effective_address = self.absolute_x_addressing(&mut cycles, memory);
-                    let byte = !memory[effective_address as usize];
-                    cycles -= 1;
+    /// takes 1 cycle
+    fn zero_page_addressing<B: Bus>(&mut self, bus: &mut B) -> Word {
+        self.fetch_byte(bus) as Word
+    }
 
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
+    /// takes 2 cycles
+    fn zero_page_x_addressing<B: Bus>(&mut self, bus: &mut B) -> Word {
+        let address = self.fetch_byte(bus);
 
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
+        // Discarded read of the base address while X is added to it
+        self.read_memory(bus, address as Word);
 
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
+        address.wrapping_add(self.x) as Word
+    }
 
-                    self.a = a;
+    /// takes 2 cycles
+    fn zero_page_y_addressing<B: Bus>(&mut self, bus: &mut B) -> Word {
+        let address = self.fetch_byte(bus);
 
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                SBC_ABSY => {
-                    let effective_address = self.absolute_y_addressing(&mut cycles, memory);
-                    let byte = !memory[effective_address as usize];
-                    cycles -= 1;
+        // Discarded read of the base address while Y is added to it
+        self.read_memory(bus, address as Word);
 
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
+        address.wrapping_add(self.y) as Word
+    }
 
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
+    /// takes 2 cycles
+    fn absolute_addressing<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        self.fetch_word(bus)
+    }
 
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
+    /// takes 2-3 cycles depending on if page was crossed
+    fn absolute_x_addressing<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let address = self.fetch_word(bus);
 
-                    self.a = a;
+        self.index_address(bus, address, self.x, false)
+    }
 
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                SBC_INDX => {
-                    let effective_address = self.indirect_x_addressing(&mut cycles, memory);
-                    let byte = !memory[effective_address as usize];
-                    cycles -= 1;
+    /// Same as `absolute_x_addressing` for stores and read-modify-write instructions\
+    /// takes 3 cycles
+    fn absolute_x_addressing_write<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let address = self.fetch_word(bus);
 
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
+        self.index_address(bus, address, self.x, true)
+    }
 
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
+    /// takes 2-3 cycles depending on if page was crossed
+    fn absolute_y_addressing<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let address = self.fetch_word(bus);
 
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
+        self.index_address(bus, address, self.y, false)
+    }
 
-                    self.a = a;
+    /// Same as `absolute_y_addressing` for stores and read-modify-write instructions\
+    /// takes 3 cycles
+    fn absolute_y_addressing_write<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let address = self.fetch_word(bus);
 
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                SBC_INDY => {
-                    let effective_address = self.indirect_y_addressing(&mut cycles, memory);
-                    let byte = !memory[effective_address as usize];
-                    cycles -= 1;
+        self.index_address(bus, address, self.y, true)
+    }
 
-                    let (mut a, mut a_overflow) = self.a.overflowing_add(byte);
+    /// The NMOS chips do not carry into the high byte of the pointer, so `JMP ($xxFF)` takes the
+    /// high byte of the target from $xx00 instead of the next page\
+    /// takes 4 cycles
+    fn indirect_addressing<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let effective_address = self.fetch_word(bus);
 
-                    if self.p.carry_flag() {
-                        let (new_a, carry_overflow) = a.overflowing_add(1);
+        let low_byte = self.read_memory(bus, effective_address);
+        let high_byte = self.read_memory(bus, (effective_address & 0xFF00) | (effective_address.wrapping_add(1) & 0x00FF));
 
-                        a = new_a;
-                        a_overflow |= carry_overflow;
-                    }
+        (low_byte as u16) | ((high_byte as u16) << 8)
+    }
 
-                    self.a = a;
+    /// 65C02 only\
+    /// takes 3 cycles
+    fn zero_page_indirect_addressing<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let effective_address = self.fetch_byte(bus);
 
-                    self.set_adc_sbc_flags(a_overflow, byte);
-                }
-                CMP_IM => {
-                    let byte = self.fetch_byte(&mut cycles, memory);
+        self.read_zero_page_word(bus, effective_address)
+    }
 
-                    self.p.set_carry(self.a >= byte);
-                    
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+    /// takes 4 cycles
+    fn indirect_x_addressing<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let address = self.fetch_byte(bus);
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CMP_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+        // Discarded read of the pointer while X is added to it
+        self.read_memory(bus, address as Word);
 
-                    self.p.set_carry(self.a >= byte);
+        let effective_address = address.wrapping_add(self.x);
 
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+        self.read_zero_page_word(bus, effective_address)
+    }
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CMP_ZPX => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+    /// takes 3-4 cycles depending on if page was crossed
+    fn indirect_y_addressing<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let effective_address = self.fetch_byte(bus);
 
-                    self.p.set_carry(self.a >= byte);
+        let address = self.read_zero_page_word(bus, effective_address);
 
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+        self.index_address(bus, address, self.y, false)
+    }
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CMP_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+    /// Same as `indirect_y_addressing` for stores and read-modify-write instructions\
+    /// takes 4 cycles
+    fn indirect_y_addressing_write<B: Bus>(&mut self, bus: &mut B) -> u16 {
+        let effective_address = self.fetch_byte(bus);
 
-                    self.p.set_carry(self.a >= byte);
+        let address = self.read_zero_page_word(bus, effective_address);
 
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+        self.index_address(bus, address, self.y, true)
+    }
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CMP_ABSX => {
-                    let effective_address = self.absolute_x_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+    /// Adds `index` to `address`. The CPU first adds it to the low byte only and reads from that
+    /// address, which is the right one unless a page was crossed. Reads skip that cycle when no page
+    /// is crossed, writes always go through it since they cannot be undone\
+    /// takes 0-1 cycles
+    fn index_address<B: Bus>(&mut self, bus: &mut B, address: Word, index: Byte, write: bool) -> Word {
+        let effective_address = address.wrapping_add(index as Word);
 
-                    self.p.set_carry(self.a >= byte);
+        // checks if page was crossed (high byte of word are the same)
+        let page_crossed = (address & 0xFF00) != (effective_address & 0xFF00);
 
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+        if page_crossed {
+            self.page_crossed = true;
+        }
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CMP_ABSY => {
-                    let effective_address = self.absolute_y_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+        if page_crossed || write {
+            if self.variant.is_cmos() {
+                // Discarded read of the last operand byte
+                self.read_memory(bus, self.pc.wrapping_sub(1));
+            } else {
+                // Discarded read at the address with the high byte not fixed yet
+                self.read_memory(bus, (address & 0xFF00) | (effective_address & 0x00FF));
+            }
+        }
 
-                    self.p.set_carry(self.a >= byte);
+        effective_address
+    }
 
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+    /// Takes the branch when `condition` holds\
+    /// takes 1-3 cycles depending on if the branch was taken and if page was crossed
+    fn branch<B: Bus>(&mut self, bus: &mut B, condition: bool) {
+        let offset = self.fetch_byte(bus) as i8;
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CMP_INDX => {
-                    let effective_address = self.indirect_x_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+        if !condition {
+            return;
+        }
 
-                    self.p.set_carry(self.a >= byte);
+        // Discarded read of the next opcode while the offset is added
+        self.read_memory(bus, self.pc);
 
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+        let new_location = self.pc.wrapping_add(offset as u16);
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CMP_INDY => {
-                    let effective_address = self.indirect_y_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+        if self.pc & 0xFF00 != new_location & 0xFF00 {
+            self.page_crossed = true;
 
-                    self.p.set_carry(self.a >= byte);
+            // Discarded read at the address with the high byte not fixed yet
+            self.read_memory(bus, (self.pc & 0xFF00) | (new_location & 0x00FF));
+        }
 
-                    if self.a == byte {
-                        self.p.set_zero(true);
-                    }
+        self.pc = new_location;
+    }
 
-                    if self.a >= byte && ((self.a - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CPX_IM => {
-                    let byte = self.fetch_byte(&mut cycles, memory);
+    /// Executes whole instructions until exactly `cycles` cycles are used up.\
+    /// Fails with `EmulatorError::BudgetExhausted` if the last instruction needs more cycles than are left,
+    /// that instruction has still been executed
+    pub fn execute<B: Bus>(&mut self, mut cycles: u32, bus: &mut B) -> Result<(), EmulatorError> {
+        while cycles > 0 {
+            let step = self.step(bus)?;
 
-                    println!("byte: {:04X}", byte);
+            cycles = cycles.checked_sub(step.cycles).ok_or(EmulatorError::BudgetExhausted {
+                pc: step.address,
+                needed: step.cycles,
+                left: cycles,
+            })?;
+        }
 
-                    self.p.set_carry(self.x >= byte);
+        Ok(())
+    }
 
-                    if self.x == byte {
-                        println!("setting zero to true");
-                        self.p.set_zero(true);
-                    }
+    /// Executes whole instructions until at least `cycles` cycles are used up.\
+    /// The last instruction is allowed to go past the budget, the returned value is the budget left
+    /// after it, which is zero or negative and can be added to the budget of the next call
+    pub fn run_for<B: Bus>(&mut self, cycles: u32, bus: &mut B) -> Result<i64, EmulatorError> {
+        let mut left = cycles as i64;
 
-                    if self.x >= byte && ((self.x - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CPX_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+        while left > 0 {
+            left -= self.step(bus)?.cycles as i64;
+        }
 
-                    self.p.set_carry(self.x >= byte);
+        Ok(left)
+    }
 
-                    if self.x == byte {
-                        self.p.set_zero(true);
-                    }
+    /// Sets the level of the IRQ line, the interrupt is taken before the next instruction
+    /// as long as the line stays asserted and the interrupt disable flag is clear
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
 
-                    if self.x >= byte && ((self.x - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CPX_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+    pub const fn irq_line(&self) -> bool {
+        self.irq_line
+    }
 
-                    self.p.set_carry(self.x >= byte);
+    /// Signals an edge on the NMI line, the interrupt is taken before the next instruction
+    /// regardless of the interrupt disable flag
+    pub fn trigger_nmi(&mut self) {
+        self.nmi_pending = true;
+    }
 
-                    if self.x == byte {
-                        self.p.set_zero(true);
-                    }
+    pub const fn nmi_pending(&self) -> bool {
+        self.nmi_pending
+    }
 
-                    if self.x >= byte && ((self.x - byte) & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                CPY_IM => {
-                    let byte = self.fetch_byte(&mut cycles, memory);
+    pub fn registers(&self) -> Registers {
+        Registers {
+            pc: self.pc,
+            sp: self.sp,
+            a: self.a,
+            x: self.x,
+            y: self.y,
+            p: self.p.bits() | 0b00110000,
+        }
+    }
 
-                    self.p.set_carry(self.y >= byte);
+    /// Loads all registers at once, B and bit 5 of `registers.p` are ignored
+    pub fn set_registers(&mut self, registers: Registers) {
+        self.pc = registers.pc;
+        self.sp = registers.sp;
+        self.a = registers.a;
+        self.x = registers.x;
+        self.y = registers.y;
+        self.p = Status::from_bits_truncate(registers.p & !0b00110000);
+    }
 
-                    self.p.set_zero(self.y == byte);
+    /// Everything needed to put the CPU back where it is now with `set_state`, see `snapshot::Snapshot`
+    pub fn state(&self) -> CpuState {
+        CpuState {
+            pc: self.pc,
+            sp: self.sp,
+            a: self.a,
+            x: self.x,
+            y: self.y,
+            p: self.p,
+            cycles: self.cycles,
+            variant: self.variant,
+            undocumented: self.undocumented,
+            irq_line: self.irq_line,
+            nmi_pending: self.nmi_pending,
+            delayed_interrupt_flag: self.delayed_interrupt_flag,
+            waiting: self.waiting,
+            stopped: self.stopped,
+        }
+    }
 
-                    self.p.set_negative(self.y >= byte && ((self.y - byte) & 0b10000000) == 0b10000000);
-                }
-                CPY_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+    /// Restores a state returned by `state`, the tracer and the debugger are kept
+    pub fn set_state(&mut self, state: &CpuState) {
+        self.pc = state.pc;
+        self.sp = state.sp;
+        self.a = state.a;
+        self.x = state.x;
+        self.y = state.y;
+        self.p = state.p;
+        self.cycles = state.cycles;
+        self.variant = state.variant;
+        self.undocumented = state.undocumented;
+        self.irq_line = state.irq_line;
+        self.nmi_pending = state.nmi_pending;
+        self.delayed_interrupt_flag = state.delayed_interrupt_flag;
+        self.waiting = state.waiting;
+        self.stopped = state.stopped;
+    }
 
-                    self.p.set_carry(self.y >= byte);
+    /// Whether the CPU is sitting in WAI until an interrupt comes in
+    pub const fn waiting(&self) -> bool {
+        self.waiting
+    }
 
-                    self.p.set_zero(self.y == byte);
+    /// Whether the CPU executed STP and is halted until the next reset
+    pub const fn stopped(&self) -> bool {
+        self.stopped
+    }
 
-                    self.p.set_negative(self.y >= byte && ((self.y - byte) & 0b10000000) == 0b10000000);
-                }
-                CPY_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+    /// Undoes up to `count` of the steps recorded by the `journal`, returns how many were undone.\
+    /// Memory is put back through `Bus::poke`, so the old values skip the side effects of a write
+    pub fn step_back<B: Bus>(&mut self, count: usize, bus: &mut B) -> usize {
+        let Some(mut journal) = self.journal.take() else {
+            return 0;
+        };
 
-                    self.p.set_carry(self.y >= byte);
+        let mut undone = 0;
 
-                    self.p.set_zero(self.y == byte);
+        while undone < count {
+            let Some(entry) = journal.pop() else {
+                break;
+            };
 
-                    self.p.set_negative(self.y >= byte && ((self.y - byte) & 0b10000000) == 0b10000000);
-                }
-                INC_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
+            for write in entry.writes.iter().rev() {
+                bus.poke(write.address, write.old);
+            }
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+            self.set_state(&entry.before);
+            undone += 1;
+        }
 
-                    // Add
-                    data += 1;
-                    cycles -= 1;
+        self.journal = Some(journal);
+        undone
+    }
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+    /// Steps back to just before the instruction that last wrote to `address` and returns its address.\
+    /// Nothing changes when the journal holds no write to `address`
+    pub fn step_back_to_write<B: Bus>(&mut self, address: Word, bus: &mut B) -> Option<Word> {
+        let count = self.journal.as_ref()?.last_write(address)?;
+        self.step_back(count, bus);
 
-                    self.p.set_zero(data == 0);
+        Some(self.pc)
+    }
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                INC_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
+    /// Executes exactly one instruction, or services a pending interrupt instead.\
+    /// While the CPU is halted by WAI or STP a step idles for a single cycle and reports that
+    /// instruction as its opcode.\
+    /// On `EmulatorError::IllegalOpcode` the program counter is left pointing at the opcode.\
+    /// With a `debugger` installed the step fails with `EmulatorError::DebugStop` when it hits a
+    /// breakpoint, watchpoint or condition. An interrupt entry is traced, watched and checked for
+    /// bus faults like an instruction, breakpoints and conditions wait for the first instruction of the handler
+    pub fn step<B: Bus>(&mut self, bus: &mut B) -> Result<StepInfo, EmulatorError> {
+        // watchpoints hit outside of a step, by `reset` for instance, are not reported
+        if let Some(debugger) = &mut self.debugger {
+            debugger.take_pending();
+        }
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+        let before = self.journal.as_ref().map(|_| self.state());
+        if let (Some(journal), Some(before)) = (&mut self.journal, before) {
+            journal.begin(before);
+        }
 
-                    // Add
-                    data += 1;
-                    cycles -= 1;
+        let step = self.step_instruction(bus);
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+        // a failed step can still have changed things, a watchpoint stops after the instruction for instance
+        if self.journal.is_some() {
+            let after = self.state();
 
-                    self.p.set_zero(data == 0);
+            if let Some(journal) = &mut self.journal {
+                journal.commit(&after);
+            }
+        }
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                INC_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
+        let step = step?;
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+        match self.debugger.as_mut().and_then(Debugger::take_pending) {
+            Some(reason) => Err(EmulatorError::DebugStop { pc: step.address, reason }),
+            None => Ok(step),
+        }
+    }
 
-                    // Add
-                    data += 1;
-                    cycles -= 1;
+    /// `step` without the read and write watchpoints
+    fn step_instruction<B: Bus>(&mut self, bus: &mut B) -> Result<StepInfo, EmulatorError> {
+        let address = self.pc;
+        let start = self.cycles;
+        self.page_crossed = false;
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+        // WAI ends with any interrupt signal, even an IRQ masked by the interrupt disable flag
+        if self.waiting && (self.nmi_pending || self.irq_line) {
+            self.waiting = false;
+        }
 
-                    self.p.set_zero(data == 0);
+        if self.waiting || self.stopped {
+            self.read_memory(bus, self.pc);
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                INC_ABSX => {
-                    let address = self.fetch_word(&mut cycles, memory);
+            return Ok(StepInfo {
+                opcode: if self.stopped { STP } else { WAI },
+                address,
+                cycles: 1,
+                page_crossed: false,
+                interrupt: None,
+            });
+        }
 
-                    let effective_address = self.x as u16 + address;
+        // interrupts were polled at the end of the previous instruction
+        let irq_masked = self.delayed_interrupt_flag.take().unwrap_or(self.p.interrupt_flag());
 
-                    // Discarded Data
-                    cycles -= 1;
+        let interrupt = if self.nmi_pending {
+            self.nmi_pending = false;
+            Some(Interrupt::Nmi)
+        } else if self.irq_line && !irq_masked {
+            Some(Interrupt::Irq)
+        } else {
+            None
+        };
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+        if let Some(interrupt) = interrupt {
+            self.trace(bus, Some(interrupt));
 
-                    // Add
-                    data += 1;
-                    cycles -= 1;
+            // the CPU fetches the next opcode but replaces it with BRK without incrementing PC
+            self.read_memory(bus, self.pc);
+            self.read_memory(bus, self.pc);
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+            let vector = match interrupt {
+                Interrupt::Nmi => NMI_VECTOR,
+                Interrupt::Irq => IRQ_VECTOR,
+            };
+            self.interrupt_sequence(bus, vector, false);
 
-                    self.p.set_zero(data == 0);
+            return self.finish_step(bus, BRK, address, start, Some(interrupt));
+        }
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                INX => {
-                    self.x += 1;
-                    cycles -= 1;
+        let interrupt_flag = self.p.interrupt_flag();
 
-                    self.p.set_zero(self.x == 0);
-                    self.p.set_negative((self.x & 0b10000000) == 0b10000000);
-                }
-                INY => {
-                    self.y += 1;
-                    cycles -= 1;
+        if self.debugger.is_some() {
+            let registers = self.registers();
+            let opcode = bus.peek(self.pc);
 
-                    self.p.set_zero(self.y == 0);
+            if let Some(reason) = self.debugger.as_mut().and_then(|debugger| debugger.check_instruction(&registers, opcode)) {
+                return Err(EmulatorError::DebugStop { pc: address, reason });
+            }
+        }
 
-                    self.p.set_negative((self.y & 0b10000000) == 0b10000000);
-                }
-                DEC_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
+        self.trace(bus, None);
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+        let instruction = self.fetch_byte(bus);
 
-                    // Subtract
-                    data -= 1;
-                    cycles -= 1;
+        match instruction {
+            LDA_IM => {
+                self.a = self.fetch_byte(bus);
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+                self.set_zero_negative_flags(self.a);
+            }
+            LDA_ZP | LDA_ZPX | LDA_ABS | LDA_ABSX | LDA_ABSY | LDA_INDX | LDA_INDY => {
+                let effective_address = match instruction {
+                    LDA_ZP => self.zero_page_addressing(bus),
+                    LDA_ZPX => self.zero_page_x_addressing(bus),
+                    LDA_ABS => self.absolute_addressing(bus),
+                    LDA_ABSX => self.absolute_x_addressing(bus),
+                    LDA_ABSY => self.absolute_y_addressing(bus),
+                    LDA_INDX => self.indirect_x_addressing(bus),
+                    LDA_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+
+                self.a = self.read_memory(bus, effective_address);
+
+                self.set_zero_negative_flags(self.a);
+            }
+            LDX_IM => {
+                self.x = self.fetch_byte(bus);
 
-                    self.p.set_zero(data == 0);
+                self.set_zero_negative_flags(self.x);
+            }
+            LDX_ZP | LDX_ZPY | LDX_ABS | LDX_ABSY => {
+                let effective_address = match instruction {
+                    LDX_ZP => self.zero_page_addressing(bus),
+                    LDX_ZPY => self.zero_page_y_addressing(bus),
+                    LDX_ABS => self.absolute_addressing(bus),
+                    LDX_ABSY => self.absolute_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                self.x = self.read_memory(bus, effective_address);
+
+                self.set_zero_negative_flags(self.x);
+            }
+            LDY_IM => {
+                self.y = self.fetch_byte(bus);
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                DEC_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
+                self.set_zero_negative_flags(self.y);
+            }
+            LDY_ZP | LDY_ZPX | LDY_ABS | LDY_ABSX => {
+                let effective_address = match instruction {
+                    LDY_ZP => self.zero_page_addressing(bus),
+                    LDY_ZPX => self.zero_page_x_addressing(bus),
+                    LDY_ABS => self.absolute_addressing(bus),
+                    LDY_ABSX => self.absolute_x_addressing(bus),
+                    _ => unreachable!(),
+                };
+                self.y = self.read_memory(bus, effective_address);
+
+                self.set_zero_negative_flags(self.y);
+            }
+            STA_ZP | STA_ZPX | STA_ABS | STA_ABSX | STA_ABSY | STA_INDX | STA_INDY => {
+                let effective_address = match instruction {
+                    STA_ZP => self.zero_page_addressing(bus),
+                    STA_ZPX => self.zero_page_x_addressing(bus),
+                    STA_ABS => self.absolute_addressing(bus),
+                    STA_ABSX => self.absolute_x_addressing_write(bus),
+                    STA_ABSY => self.absolute_y_addressing_write(bus),
+                    STA_INDX => self.indirect_x_addressing(bus),
+                    STA_INDY => self.indirect_y_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.write_memory(bus, effective_address, self.a);
+            }
+            STX_ZP | STX_ZPY | STX_ABS => {
+                let effective_address = match instruction {
+                    STX_ZP => self.zero_page_addressing(bus),
+                    STX_ZPY => self.zero_page_y_addressing(bus),
+                    STX_ABS => self.absolute_addressing(bus),
+                    _ => unreachable!(),
+                };
+
+                self.write_memory(bus, effective_address, self.x);
+            }
+            STY_ZP | STY_ZPX | STY_ABS => {
+                let effective_address = match instruction {
+                    STY_ZP => self.zero_page_addressing(bus),
+                    STY_ZPX => self.zero_page_x_addressing(bus),
+                    STY_ABS => self.absolute_addressing(bus),
+                    _ => unreachable!(),
+                };
+
+                self.write_memory(bus, effective_address, self.y);
+            }
+            TAX => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+                self.x = self.a;
+                self.set_zero_negative_flags(self.x);
+            }
+            TAY => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Subtract
-                    data -= 1;
-                    cycles -= 1;
+                self.y = self.a;
+                self.set_zero_negative_flags(self.y);
+            }
+            TXA => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+                self.a = self.x;
+                self.set_zero_negative_flags(self.a);
+            }
+            TYA => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    self.p.set_zero(data == 0);
+                self.a = self.y;
+                self.set_zero_negative_flags(self.a);
+            }
+            TSX => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                DEC_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
+                self.x = self.sp;
+                self.set_zero_negative_flags(self.x);
+            }
+            TXS => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+                self.sp = self.x;
+            }
+            PHA => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Subtract
-                    data -= 1;
-                    cycles -= 1;
+                self.push(bus, self.a);
+            }
+            PHP => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+                self.push_status(bus, true);
+            }
+            PLA => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    self.p.set_zero(data == 0);
+                // Discarded read of the stack while the stack pointer is incremented
+                self.read_memory(bus, 0x0100 | self.sp as Word);
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                DEC_ABSX => {
-                    let address = self.fetch_word(&mut cycles, memory);
+                self.a = self.pull(bus);
 
-                    let effective_address = self.x as u16 + address;
+                self.set_zero_negative_flags(self.a);
+            }
+            PLP => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Discarded Data
-                    cycles -= 1;
+                // Discarded read of the stack while the stack pointer is incremented
+                self.read_memory(bus, 0x0100 | self.sp as Word);
 
-                    // Fetch data
-                    let mut data = memory[effective_address as usize];
-                    cycles -= 1;
+                self.pull_status(bus);
+            }
+            AND_IM | EOR_IM | ORA_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    // Subtract
-                    data -= 1;
-                    cycles -= 1;
+                match instruction {
+                    AND_IM => self.a &= byte,
+                    EOR_IM => self.a ^= byte,
+                    ORA_IM => self.a |= byte,
+                    _ => unreachable!(),
+                }
 
-                    // Write modified data back to memory cycle
-                    memory[effective_address as usize] = data;
-                    cycles -= 1;
+                self.set_zero_negative_flags(self.a);
+            }
+            AND_ZP | AND_ZPX | AND_ABS | AND_ABSX | AND_ABSY | AND_INDX | AND_INDY => {
+                let effective_address = match instruction {
+                    AND_ZP => self.zero_page_addressing(bus),
+                    AND_ZPX => self.zero_page_x_addressing(bus),
+                    AND_ABS => self.absolute_addressing(bus),
+                    AND_ABSX => self.absolute_x_addressing(bus),
+                    AND_ABSY => self.absolute_y_addressing(bus),
+                    AND_INDX => self.indirect_x_addressing(bus),
+                    AND_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                self.a &= self.read_memory(bus, effective_address);
+
+                self.set_zero_negative_flags(self.a);
+            }
+            EOR_ZP | EOR_ZPX | EOR_ABS | EOR_ABSX | EOR_ABSY | EOR_INDX | EOR_INDY => {
+                let effective_address = match instruction {
+                    EOR_ZP => self.zero_page_addressing(bus),
+                    EOR_ZPX => self.zero_page_x_addressing(bus),
+                    EOR_ABS => self.absolute_addressing(bus),
+                    EOR_ABSX => self.absolute_x_addressing(bus),
+                    EOR_ABSY => self.absolute_y_addressing(bus),
+                    EOR_INDX => self.indirect_x_addressing(bus),
+                    EOR_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                self.a ^= self.read_memory(bus, effective_address);
+
+                self.set_zero_negative_flags(self.a);
+            }
+            ORA_ZP | ORA_ZPX | ORA_ABS | ORA_ABSX | ORA_ABSY | ORA_INDX | ORA_INDY => {
+                let effective_address = match instruction {
+                    ORA_ZP => self.zero_page_addressing(bus),
+                    ORA_ZPX => self.zero_page_x_addressing(bus),
+                    ORA_ABS => self.absolute_addressing(bus),
+                    ORA_ABSX => self.absolute_x_addressing(bus),
+                    ORA_ABSY => self.absolute_y_addressing(bus),
+                    ORA_INDX => self.indirect_x_addressing(bus),
+                    ORA_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                self.a |= self.read_memory(bus, effective_address);
+
+                self.set_zero_negative_flags(self.a);
+            }
+            BIT_ZP | BIT_ABS => {
+                let effective_address = match instruction {
+                    BIT_ZP => self.zero_page_addressing(bus),
+                    BIT_ABS => self.absolute_addressing(bus),
+                    _ => unreachable!(),
+                };
+                let byte = self.read_memory(bus, effective_address);
+
+                self.bit_test(byte);
+            }
+            ADC_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    self.p.set_zero(data == 0);
+                self.add_with_carry(byte);
+                self.decimal_cycle(bus);
+            }
+            ADC_ZP | ADC_ZPX | ADC_ABS | ADC_ABSX | ADC_ABSY | ADC_INDX | ADC_INDY => {
+                let effective_address = match instruction {
+                    ADC_ZP => self.zero_page_addressing(bus),
+                    ADC_ZPX => self.zero_page_x_addressing(bus),
+                    ADC_ABS => self.absolute_addressing(bus),
+                    ADC_ABSX => self.absolute_x_addressing(bus),
+                    ADC_ABSY => self.absolute_y_addressing(bus),
+                    ADC_INDX => self.indirect_x_addressing(bus),
+                    ADC_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                let byte = self.read_memory(bus, effective_address);
+
+                self.add_with_carry(byte);
+                self.decimal_cycle(bus);
+            }
+            SBC_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    self.p.set_negative((data & 0b10000000) == 0b10000000);
-                }
-                DEX => {
-                    self.x -= 1;
+                self.subtract_with_carry(byte);
+                self.decimal_cycle(bus);
+            }
+            SBC_ZP | SBC_ZPX | SBC_ABS | SBC_ABSX | SBC_ABSY | SBC_INDX | SBC_INDY => {
+                let effective_address = match instruction {
+                    SBC_ZP => self.zero_page_addressing(bus),
+                    SBC_ZPX => self.zero_page_x_addressing(bus),
+                    SBC_ABS => self.absolute_addressing(bus),
+                    SBC_ABSX => self.absolute_x_addressing(bus),
+                    SBC_ABSY => self.absolute_y_addressing(bus),
+                    SBC_INDX => self.indirect_x_addressing(bus),
+                    SBC_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                let byte = self.read_memory(bus, effective_address);
+
+                self.subtract_with_carry(byte);
+                self.decimal_cycle(bus);
+            }
+            CMP_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    self.p.set_zero(self.x == 0);
+                self.compare(self.a, byte);
+            }
+            CMP_ZP | CMP_ZPX | CMP_ABS | CMP_ABSX | CMP_ABSY | CMP_INDX | CMP_INDY => {
+                let effective_address = match instruction {
+                    CMP_ZP => self.zero_page_addressing(bus),
+                    CMP_ZPX => self.zero_page_x_addressing(bus),
+                    CMP_ABS => self.absolute_addressing(bus),
+                    CMP_ABSX => self.absolute_x_addressing(bus),
+                    CMP_ABSY => self.absolute_y_addressing(bus),
+                    CMP_INDX => self.indirect_x_addressing(bus),
+                    CMP_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                let byte = self.read_memory(bus, effective_address);
+
+                self.compare(self.a, byte);
+            }
+            CPX_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    self.p.set_negative((self.x & 0b10000000) == 0b10000000);
-                }
-                DEY => {
-                    self.y -= 1;
+                self.compare(self.x, byte);
+            }
+            CPX_ZP | CPX_ABS => {
+                let effective_address = match instruction {
+                    CPX_ZP => self.zero_page_addressing(bus),
+                    CPX_ABS => self.absolute_addressing(bus),
+                    _ => unreachable!(),
+                };
+                let byte = self.read_memory(bus, effective_address);
+
+                self.compare(self.x, byte);
+            }
+            CPY_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    self.p.set_zero(self.y == 0);
+                self.compare(self.y, byte);
+            }
+            CPY_ZP | CPY_ABS => {
+                let effective_address = match instruction {
+                    CPY_ZP => self.zero_page_addressing(bus),
+                    CPY_ABS => self.absolute_addressing(bus),
+                    _ => unreachable!(),
+                };
+                let byte = self.read_memory(bus, effective_address);
+
+                self.compare(self.y, byte);
+            }
+            INC_ZP | INC_ZPX | INC_ABS | INC_ABSX => {
+                let effective_address = match instruction {
+                    INC_ZP => self.zero_page_addressing(bus),
+                    INC_ZPX => self.zero_page_x_addressing(bus),
+                    INC_ABS => self.absolute_addressing(bus),
+                    INC_ABSX => self.absolute_x_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.read_modify_write(bus, effective_address, Self::increment);
+            }
+            INX => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    self.p.set_negative((self.y & 0b10000000) == 0b10000000);
-                }
-                ASL_A => {
-                    let old_a = self.a;
-                    self.a <<= 1;
-                    cycles -= 1;
-
-                    if (old_a & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true);
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                self.x = self.x.wrapping_add(1);
+                self.set_zero_negative_flags(self.x);
+            }
+            INY => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    self.p.set_zero(self.a == 0);
+                self.y = self.y.wrapping_add(1);
+                self.set_zero_negative_flags(self.y);
+            }
+            DEC_ZP | DEC_ZPX | DEC_ABS | DEC_ABSX => {
+                let effective_address = match instruction {
+                    DEC_ZP => self.zero_page_addressing(bus),
+                    DEC_ZPX => self.zero_page_x_addressing(bus),
+                    DEC_ABS => self.absolute_addressing(bus),
+                    DEC_ABSX => self.absolute_x_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.read_modify_write(bus, effective_address, Self::decrement);
+            }
+            DEX => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    if (self.a & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ASL_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                self.x = self.x.wrapping_sub(1);
+                self.set_zero_negative_flags(self.x);
+            }
+            DEY => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    let new_byte = old_byte << 1;
-                    cycles -= 1;
+                self.y = self.y.wrapping_sub(1);
+                self.set_zero_negative_flags(self.y);
+            }
+            ASL_A => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                self.a = self.shift_left(self.a);
+            }
+            ASL_ZP | ASL_ZPX | ASL_ABS | ASL_ABSX => {
+                let effective_address = match instruction {
+                    ASL_ZP => self.zero_page_addressing(bus),
+                    ASL_ZPX => self.zero_page_x_addressing(bus),
+                    ASL_ABS => self.absolute_addressing(bus),
+                    ASL_ABSX if self.variant.is_cmos() => self.absolute_x_addressing(bus),
+                    ASL_ABSX => self.absolute_x_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.read_modify_write(bus, effective_address, Self::shift_left);
+            }
+            LSR_A => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true);
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                self.a = self.shift_right(self.a);
+            }
+            LSR_ZP | LSR_ZPX | LSR_ABS | LSR_ABSX => {
+                let effective_address = match instruction {
+                    LSR_ZP => self.zero_page_addressing(bus),
+                    LSR_ZPX => self.zero_page_x_addressing(bus),
+                    LSR_ABS => self.absolute_addressing(bus),
+                    LSR_ABSX if self.variant.is_cmos() => self.absolute_x_addressing(bus),
+                    LSR_ABSX => self.absolute_x_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.read_modify_write(bus, effective_address, Self::shift_right);
+            }
+            ROL_A => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    self.p.set_zero(new_byte == 0);
+                self.a = self.rotate_left(self.a);
+            }
+            ROL_ZP | ROL_ZPX | ROL_ABS | ROL_ABSX => {
+                let effective_address = match instruction {
+                    ROL_ZP => self.zero_page_addressing(bus),
+                    ROL_ZPX => self.zero_page_x_addressing(bus),
+                    ROL_ABS => self.absolute_addressing(bus),
+                    ROL_ABSX if self.variant.is_cmos() => self.absolute_x_addressing(bus),
+                    ROL_ABSX => self.absolute_x_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.read_modify_write(bus, effective_address, Self::rotate_left);
+            }
+            ROR_A => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ASL_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                self.a = self.rotate_right(self.a);
+            }
+            ROR_ZP | ROR_ZPX | ROR_ABS | ROR_ABSX => {
+                let effective_address = match instruction {
+                    ROR_ZP => self.zero_page_addressing(bus),
+                    ROR_ZPX => self.zero_page_x_addressing(bus),
+                    ROR_ABS => self.absolute_addressing(bus),
+                    ROR_ABSX if self.variant.is_cmos() => self.absolute_x_addressing(bus),
+                    ROR_ABSX => self.absolute_x_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.read_modify_write(bus, effective_address, Self::rotate_right);
+            }
+            JMP_ABS => {
+                self.pc = self.absolute_addressing(bus);
+            }
+            JMP_IND if self.variant.is_cmos() => {
+                let effective_address = self.fetch_word(bus);
 
-                    let new_byte = old_byte << 1;
-                    cycles -= 1;
+                // Discarded read of the last operand byte, the 65C02 takes a cycle to get the
+                // pointer right when it sits at the end of a page
+                self.read_memory(bus, self.pc.wrapping_sub(1));
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                self.pc = self.read_word_memory(bus, effective_address);
+            }
+            JMP_IND => {
+                self.pc = self.indirect_addressing(bus);
+            }
+            JSR => {
+                let low_byte = self.fetch_byte(bus);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true);
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                // Discarded read of the stack, the CPU keeps the low byte of the target there meanwhile
+                self.read_memory(bus, 0x0100 | self.sp as Word);
 
-                    self.p.set_zero(new_byte == 0);
+                // PC points at the high byte of the target, which is the return address minus one
+                self.push(bus, (self.pc >> 8) as u8);
+                self.push(bus, self.pc as u8);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ASL_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                let high_byte = self.fetch_byte(bus);
 
-                    let new_byte = old_byte << 1;
-                    cycles -= 1;
+                self.pc = ((high_byte as u16) << 8) | low_byte as u16;
+            }
+            RTS => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                // Discarded read of the stack while the stack pointer is incremented
+                self.read_memory(bus, 0x0100 | self.sp as Word);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true);
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                let low_byte = self.pull(bus);
+                let high_byte = self.pull(bus);
 
-                    self.p.set_zero(new_byte == 0);
+                self.pc = ((high_byte as u16) << 8) | low_byte as u16;
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
+                // Discarded read of the return address minus one while PC is incremented
+                self.fetch_byte(bus);
+            }
+            BCC => self.branch(bus, !self.p.carry_flag()),
+            BCS => self.branch(bus, self.p.carry_flag()),
+            BEQ => self.branch(bus, self.p.zero_flag()),
+            BMI => self.branch(bus, self.p.negative_flag()),
+            BNE => self.branch(bus, !self.p.zero_flag()),
+            BPL => self.branch(bus, !self.p.negative_flag()),
+            BVC => self.branch(bus, !self.p.overflow_flag()),
+            BVS => self.branch(bus, self.p.overflow_flag()),
+            CLC | CLD | CLI | CLV | SEC | SED | SEI => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
+
+                match instruction {
+                    CLC => self.p.set_carry(false),
+                    CLD => self.p.set_decimal(false),
+                    CLI => self.p.set_interrupt(false),
+                    CLV => self.p.set_overflow(false),
+                    SEC => self.p.set_carry(true),
+                    SED => self.p.set_decimal(true),
+                    SEI => self.p.set_interrupt(true),
+                    _ => unreachable!(),
                 }
-                ASL_ABSX => {
-                    let address = self.fetch_word(&mut cycles, memory);
+            }
+            BRK => {
+                // Padding byte, skipped so that the return address is BRK + 2
+                self.fetch_byte(bus);
 
-                    let effective_address = self.x as u16 + address;
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                self.interrupt_sequence(bus, IRQ_VECTOR, true);
+            }
+            NOP => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
+            }
+            RTI => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Discarded Data
-                    cycles -= 1;
+                // Discarded read of the stack while the stack pointer is incremented
+                self.read_memory(bus, 0x0100 | self.sp as Word);
 
-                    let new_byte = old_byte << 1;
-                    cycles -= 1;
+                self.pull_status(bus);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                let low_byte = self.pull(bus);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true);
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                let high_byte = self.pull(bus);
 
-                    self.p.set_zero(new_byte == 0);
+                self.pc = ((high_byte as u16) << 8) | low_byte as u16;
+            }
+            _ if self.variant.is_cmos() => self.step_cmos(bus, instruction),
+            _ => match self.undocumented {
+                Some(undocumented) => {
+                    if let Err(error) = self.step_undocumented(bus, instruction, address, undocumented) {
+                        self.pc = address;
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
+                        return Err(error);
                     }
                 }
-                LSR_A => {
-                    let old_a = self.a;
-                    self.a >>= 1;
-                    cycles -= 1;
-
-                    if (old_a & 0b00000001) == 0b00000001 {
-                        self.p.set_carry(true);
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                _ => {
+                    self.pc = address;
 
-                    self.p.set_zero(self.a == 0);
-
-                    if (self.a & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
+                    return Err(EmulatorError::IllegalOpcode { opcode: instruction, pc: address });
                 }
-                LSR_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
-
-                    let new_byte = old_byte >> 1;
-                    cycles -= 1;
-
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+            },
+        }
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+        // CLI, SEI and PLP change the flag after interrupts are polled, so their effect is delayed by one instruction
+        if matches!(instruction, CLI | SEI | PLP) && interrupt_flag != self.p.interrupt_flag() {
+            self.delayed_interrupt_flag = Some(interrupt_flag);
+        }
 
-                    self.p.set_zero(new_byte == 0);
+        self.finish_step(bus, instruction, address, start, None)
+    }
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                LSR_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+    /// Hands the state before the instruction, or before the interrupt entered instead of it, to the tracer
+    fn trace<B: Bus>(&mut self, bus: &mut B, interrupt: Option<Interrupt>) {
+        if self.tracer.is_some() {
+            let event = TraceEvent {
+                registers: self.registers(),
+                bytes: [bus.peek(self.pc), bus.peek(self.pc.wrapping_add(1)), bus.peek(self.pc.wrapping_add(2))],
+                cycles: self.cycles,
+                variant: self.variant,
+                interrupt,
+            };
+
+            if let Some(tracer) = &mut self.tracer {
+                tracer.trace(&event);
+            }
+        }
+    }
 
-                    let new_byte = old_byte >> 1;
-                    cycles -= 1;
+    /// What every instruction and interrupt entry ends with, the bus faults it caused fail the step
+    fn finish_step<B: Bus>(
+        &mut self,
+        bus: &mut B,
+        opcode: Byte,
+        address: Word,
+        start: u64,
+        interrupt: Option<Interrupt>,
+    ) -> Result<StepInfo, EmulatorError> {
+        if let Some(fault) = bus.take_fault() {
+            return Err(EmulatorError::BusFault { pc: address, fault });
+        }
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+        Ok(StepInfo {
+            opcode,
+            address,
+            cycles: (self.cycles - start) as u32,
+            page_crossed: self.page_crossed,
+            interrupt,
+        })
+    }
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+    /// Executes the 65C02 `instruction`, everything the NMOS chips do not have
+    fn step_cmos<B: Bus>(&mut self, bus: &mut B, instruction: Byte) {
+        match instruction {
+            BRA => self.branch(bus, true),
+            RMB0 | RMB1 | RMB2 | RMB3 | RMB4 | RMB5 | RMB6 | RMB7
+            | SMB0 | SMB1 | SMB2 | SMB3 | SMB4 | SMB5 | SMB6 | SMB7 if self.variant.has_bit_instructions() => {
+                let effective_address = self.zero_page_addressing(bus);
+                let value = self.read_memory(bus, effective_address);
 
-                    self.p.set_zero(new_byte == 0);
+                // Discarded read while the bit is changed
+                self.read_memory(bus, effective_address);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                LSR_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                // bits 4-6 of the opcode select the bit, bit 7 tells set from reset
+                let bit = 1 << ((instruction >> 4) & 0b111);
+                let value = if instruction & 0b10000000 == 0 { value & !bit } else { value | bit };
 
-                    let new_byte = old_byte >> 1;
-                    cycles -= 1;
+                self.write_memory(bus, effective_address, value);
+            }
+            BBR0 | BBR1 | BBR2 | BBR3 | BBR4 | BBR5 | BBR6 | BBR7
+            | BBS0 | BBS1 | BBS2 | BBS3 | BBS4 | BBS5 | BBS6 | BBS7 if self.variant.has_bit_instructions() => {
+                let effective_address = self.zero_page_addressing(bus);
+                let value = self.read_memory(bus, effective_address);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                // Discarded read while the bit is tested
+                self.read_memory(bus, effective_address);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                let bit_set = value & (1 << ((instruction >> 4) & 0b111)) != 0;
+                let branch_on_set = instruction & 0b10000000 != 0;
 
-                    self.p.set_zero(new_byte == 0);
+                self.branch(bus, bit_set == branch_on_set);
+            }
+            WAI | STP if self.variant == Variant::Wdc65C02 => {
+                // Discarded reads of the next opcode
+                self.read_memory(bus, self.pc);
+                self.read_memory(bus, self.pc);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
+                if instruction == WAI {
+                    self.waiting = true;
+                } else {
+                    self.stopped = true;
                 }
-                LSR_ABSX => {
-                    let address = self.fetch_word(&mut cycles, memory);
+            }
+            PHX | PHY => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    let effective_address = self.x as u16 + address;
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                let value = if instruction == PHX { self.x } else { self.y };
+                self.push(bus, value);
+            }
+            PLX | PLY => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    // Discarded Data
-                    cycles -= 1;
+                // Discarded read of the stack while the stack pointer is incremented
+                self.read_memory(bus, 0x0100 | self.sp as Word);
 
-                    let new_byte = old_byte >> 1;
-                    cycles -= 1;
+                let value = self.pull(bus);
+                self.set_zero_negative_flags(value);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                if instruction == PLX {
+                    self.x = value;
+                } else {
+                    self.y = value;
+                }
+            }
+            STZ_ZP | STZ_ZPX | STZ_ABS | STZ_ABSX => {
+                let effective_address = match instruction {
+                    STZ_ZP => self.zero_page_addressing(bus),
+                    STZ_ZPX => self.zero_page_x_addressing(bus),
+                    STZ_ABS => self.absolute_addressing(bus),
+                    STZ_ABSX => self.absolute_x_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                self.write_memory(bus, effective_address, 0);
+            }
+            TRB_ZP | TRB_ABS | TSB_ZP | TSB_ABS => {
+                let effective_address = match instruction {
+                    TRB_ZP | TSB_ZP => self.zero_page_addressing(bus),
+                    TRB_ABS | TSB_ABS => self.absolute_addressing(bus),
+                    _ => unreachable!(),
+                };
+
+                let operation = match instruction {
+                    TRB_ZP | TRB_ABS => Self::test_and_reset_bits,
+                    _ => Self::test_and_set_bits,
+                };
+
+                self.read_modify_write(bus, effective_address, operation);
+            }
+            ORA_ZPIND | AND_ZPIND | EOR_ZPIND | ADC_ZPIND | LDA_ZPIND | CMP_ZPIND | SBC_ZPIND => {
+                let effective_address = self.zero_page_indirect_addressing(bus);
+                let byte = self.read_memory(bus, effective_address);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
+                match instruction {
+                    ORA_ZPIND => self.a |= byte,
+                    AND_ZPIND => self.a &= byte,
+                    EOR_ZPIND => self.a ^= byte,
+                    LDA_ZPIND => self.a = byte,
+                    CMP_ZPIND => self.compare(self.a, byte),
+                    ADC_ZPIND => {
+                        self.add_with_carry(byte);
+                        self.decimal_cycle(bus);
                     }
-
-                    self.p.set_zero(new_byte == 0);
-
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
+                    SBC_ZPIND => {
+                        self.subtract_with_carry(byte);
+                        self.decimal_cycle(bus);
                     }
+                    _ => unreachable!(),
                 }
-                ROL_A => {
-                    let old_a = self.a;
-                    self.a <<= 1;
-                    self.a |= self.p.bits() & 0b00000001;
-                    cycles -= 1;
-
-                    if (old_a & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
 
-                    self.p.set_zero(self.a == 0);
-
-                    if (self.a & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
+                if matches!(instruction, ORA_ZPIND | AND_ZPIND | EOR_ZPIND | LDA_ZPIND) {
+                    self.set_zero_negative_flags(self.a);
                 }
-                ROL_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+            }
+            STA_ZPIND => {
+                let effective_address = self.zero_page_indirect_addressing(bus);
 
-                    let mut new_byte = old_byte << 1;
-                    new_byte |= self.p.bits() & 0b00000001;
-                    cycles -= 1;
+                self.write_memory(bus, effective_address, self.a);
+            }
+            INC_A => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                self.a = self.increment(self.a);
+            }
+            DEC_A => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                self.a = self.decrement(self.a);
+            }
+            BIT_IM => {
+                // only Z is affected, there is no memory operand to copy N and V from
+                let byte = self.fetch_byte(bus);
 
-                    self.p.set_zero(new_byte == 0);
+                self.p.set_zero(self.a & byte == 0);
+            }
+            BIT_ZPX | BIT_ABSX => {
+                let effective_address = match instruction {
+                    BIT_ZPX => self.zero_page_x_addressing(bus),
+                    BIT_ABSX => self.absolute_x_addressing(bus),
+                    _ => unreachable!(),
+                };
+                let byte = self.read_memory(bus, effective_address);
+
+                self.bit_test(byte);
+            }
+            JMP_INDX => {
+                let address = self.fetch_word(bus);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROL_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                // Discarded read of the last operand byte while X is added
+                self.read_memory(bus, self.pc.wrapping_sub(1));
 
-                    let mut new_byte = old_byte << 1;
-                    new_byte |= self.p.bits() & 0b00000001;
-                    cycles -= 1;
+                let effective_address = address.wrapping_add(self.x as Word);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                self.pc = self.read_word_memory(bus, effective_address);
+            }
+            // the remaining opcodes are NOPs, using the same operand size and bus cycles as the
+            // instruction they decode like
+            0x02 | 0x22 | 0x42 | 0x62 | 0x82 | 0xC2 | 0xE2 => {
+                self.fetch_byte(bus);
+            }
+            0x44 => {
+                let effective_address = self.zero_page_addressing(bus);
+                self.read_memory(bus, effective_address);
+            }
+            0x54 | 0xD4 | 0xF4 => {
+                let effective_address = self.zero_page_x_addressing(bus);
+                self.read_memory(bus, effective_address);
+            }
+            0xDC | 0xFC => {
+                let effective_address = self.absolute_addressing(bus);
+                self.read_memory(bus, effective_address);
+            }
+            0x5C => {
+                // reads its operand then spends 4 more cycles on the bus
+                let address = self.absolute_addressing(bus);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                self.read_memory(bus, 0xFF00 | (address & 0x00FF));
+                for _ in 0..4 {
+                    self.read_memory(bus, 0xFFFF);
+                }
+            }
+            // columns 3, 7, B and F, 1 byte NOPs that are done once the opcode is fetched
+            _ => (),
+        }
+    }
 
-                    self.p.set_zero(new_byte == 0);
+    /// Executes the undocumented NMOS `instruction` fetched at `address`
+    fn step_undocumented<B: Bus>(&mut self, bus: &mut B, instruction: Byte, address: Word, undocumented: Undocumented) -> Result<(), EmulatorError> {
+        match instruction {
+            SLO_ZP | SLO_ZPX | SLO_ABS | SLO_ABSX | SLO_ABSY | SLO_INDX | SLO_INDY
+            | RLA_ZP | RLA_ZPX | RLA_ABS | RLA_ABSX | RLA_ABSY | RLA_INDX | RLA_INDY
+            | SRE_ZP | SRE_ZPX | SRE_ABS | SRE_ABSX | SRE_ABSY | SRE_INDX | SRE_INDY
+            | RRA_ZP | RRA_ZPX | RRA_ABS | RRA_ABSX | RRA_ABSY | RRA_INDX | RRA_INDY
+            | DCP_ZP | DCP_ZPX | DCP_ABS | DCP_ABSX | DCP_ABSY | DCP_INDX | DCP_INDY
+            | ISC_ZP | ISC_ZPX | ISC_ABS | ISC_ABSX | ISC_ABSY | ISC_INDX | ISC_INDY => {
+                // the low 5 bits select the addressing mode, the high 3 bits the operation
+                let effective_address = match instruction & 0b00011111 {
+                    0x07 => self.zero_page_addressing(bus),
+                    0x17 => self.zero_page_x_addressing(bus),
+                    0x0F => self.absolute_addressing(bus),
+                    0x1F => self.absolute_x_addressing_write(bus),
+                    0x1B => self.absolute_y_addressing_write(bus),
+                    0x03 => self.indirect_x_addressing(bus),
+                    0x13 => self.indirect_y_addressing_write(bus),
+                    _ => unreachable!(),
+                };
+
+                let operation: fn(&mut Self, Byte) -> Byte = match instruction & 0b11100000 {
+                    0x00 => Self::shift_left_or,
+                    0x20 => Self::rotate_left_and,
+                    0x40 => Self::shift_right_eor,
+                    0x60 => Self::rotate_right_add,
+                    0xC0 => Self::decrement_compare,
+                    0xE0 => Self::increment_subtract,
+                    _ => unreachable!(),
+                };
+
+                self.read_modify_write(bus, effective_address, operation);
+            }
+            SAX_ZP | SAX_ZPY | SAX_ABS | SAX_INDX => {
+                let effective_address = match instruction {
+                    SAX_ZP => self.zero_page_addressing(bus),
+                    SAX_ZPY => self.zero_page_y_addressing(bus),
+                    SAX_ABS => self.absolute_addressing(bus),
+                    SAX_INDX => self.indirect_x_addressing(bus),
+                    _ => unreachable!(),
+                };
+
+                self.write_memory(bus, effective_address, self.a & self.x);
+            }
+            LAX_ZP | LAX_ZPY | LAX_ABS | LAX_ABSY | LAX_INDX | LAX_INDY => {
+                let effective_address = match instruction {
+                    LAX_ZP => self.zero_page_addressing(bus),
+                    LAX_ZPY => self.zero_page_y_addressing(bus),
+                    LAX_ABS => self.absolute_addressing(bus),
+                    LAX_ABSY => self.absolute_y_addressing(bus),
+                    LAX_INDX => self.indirect_x_addressing(bus),
+                    LAX_INDY => self.indirect_y_addressing(bus),
+                    _ => unreachable!(),
+                };
+                self.a = self.read_memory(bus, effective_address);
+                self.x = self.a;
+
+                self.set_zero_negative_flags(self.a);
+            }
+            ANC_IM | ANC_IM_2B => {
+                self.a &= self.fetch_byte(bus);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROL_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                self.set_zero_negative_flags(self.a);
+                self.p.set_carry(self.p.negative_flag());
+            }
+            ALR_IM => {
+                self.a &= self.fetch_byte(bus);
 
-                    let mut new_byte = old_byte << 1;
-                    new_byte |= self.p.bits() & 0b00000001;
-                    cycles -= 1;
+                self.a = self.shift_right(self.a);
+            }
+            ARR_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                self.and_rotate_right(byte);
+            }
+            SBX_IM => {
+                let byte = self.fetch_byte(bus);
+                let value = self.a & self.x;
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                self.p.set_carry(value >= byte);
+                self.x = value.wrapping_sub(byte);
+                self.set_zero_negative_flags(self.x);
+            }
+            USBC_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    self.p.set_zero(new_byte == 0);
+                self.subtract_with_carry(byte);
+            }
+            ANE_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROL_ABSX => {
-                    let address = self.fetch_word(&mut cycles, memory);
+                self.a = (self.a | undocumented.ane_magic) & self.x & byte;
+                self.set_zero_negative_flags(self.a);
+            }
+            LXA_IM => {
+                let byte = self.fetch_byte(bus);
 
-                    let effective_address = self.x as u16 + address;
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+                self.a = (self.a | undocumented.lxa_magic) & byte;
+                self.x = self.a;
+                self.set_zero_negative_flags(self.a);
+            }
+            LAS_ABSY => {
+                let effective_address = self.absolute_y_addressing(bus);
+                let value = self.read_memory(bus, effective_address) & self.sp;
+
+                self.a = value;
+                self.x = value;
+                self.sp = value;
+                self.set_zero_negative_flags(value);
+            }
+            TAS_ABSY => {
+                let base_address = self.absolute_addressing(bus);
 
-                    // Discarded Data
-                    cycles -= 1;
+                self.sp = self.a & self.x;
+                self.store_and_high(bus, base_address, self.y, self.sp);
+            }
+            SHA_ABSY => {
+                let base_address = self.absolute_addressing(bus);
 
-                    let mut new_byte = old_byte << 1;
-                    new_byte |= self.p.bits() & 0b00000001;
-                    cycles -= 1;
+                self.store_and_high(bus, base_address, self.y, self.a & self.x);
+            }
+            SHA_INDY => {
+                let pointer = self.fetch_byte(bus);
+                let base_address = self.read_zero_page_word(bus, pointer);
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+                self.store_and_high(bus, base_address, self.y, self.a & self.x);
+            }
+            SHX_ABSY => {
+                let base_address = self.absolute_addressing(bus);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+                self.store_and_high(bus, base_address, self.y, self.x);
+            }
+            SHY_ABSX => {
+                let base_address = self.absolute_addressing(bus);
 
-                    self.p.set_zero(new_byte == 0);
+                self.store_and_high(bus, base_address, self.x, self.y);
+            }
+            NOP_IMP_1A | NOP_IMP_3A | NOP_IMP_5A | NOP_IMP_7A | NOP_IMP_DA | NOP_IMP_FA => {
+                // Discarded read of the next opcode
+                self.read_memory(bus, self.pc);
+            }
+            NOP_IM_80 | NOP_IM_82 | NOP_IM_89 | NOP_IM_C2 | NOP_IM_E2 => {
+                self.fetch_byte(bus);
+            }
+            NOP_ZP_04 | NOP_ZP_44 | NOP_ZP_64
+            | NOP_ZPX_14 | NOP_ZPX_34 | NOP_ZPX_54 | NOP_ZPX_74 | NOP_ZPX_D4 | NOP_ZPX_F4
+            | NOP_ABS_0C
+            | NOP_ABSX_1C | NOP_ABSX_3C | NOP_ABSX_5C | NOP_ABSX_7C | NOP_ABSX_DC | NOP_ABSX_FC => {
+                let effective_address = match instruction & 0b00011100 {
+                    0x04 => self.zero_page_addressing(bus),
+                    0x14 => self.zero_page_x_addressing(bus),
+                    0x0C => self.absolute_addressing(bus),
+                    0x1C => self.absolute_x_addressing(bus),
+                    _ => unreachable!(),
+                };
+
+                // the operand is read and thrown away
+                self.read_memory(bus, effective_address);
+            }
+            JAM_02 | JAM_12 | JAM_22 | JAM_32 | JAM_42 | JAM_52 | JAM_62 | JAM_72 | JAM_92 | JAM_B2 | JAM_D2 | JAM_F2 => {
+                return Err(EmulatorError::Jammed { opcode: instruction, pc: address });
+            }
+            _ => return Err(EmulatorError::IllegalOpcode { opcode: instruction, pc: address }),
+        }
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROR_A => {
-                    let old_a = self.a;
-                    self.a >>= 1;
-                    if self.p.carry_flag() {
-                        self.a |= 0b10000000;
-                    } else {
-                        self.a &= 0b01111111;
-                    }
-                    cycles -= 1;
+        Ok(())
+    }
 
-                    if (old_a & 0b00000001) == 0b00000001 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+    /// Pushes PC and the status register then jumps through `vector`, shared by BRK, IRQ and NMI.\
+    /// The 65C02 also clears the decimal flag\
+    /// takes 5 cycles
+    fn interrupt_sequence<B: Bus>(&mut self, bus: &mut B, vector: Word, break_flag: bool) {
+        self.push(bus, (self.pc >> 8) as u8);
+        self.push(bus, self.pc as u8);
+        self.push_status(bus, break_flag);
 
-                    self.p.set_zero(self.a == 0);
+        self.p.set_interrupt(true);
 
-                    if (self.a & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROR_ZP => {
-                    let effective_address = self.zero_page_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
-
-                    let mut new_byte = old_byte >> 1;
-                    if self.p.carry_flag() {
-                        new_byte |= 0b10000000;
-                    } else {
-                        new_byte &= 0b01111111;
-                    }
-                    cycles -= 1;
+        if self.variant.is_cmos() {
+            self.p.set_decimal(false);
+        }
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+        self.pc = self.read_word_memory(bus, vector);
+    }
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+    /// The pushed status always has bit 5 set, B is set for BRK and PHP and clear for IRQ and NMI\
+    /// takes 1 cycle
+    fn push_status<B: Bus>(&mut self, bus: &mut B, break_flag: bool) {
+        let mut status = self.p.bits() | 0b00100000;
 
-                    self.p.set_zero(new_byte == 0);
+        if break_flag {
+            status |= 0b00010000;
+        } else {
+            status &= !0b00010000;
+        }
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROR_ZPX => {
-                    let effective_address = self.zero_page_x_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
-
-                    let mut new_byte = old_byte >> 1;
-                    if self.p.carry_flag() {
-                        new_byte |= 0b10000000;
-                    } else {
-                        new_byte &= 0b01111111;
-                    }
-                    cycles -= 1;
+        self.push(bus, status);
+    }
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+    /// B and bit 5 do not exist in the register, they are ignored when pulling the status\
+    /// takes 1 cycle
+    fn pull_status<B: Bus>(&mut self, bus: &mut B) {
+        let status = self.pull(bus);
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+        self.p = Status::from_bits_truncate(status & !0b00110000);
+    }
 
-                    self.p.set_zero(new_byte == 0);
+    fn set_zero_negative_flags(&mut self, value: Byte) {
+        self.p.set_zero(value == 0);
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROR_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
-
-                    let mut new_byte = old_byte >> 1;
-                    if self.p.carry_flag() {
-                        new_byte |= 0b10000000;
-                    } else {
-                        new_byte &= 0b01111111;
-                    }
-                    cycles -= 1;
+        self.p.set_negative(value & 0b10000000 == 0b10000000);
+    }
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+    /// BIT, N and V are copied from the operand, Z comes from the AND with A
+    fn bit_test(&mut self, value: Byte) {
+        self.p.set_zero(self.a & value == 0);
+        self.p.set_overflow(value & 0b01000000 == 0b01000000);
+        self.p.set_negative(value & 0b10000000 == 0b10000000);
+    }
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+    /// CMP, CPX and CPY, `register` minus `value` without storing the result
+    fn compare(&mut self, register: Byte, value: Byte) {
+        self.p.set_carry(register >= value);
 
-                    self.p.set_zero(new_byte == 0);
+        self.set_zero_negative_flags(register.wrapping_sub(value));
+    }
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                ROR_ABSX => {
-                    let address = self.fetch_word(&mut cycles, memory);
+    fn increment(&mut self, value: Byte) -> Byte {
+        let result = value.wrapping_add(1);
+        self.set_zero_negative_flags(result);
 
-                    let effective_address = self.x as u16 + address;
-                    let old_byte = self.read_memory(&mut cycles, memory, effective_address as usize);
+        result
+    }
 
-                    // Discarded Data
-                    cycles -= 1;
+    fn decrement(&mut self, value: Byte) -> Byte {
+        let result = value.wrapping_sub(1);
+        self.set_zero_negative_flags(result);
 
-                    let mut new_byte = old_byte >> 1;
-                    if self.p.carry_flag() {
-                        new_byte |= 0b10000000;
-                    } else {
-                        new_byte &= 0b01111111;
-                    }
-                    cycles -= 1;
+        result
+    }
 
-                    memory[effective_address as usize] = new_byte;
-                    cycles -= 1;
+    /// ASL, bit 7 goes into the carry
+    fn shift_left(&mut self, value: Byte) -> Byte {
+        let result = value << 1;
 
-                    if (old_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_carry(true)
-                    } else {
-                        self.p.set_negative(false);
-                    }
+        self.p.set_carry(value & 0b10000000 == 0b10000000);
+        self.set_zero_negative_flags(result);
 
-                    self.p.set_zero(new_byte == 0);
+        result
+    }
 
-                    if (new_byte & 0b10000000) == 0b10000000 {
-                        self.p.set_negative(true);
-                    }
-                }
-                JMP_ABS => {
-                    let effective_address = self.absolute_addressing(&mut cycles, memory);
-                    self.pc = effective_address;
-                }
-                JMP_IND => {
-                    let effective_address = self.indirect_addressing(&mut cycles, memory);
-                    self.pc = effective_address;
-                }
-                JSR => {
-                    let low_byte = self.fetch_byte(&mut cycles, memory);
+    /// LSR, bit 0 goes into the carry
+    fn shift_right(&mut self, value: Byte) -> Byte {
+        let result = value >> 1;
 
-                    // Discarded data
-                    cycles -= 1;
+        self.p.set_carry(value & 0b00000001 == 0b00000001);
+        self.set_zero_negative_flags(result);
 
-                    memory[self.sp as usize] = (self.pc >> 8) as u8;
-                    self.sp -= 1;
-                    cycles -= 1;
+        result
+    }
 
-                    memory[self.sp as usize] = self.pc as u8;
-                    self.sp -= 1;
-                    cycles -= 1;
+    /// ROL, the carry goes into bit 0 and bit 7 goes into the carry
+    fn rotate_left(&mut self, value: Byte) -> Byte {
+        let result = (value << 1) | self.p.carry_flag() as Byte;
 
-                    let high_byte = self.fetch_byte(&mut cycles, memory);
+        self.p.set_carry(value & 0b10000000 == 0b10000000);
+        self.set_zero_negative_flags(result);
 
-                    self.pc = ((high_byte as u16) << 8) | low_byte as u16;
-                }
-                RTS => {
-                    // Discarded data
-                    cycles -= 1;
+        result
+    }
 
-                    // Discarded data
-                    cycles -= 1;
+    /// ROR, the carry goes into bit 7 and bit 0 goes into the carry
+    fn rotate_right(&mut self, value: Byte) -> Byte {
+        let result = (value >> 1) | ((self.p.carry_flag() as Byte) << 7);
 
-                    self.sp += 1;
-                    let low_byte = memory[self.sp as usize];
-                    cycles -= 1;
+        self.p.set_carry(value & 0b00000001 == 0b00000001);
+        self.set_zero_negative_flags(result);
 
-                    self.sp += 1;
-                    let high_byte = memory[self.sp as usize];
-                    cycles -= 1;
+        result
+    }
 
-                    // Discarded data
-                    cycles -= 1;
+    /// TRB, Z comes from A AND memory, then the bits set in A are cleared in memory
+    fn test_and_reset_bits(&mut self, value: Byte) -> Byte {
+        self.p.set_zero(self.a & value == 0);
 
-                    self.pc = ((high_byte as u16) << 8) | low_byte as u16;
-                    self.pc += 1;
-                }
-                BCC => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
-                    
-                    if !self.p.carry_flag() {
-                        cycles -= 1;
-
-                        let new_location;
-
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
-
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
-
-                        self.pc = new_location;
-                    }
-                }
-                BCS => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
+        value & !self.a
+    }
 
-                    if self.p.carry_flag() {
-                        cycles -= 1;
+    /// TSB, Z comes from A AND memory, then the bits set in A are set in memory
+    fn test_and_set_bits(&mut self, value: Byte) -> Byte {
+        self.p.set_zero(self.a & value == 0);
 
-                        let new_location;
+        value | self.a
+    }
 
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
+    /// The 65C02 spends one more cycle fixing up the flags after decimal mode ADC and SBC\
+    /// takes 0-1 cycles
+    fn decimal_cycle<B: Bus>(&mut self, bus: &mut B) {
+        if self.variant.is_cmos() && self.p.decimal_flag() {
+            // Discarded read of the next opcode
+            self.read_memory(bus, self.pc);
+        }
+    }
 
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
+    /// SLO, ASL on memory then ORA with the result
+    fn shift_left_or(&mut self, value: Byte) -> Byte {
+        let result = self.shift_left(value);
 
-                        self.pc = new_location;
-                    }
-                }
-                BEQ => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
+        self.a |= result;
+        self.set_zero_negative_flags(self.a);
 
-                    if self.p.zero_flag() {
-                        cycles -= 1;
+        result
+    }
 
-                        let new_location;
+    /// RLA, ROL on memory then AND with the result
+    fn rotate_left_and(&mut self, value: Byte) -> Byte {
+        let result = self.rotate_left(value);
 
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
+        self.a &= result;
+        self.set_zero_negative_flags(self.a);
 
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
+        result
+    }
 
-                        self.pc = new_location;
-                    }
-                }
-                BMI => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
+    /// SRE, LSR on memory then EOR with the result
+    fn shift_right_eor(&mut self, value: Byte) -> Byte {
+        let result = self.shift_right(value);
 
-                    if self.p.negative_flag() {
-                        cycles -= 1;
+        self.a ^= result;
+        self.set_zero_negative_flags(self.a);
 
-                        let new_location;
+        result
+    }
 
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
+    /// RRA, ROR on memory then ADC with the result, using the carry that came out of the rotation
+    fn rotate_right_add(&mut self, value: Byte) -> Byte {
+        let result = self.rotate_right(value);
 
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
+        self.add_with_carry(result);
 
-                        self.pc = new_location;
-                    }
-                }
-                BNE => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
+        result
+    }
 
-                    if !self.p.zero_flag() {
-                        cycles -= 1;
+    /// DCP, DEC on memory then CMP with the result
+    fn decrement_compare(&mut self, value: Byte) -> Byte {
+        let result = value.wrapping_sub(1);
 
-                        let new_location;
+        self.compare(self.a, result);
 
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
+        result
+    }
 
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
+    /// ISC, INC on memory then SBC with the result
+    fn increment_subtract(&mut self, value: Byte) -> Byte {
+        let result = value.wrapping_add(1);
 
-                        self.pc = new_location;
-                    }
-                }
-                BPL => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
-                    
-                    if !self.p.negative_flag() {
-                        cycles -= 1;
-
-                        let new_location;
-
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
-
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
-
-                        self.pc = new_location;
-                    }
-                }
-                BVC => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
+        self.subtract_with_carry(result);
 
-                    if !self.p.overflow_flag() {
-                        cycles -= 1;
+        result
+    }
 
-                        let new_location;
+    /// ARR, AND then ROR on the accumulator with flags coming from the adder, which in decimal mode
+    /// also fixes up the result like ADC would
+    fn and_rotate_right(&mut self, value: Byte) {
+        let and = self.a & value;
+        let carry = self.p.carry_flag();
+        let mut result = (and >> 1) | ((carry as Byte) << 7);
 
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
+        self.set_zero_negative_flags(result);
+        self.p.set_overflow((and ^ result) & 0b01000000 == 0b01000000);
 
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
+        if !self.p.decimal_flag() || !self.variant.has_decimal_mode() {
+            self.p.set_carry(and & 0b10000000 == 0b10000000);
+            self.a = result;
 
-                        self.pc = new_location;
-                    }
-                }
-                BVS => {
-                    let offset = self.fetch_byte(&mut cycles, memory);
-                    cycles -= 1;
+            return;
+        }
 
-                    if self.p.overflow_flag() {
-                        let new_location;
+        // N, Z and V above come from the binary result, the digits are then adjusted
+        if (and & 0x0F) + (and & 0x01) > 0x05 {
+            result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+        }
 
-                        if offset >= 128 {
-                            new_location = self.pc - (256u16.wrapping_sub(offset as u16)) as u16;
-                        } else {
-                            new_location = self.pc + offset as u16;
-                        }
+        let high_digit_adjust = (and >> 4) + ((and >> 4) & 0x01) > 0x05;
+        if high_digit_adjust {
+            result = result.wrapping_add(0x60);
+        }
 
-                        if self.pc & 0xFF00 != new_location & 0xFF00 {
-                            cycles -= 1;
-                            cycles -= 1;
-                        }
+        self.p.set_carry(high_digit_adjust);
+        self.a = result;
+    }
 
-                        self.pc = new_location;
-                    }
-                }
-                CLC => {
-                    self.p.set_carry(false);
-                    cycles -= 1;
-                }
-                CLD => {
-                    self.p.set_decimal(false);
-                    cycles -= 1;
-                }
-                CLI => {
-                    self.p.set_interrupt(false);
-                    cycles -= 1;
-                }
-                CLV => {
-                    self.p.set_overflow(false);
-                    cycles -= 1;
-                }
-                SEC => {
-                    self.p.set_carry(true);
-                    cycles -= 1;
-                }
-                SED => {
-                    self.p.set_decimal(true);
-                    cycles -= 1;
-                }
-                SEI => {
-                    self.p.set_interrupt(true);
-                    cycles -= 1;
-                }
-                BRK => {
-                    // Discarded data
-                    cycles -= 1;
+    /// SHA, SHX, SHY and TAS store `value` ANDed with the high byte of `address` plus one. When
+    /// indexing crosses a page that same value replaces the high byte of the effective address\
+    /// takes 2 cycles
+    fn store_and_high<B: Bus>(&mut self, bus: &mut B, address: Word, index: Byte, value: Byte) {
+        let effective_address = self.index_address(bus, address, index, true);
+        let value = value & ((address >> 8) as Byte).wrapping_add(1);
 
-                    memory[self.sp as usize] = (self.pc >> 8) as u8;
-                    self.sp -= 1;
-                    cycles -= 1;
+        let effective_address = if (address & 0xFF00) != (effective_address & 0xFF00) {
+            ((value as Word) << 8) | (effective_address & 0x00FF)
+        } else {
+            effective_address
+        };
 
-                    memory[self.sp as usize] = self.pc as u8;
-                    self.sp -= 1;
-                    cycles -= 1;
+        self.write_memory(bus, effective_address, value);
+    }
 
-                    memory[self.sp as usize] = self.p.bits();
-                    self.sp -= 1;
-                    cycles -= 1;
+    /// ADC, the decimal flag selects between binary and BCD arithmetic
+    fn add_with_carry(&mut self, value: Byte) {
+        let carry = self.p.carry_flag() as u16;
+        let binary = self.a as u16 + value as u16 + carry;
 
-                    let low_byte = memory[0xFFFE];
-                    cycles -= 1;
+        // both operands have the same sign and the result has the other one
+        let overflow = (!(self.a ^ value) & (self.a ^ binary as u8)) & 0b10000000 != 0;
 
-                    let high_byte = memory[0xFFFF];
-                    cycles -= 1;
+        if !self.p.decimal_flag() || !self.variant.has_decimal_mode() {
+            self.a = binary as u8;
 
-                    self.pc = ((high_byte as u16) << 8) | low_byte as u16;
+            self.p.set_carry(binary > 0xFF);
+            self.p.set_overflow(overflow);
+            self.p.set_zero(self.a == 0);
+            self.p.set_negative(self.a & 0b10000000 == 0b10000000);
 
-                    self.p.set_break(true);
-                }
-                // todo: research nop behavior
-                NOP => (),
-                RTI => {
-                    // Discarded data
-                    cycles -= 1;
+            return;
+        }
 
-                    // Discarded data
-                    cycles -= 1;
+        // add the low digits and carry into the high digit if needed
+        let mut low = (self.a & 0x0F) as i16 + (value & 0x0F) as i16 + carry as i16;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
 
-                    self.sp += 1;
-                    self.p = memory[self.sp as usize].into();
-                    cycles -= 1;
+        // N and V come from the sum before the high digit is adjusted,
+        // V being computed with the high digits taken as signed numbers
+        let unadjusted = (self.a & 0xF0) as i16 + (value & 0xF0) as i16 + low;
+        let signed = (self.a & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + low;
 
-                    self.sp += 1;
-                    let low_byte = memory[self.sp as usize];
-                    cycles -= 1;
+        let mut result = unadjusted;
+        if result >= 0xA0 {
+            result += 0x60;
+        }
 
-                    self.sp += 1;
-                    let high_byte = memory[self.sp as usize];
-                    cycles -= 1;
+        self.a = result as u8;
+        self.p.set_carry(result >= 0x100);
+        self.p.set_overflow(!(-128..=127).contains(&signed));
 
-                    self.pc = ((high_byte as u16) << 8) | low_byte as u16;
-                }
-                _ => panic!("Tried to execute unknown instruction"),
+        match self.variant {
+            // the NMOS chip takes Z from the binary sum and N from the unadjusted one
+            Variant::Nmos6502 | Variant::Ricoh2A03 => {
+                self.p.set_zero(binary as u8 == 0);
+                self.p.set_negative(unadjusted & 0b10000000 == 0b10000000);
+            }
+            Variant::Cmos65C02 | Variant::Rockwell65C02 | Variant::Wdc65C02 => {
+                self.p.set_zero(self.a == 0);
+                self.p.set_negative(self.a & 0b10000000 == 0b10000000);
             }
         }
     }
 
-    fn set_lda_flags(&mut self) {
-        self.p.set_zero(self.a == 0);
+    /// SBC, the decimal flag selects between binary and BCD arithmetic
+    fn subtract_with_carry(&mut self, value: Byte) {
+        let borrow = !self.p.carry_flag() as i16;
+        let a = self.a;
 
-        self.p.set_negative(self.a & 0b10000000 == 0b10000000);
-    }
+        // binary subtraction is an addition of the complement
+        let binary = a as u16 + !value as u16 + self.p.carry_flag() as u16;
+        let overflow = ((a ^ value) & (a ^ binary as u8)) & 0b10000000 != 0;
 
-    fn set_ldx_flags(&mut self) {
-        self.p.set_zero(self.x == 0);
+        self.p.set_carry(binary > 0xFF);
+        self.p.set_overflow(overflow);
 
-        self.p.set_negative(self.x & 0b10000000 == 0b10000000);
-    }
+        if !self.p.decimal_flag() || !self.variant.has_decimal_mode() {
+            self.a = binary as u8;
 
-    fn set_ldy_flags(&mut self) {
-        self.p.set_zero(self.y == 0);
+            self.p.set_zero(self.a == 0);
+            self.p.set_negative(self.a & 0b10000000 == 0b10000000);
 
-        self.p.set_negative(self.y & 0b10000000 == 0b10000000);
-    }
+            return;
+        }
 
-    fn set_adc_sbc_flags(&mut self, overflow: bool, initial_value: u8) {
-        self.p.set_carry(overflow);
+        let low = (a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
 
-        // incorrect sign means there was an overflow
-        self.p.set_overflow((initial_value & 0b10000000) != (self.a & 0b10000000));
+        match self.variant {
+            // every flag comes from the binary subtraction on the NMOS chip
+            Variant::Nmos6502 | Variant::Ricoh2A03 => {
+                let low = if low < 0 { ((low - 0x06) & 0x0F) - 0x10 } else { low };
 
-        self.p.set_zero(self.a == 0);
+                let mut result = (a & 0xF0) as i16 - (value & 0xF0) as i16 + low;
+                if result < 0 {
+                    result -= 0x60;
+                }
+
+                self.a = result as u8;
+                self.p.set_zero(binary as u8 == 0);
+                self.p.set_negative(binary & 0b10000000 == 0b10000000);
+            }
+            Variant::Cmos65C02 | Variant::Rockwell65C02 | Variant::Wdc65C02 => {
+                let mut result = a as i16 - value as i16 - borrow;
+                if result < 0 {
+                    result -= 0x60;
+                }
+                if low < 0 {
+                    result -= 0x06;
+                }
 
-        // if A has negative bit on
-        self.p.set_negative((self.a & 0b10000000) == 0b1000000);
+                self.a = result as u8;
+                self.p.set_zero(self.a == 0);
+                self.p.set_negative(self.a & 0b10000000 == 0b10000000);
+            }
+        }
     }
-}
\ No newline at end of file
+}
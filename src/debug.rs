@@ -0,0 +1,343 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use bitflags::bitflags;
+
+use crate::cpu::{Registers, Status};
+use crate::{Byte, Word};
+
+bitflags! {
+    /// The kinds of access a `Watchpoint` reacts to
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Watch: u8 {
+        const READ = 0b001;
+        const WRITE = 0b010;
+        /// An instruction is about to execute from the address
+        const EXECUTE = 0b100;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    Pc,
+    Sp,
+    A,
+    X,
+    Y,
+    /// Status as `Registers::p` has it, B and bit 5 set
+    P,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// A test on the registers, parsed from text like `A == $42 && !C || X >= 10`.\
+/// `&&` binds tighter than `||`, numbers are `$hex`, `%binary` or decimal and a flag name
+/// alone (`N V D I Z C`) is true when the flag is set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Compare(Register, Comparison, Word),
+    /// True when the flag is in the given state
+    Flag(Status, bool),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    pub fn evaluate(&self, registers: &Registers) -> bool {
+        match self {
+            Condition::Compare(register, comparison, value) => {
+                let actual = match register {
+                    Register::Pc => registers.pc,
+                    Register::Sp => registers.sp as Word,
+                    Register::A => registers.a as Word,
+                    Register::X => registers.x as Word,
+                    Register::Y => registers.y as Word,
+                    Register::P => registers.p as Word,
+                };
+
+                match comparison {
+                    Comparison::Equal => actual == *value,
+                    Comparison::NotEqual => actual != *value,
+                    Comparison::Less => actual < *value,
+                    Comparison::LessOrEqual => actual <= *value,
+                    Comparison::Greater => actual > *value,
+                    Comparison::GreaterOrEqual => actual >= *value,
+                }
+            }
+            Condition::Flag(flag, set) => (registers.p & flag.bits() != 0) == *set,
+            Condition::All(conditions) => conditions.iter().all(|condition| condition.evaluate(registers)),
+            Condition::Any(conditions) => conditions.iter().any(|condition| condition.evaluate(registers)),
+        }
+    }
+}
+
+const REGISTERS: [(&str, Register); 6] = [
+    ("PC", Register::Pc),
+    ("SP", Register::Sp),
+    ("A", Register::A),
+    ("X", Register::X),
+    ("Y", Register::Y),
+    ("P", Register::P),
+];
+
+const FLAGS: [(&str, Status); 6] =
+    [("N", Status::N), ("V", Status::V), ("D", Status::D), ("I", Status::I), ("Z", Status::Z), ("C", Status::C)];
+
+/// Two character operators come first so `<=` is not taken for `<`
+const COMPARISONS: [(&str, Comparison); 6] = [
+    ("==", Comparison::Equal),
+    ("!=", Comparison::NotEqual),
+    ("<=", Comparison::LessOrEqual),
+    (">=", Comparison::GreaterOrEqual),
+    ("<", Comparison::Less),
+    (">", Comparison::Greater),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseConditionError {
+    pub message: String,
+}
+
+impl fmt::Display for ParseConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseConditionError {}
+
+impl FromStr for Condition {
+    type Err = ParseConditionError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = |message: String| ParseConditionError { message };
+
+        let parse_clause = |clause: &str| -> Result<Condition, ParseConditionError> {
+            let clause = clause.trim();
+            let (name, set) = match clause.strip_prefix('!') {
+                Some(name) => (name.trim(), false),
+                None => (clause, true),
+            };
+
+            if let Some((_, flag)) = FLAGS.iter().find(|(flag, _)| flag.eq_ignore_ascii_case(name)) {
+                return Ok(Condition::Flag(*flag, set));
+            }
+
+            let Some((operator, comparison)) = COMPARISONS.iter().find(|(operator, _)| clause.contains(operator)) else {
+                return Err(error(format!("expected a flag or a comparison, found `{}`", clause)));
+            };
+
+            let (left, right) = clause.split_once(operator).unwrap();
+            let register = REGISTERS
+                .iter()
+                .find(|(register, _)| register.eq_ignore_ascii_case(left.trim()))
+                .map(|(_, register)| *register)
+                .ok_or_else(|| error(format!("unknown register `{}`", left.trim())))?;
+
+            let right = right.trim();
+            let value = match (right.strip_prefix('$'), right.strip_prefix('%')) {
+                (Some(hex), _) => Word::from_str_radix(hex, 16),
+                (_, Some(binary)) => Word::from_str_radix(binary, 2),
+                _ => right.parse(),
+            }
+            .map_err(|_| error(format!("invalid number `{}`", right)))?;
+
+            Ok(Condition::Compare(register, *comparison, value))
+        };
+
+        let mut any = text
+            .split("||")
+            .map(|conjunction| {
+                let mut all = conjunction.split("&&").map(parse_clause).collect::<Result<Vec<_>, _>>()?;
+
+                Ok(if all.len() == 1 { all.remove(0) } else { Condition::All(all) })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(if any.len() == 1 { any.remove(0) } else { Condition::Any(any) })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, conditions: &[Condition], separator: &str| {
+            for (index, condition) in conditions.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " {} ", separator)?;
+                }
+                write!(f, "{}", condition)?;
+            }
+            Ok(())
+        };
+
+        match self {
+            Condition::Compare(register, comparison, value) => {
+                let register = REGISTERS.iter().find(|(_, other)| other == register).unwrap().0;
+                let operator = COMPARISONS.iter().find(|(_, other)| other == comparison).unwrap().0;
+                write!(f, "{} {} ${:X}", register, operator, value)
+            }
+            Condition::Flag(flag, set) => {
+                let name = FLAGS.iter().find(|(_, other)| other == flag).map_or("?", |(name, _)| name);
+                write!(f, "{}{}", if *set { "" } else { "!" }, name)
+            }
+            Condition::All(conditions) => join(f, conditions, "&&"),
+            Condition::Any(conditions) => join(f, conditions, "||"),
+        }
+    }
+}
+
+/// Stops the CPU before it executes the instruction at `address`, if `condition` holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub address: Word,
+    pub condition: Option<Condition>,
+}
+
+/// Stops the CPU when an address in `range` is accessed in one of the `kinds` ways, if `condition`
+/// holds at that time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<Word>,
+    pub kinds: Watch,
+    pub condition: Option<Condition>,
+}
+
+/// Why `CPU::step` returned `EmulatorError::DebugStop`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// The instruction at `address` has not been executed
+    Breakpoint { address: Word },
+    /// `value` was read from or written to `address`, for `Watch::EXECUTE` it is the opcode and
+    /// the instruction has not been executed. Reads and writes stop the CPU once the instruction
+    /// that made them is complete
+    Watchpoint { address: Word, kind: Watch, value: Byte },
+    /// `conditions[index]` of the debugger became true, the next instruction has not been executed
+    Condition { index: usize },
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::Breakpoint { address } => write!(f, "breakpoint at ${:04X}", address),
+            StopReason::Watchpoint { address, kind, value } => {
+                let kind = match *kind {
+                    Watch::READ => "read",
+                    Watch::WRITE => "write",
+                    _ => "execute",
+                };
+                write!(f, "watchpoint: {} of ${:02X} at ${:04X}", kind, value, address)
+            }
+            StopReason::Condition { index } => write!(f, "condition {} is true", index),
+        }
+    }
+}
+
+/// Breakpoints, watchpoints and conditions checked by the CPU, install one with `CPU::debugger`.\
+/// Every bus access counts for read and write watchpoints, opcode fetches and the discarded reads
+/// of the addressing modes included.\
+/// After a stop before an instruction the next `step` executes that instruction, so calling
+/// `execute` again resumes the program
+#[derive(Debug, Clone, Default)]
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    pub watchpoints: Vec<Watchpoint>,
+    /// Stop before any instruction for which one of these holds
+    pub conditions: Vec<Condition>,
+
+    // address of the instruction the last stop happened before, it runs on the next step
+    resume_at: Option<Word>,
+    // read or write watchpoint hit by the instruction in progress
+    pending: Option<StopReason>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_breakpoint(&mut self, address: Word, condition: Option<Condition>) {
+        self.breakpoints.push(Breakpoint { address, condition });
+    }
+
+    /// Removes every breakpoint at `address`, returns whether there was any
+    pub fn remove_breakpoint(&mut self, address: Word) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.address != address);
+
+        self.breakpoints.len() != len
+    }
+
+    pub fn add_watchpoint(&mut self, range: RangeInclusive<Word>, kinds: Watch, condition: Option<Condition>) {
+        self.watchpoints.push(Watchpoint { range, kinds, condition });
+    }
+
+    /// Lets the instruction at `pc` run on the next step even if something would stop it
+    pub fn resume(&mut self, pc: Word) {
+        self.resume_at = Some(pc);
+    }
+
+    /// Called before the instruction at `registers.pc` executes
+    pub(crate) fn check_instruction(&mut self, registers: &Registers, opcode: Byte) -> Option<StopReason> {
+        let pc = registers.pc;
+
+        if self.resume_at.take() == Some(pc) {
+            return None;
+        }
+
+        let reason = if self.breakpoints.iter().any(|breakpoint| breakpoint.address == pc && holds(&breakpoint.condition, registers)) {
+            Some(StopReason::Breakpoint { address: pc })
+        } else if self.watchpoints.iter().any(|watchpoint| {
+            watchpoint.kinds.contains(Watch::EXECUTE) && watchpoint.range.contains(&pc) && holds(&watchpoint.condition, registers)
+        }) {
+            Some(StopReason::Watchpoint { address: pc, kind: Watch::EXECUTE, value: opcode })
+        } else {
+            self.conditions
+                .iter()
+                .position(|condition| condition.evaluate(registers))
+                .map(|index| StopReason::Condition { index })
+        };
+
+        if reason.is_some() {
+            self.resume_at = Some(pc);
+        }
+
+        reason
+    }
+
+    /// Whether a read or write of `address` could hit a watchpoint, checked before the registers
+    /// are gathered for `check_access`
+    pub(crate) fn watches(&self, address: Word) -> bool {
+        self.pending.is_none() && self.watchpoints.iter().any(|watchpoint| watchpoint.range.contains(&address))
+    }
+
+    /// Called for every bus access, the first hit is kept until `take_pending`
+    pub(crate) fn check_access(&mut self, registers: &Registers, address: Word, kind: Watch, value: Byte) {
+        let hit = self.watchpoints.iter().any(|watchpoint| {
+            watchpoint.kinds.contains(kind)
+                && watchpoint.range.contains(&address)
+                && holds(&watchpoint.condition, registers)
+        });
+
+        if hit && self.pending.is_none() {
+            self.pending = Some(StopReason::Watchpoint { address, kind, value });
+        }
+    }
+
+    pub(crate) fn take_pending(&mut self) -> Option<StopReason> {
+        self.pending.take()
+    }
+}
+
+/// Breakpoints and watchpoints without a condition always hold
+fn holds(condition: &Option<Condition>, registers: &Registers) -> bool {
+    condition.as_ref().map_or(true, |condition| condition.evaluate(registers))
+}
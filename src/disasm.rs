@@ -0,0 +1,129 @@
+use std::fmt::{self, Write};
+use std::ops::RangeInclusive;
+
+use crate::bus::Bus;
+use crate::cpu::Variant;
+use crate::opcodes::{self, Mode};
+use crate::{Byte, Word};
+
+use Mode::*;
+
+/// A decoded instruction, `Display` prints it in the usual assembler syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub address: Word,
+    pub opcode: Byte,
+    pub mnemonic: &'static str,
+    pub mode: Mode,
+    /// The operand bytes as a little endian value, zero for implied and accumulator instructions.\
+    /// `ZeroPageRelative` keeps the zero page address in the low byte and the offset in the high byte
+    pub operand: Word,
+    /// Opcode included
+    pub length: Word,
+    /// Where a branch goes when taken
+    pub target: Option<Word>,
+}
+
+impl Instruction {
+    /// The opcode and operand bytes
+    pub fn bytes(&self) -> Vec<Byte> {
+        let [low, high] = self.operand.to_le_bytes();
+        [self.opcode, low, high][..self.length as usize].to_vec()
+    }
+
+    /// Address of the instruction that follows
+    pub const fn next(&self) -> Word {
+        self.address.wrapping_add(self.length)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = self.operand;
+
+        write!(f, "{}", self.mnemonic)?;
+
+        match self.mode {
+            Implied => Ok(()),
+            Accumulator => write!(f, " A"),
+            Immediate => write!(f, " #${:02X}", operand),
+            ZeroPage => write!(f, " ${:02X}", operand),
+            ZeroPageX => write!(f, " ${:02X},X", operand),
+            ZeroPageY => write!(f, " ${:02X},Y", operand),
+            Absolute => write!(f, " ${:04X}", operand),
+            AbsoluteX => write!(f, " ${:04X},X", operand),
+            AbsoluteY => write!(f, " ${:04X},Y", operand),
+            Indirect => write!(f, " (${:04X})", operand),
+            IndirectX => write!(f, " (${:02X},X)", operand),
+            IndirectY => write!(f, " (${:02X}),Y", operand),
+            ZeroPageIndirect => write!(f, " (${:02X})", operand),
+            AbsoluteIndirectX => write!(f, " (${:04X},X)", operand),
+            Relative => write!(f, " ${:04X}", self.target.unwrap_or_default()),
+            ZeroPageRelative => write!(f, " ${:02X},${:04X}", operand & 0xFF, self.target.unwrap_or_default()),
+        }
+    }
+}
+
+/// Decodes the instruction whose opcode is `bytes[0]`, as if it were located at `address`.\
+/// Bytes past the instruction length are ignored
+pub fn decode(bytes: [Byte; 3], address: Word, variant: Variant) -> Instruction {
+    let opcode = bytes[0];
+    let metadata = opcodes::lookup(variant, opcode);
+    let (mnemonic, mode, length) = (metadata.mnemonic, metadata.mode, metadata.length());
+
+    let operand = match length {
+        1 => 0,
+        2 => bytes[1] as Word,
+        _ => Word::from_le_bytes([bytes[1], bytes[2]]),
+    };
+
+    let next = address.wrapping_add(length);
+    let target = match mode {
+        Relative => Some(next.wrapping_add(bytes[1] as i8 as Word)),
+        ZeroPageRelative => Some(next.wrapping_add(bytes[2] as i8 as Word)),
+        _ => None,
+    };
+
+    Instruction { address, opcode, mnemonic, mode, operand, length, target }
+}
+
+/// Decodes the instruction at `address`, reading memory with `Bus::peek` so devices are left alone
+pub fn disassemble<B: Bus + ?Sized>(bus: &B, address: Word, variant: Variant) -> Instruction {
+    let bytes = [bus.peek(address), bus.peek(address.wrapping_add(1)), bus.peek(address.wrapping_add(2))];
+
+    decode(bytes, address, variant)
+}
+
+/// Disassembles every instruction starting inside `range`, one line each:
+///
+/// ```text
+/// E000  A9 10     LDA #$10
+/// E002  D0 FC     BNE $E000
+/// ```
+pub fn listing<B: Bus + ?Sized>(bus: &B, range: RangeInclusive<Word>, variant: Variant) -> String {
+    let mut listing = String::new();
+    let mut address = *range.start();
+
+    while range.contains(&address) {
+        let instruction = disassemble(bus, address, variant);
+        let _ = writeln!(listing, "{}", listing_line(&instruction));
+
+        // stop instead of wrapping around to $0000
+        match address.checked_add(instruction.length) {
+            Some(next) => address = next,
+            None => break,
+        }
+    }
+
+    listing
+}
+
+/// One line of `listing`, without the newline
+pub fn listing_line(instruction: &Instruction) -> String {
+    format!("{:04X}  {:<8}  {}", instruction.address, hex_bytes(instruction), instruction)
+}
+
+/// The instruction bytes as space separated hex, `A9 10`
+pub fn hex_bytes(instruction: &Instruction) -> String {
+    instruction.bytes().iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use crate::bus::BusFault;
+use crate::debug::StopReason;
+use crate::{Byte, Word};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmulatorError {
+    /// `opcode` at `pc` is not an instruction the CPU knows
+    IllegalOpcode { opcode: Byte, pc: Word },
+    /// The NMOS `opcode` at `pc` locked up the CPU, only a reset gets it going again
+    Jammed { opcode: Byte, pc: Word },
+    /// The instruction at `pc` needed `needed` cycles but only `left` were left in the budget
+    BudgetExhausted { pc: Word, needed: u32, left: u32 },
+    /// The bus reported `fault` while executing the instruction at `pc`
+    BusFault { pc: Word, fault: BusFault },
+    /// The debugger stopped the CPU at the instruction at `pc`, see `StopReason` for whether it ran
+    DebugStop { pc: Word, reason: StopReason },
+}
+
+impl fmt::Display for EmulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmulatorError::IllegalOpcode { opcode, pc } => {
+                write!(f, "illegal opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+            EmulatorError::Jammed { opcode, pc } => {
+                write!(f, "CPU jammed by opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+            EmulatorError::BudgetExhausted { pc, needed, left } => write!(
+                f,
+                "instruction at ${:04X} needs {} cycles but only {} are left",
+                pc, needed, left
+            ),
+            EmulatorError::BusFault { pc, fault } => {
+                write!(f, "bus fault at ${:04X}: {}", pc, fault)
+            }
+            EmulatorError::DebugStop { pc, reason } => write!(f, "stopped at ${:04X}: {}", pc, reason),
+        }
+    }
+}
+
+impl std::error::Error for EmulatorError {}
@@ -0,0 +1,445 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::ops::RangeInclusive;
+
+use crate::bus::Bus;
+use crate::cpu::{Registers, CPU};
+use crate::debug::{Debugger, StopReason, Watch};
+use crate::error::EmulatorError;
+use crate::{Byte, Word};
+
+/// Describes the registers to GDB, in the order `g` and `p` use
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.emulator6502.cpu">
+    <reg name="pc" bitsize="16" type="code_ptr" regnum="0"/>
+    <reg name="sp" bitsize="8" type="uint8"/>
+    <reg name="a" bitsize="8" type="uint8"/>
+    <reg name="x" bitsize="8" type="uint8"/>
+    <reg name="y" bitsize="8" type="uint8"/>
+    <reg name="p" bitsize="8" type="uint8"/>
+  </feature>
+</target>
+"#;
+
+/// Largest packet GDB may send or ask for, in characters. Memory goes over the wire as two hex
+/// digits per byte so `m` and `M` are limited to half of it
+const PACKET_SIZE: usize = 0x1000;
+
+/// Instructions `c` runs between two checks for an interrupt from GDB
+const INTERRUPT_POLL: usize = 4096;
+
+/// The byte GDB sends when the user presses Ctrl-C
+const INTERRUPT: u8 = 0x03;
+
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/// Accepts a single GDB connection on `address` and serves it, see `serve`
+pub fn listen<A: ToSocketAddrs, B: Bus>(address: A, cpu: &mut CPU, bus: &mut B) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    let (stream, _) = listener.accept()?;
+
+    serve(stream, cpu, bus)
+}
+
+/// Lets GDB control `cpu` through the remote serial protocol until it detaches or kills the program.\
+/// Registers are pc, sp, a, x, y and p, `p` being the status as PHP pushes it. Memory is read with
+/// `Bus::peek` and written with `Bus::write`.\
+/// Software and hardware breakpoints become breakpoints of the CPU debugger, watch, rwatch and
+/// awatch become watchpoints. A debugger is installed if the CPU has none, GDB can only remove the
+/// breakpoints and watchpoints it inserted itself and those are removed when the session ends
+pub fn serve<B: Bus>(stream: TcpStream, cpu: &mut CPU, bus: &mut B) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    cpu.debugger.get_or_insert_with(Debugger::new);
+
+    let mut stub = GdbStub { stream, cpu, bus, no_ack: false, breakpoints: Vec::new(), watchpoints: Vec::new() };
+    let result = stub.run();
+
+    // whatever ended the session, the breakpoints GDB inserted should not outlive it
+    for address in std::mem::take(&mut stub.breakpoints) {
+        stub.remove_breakpoint(address);
+    }
+    for (range, kinds) in std::mem::take(&mut stub.watchpoints) {
+        stub.remove_watchpoint(&range, kinds);
+    }
+
+    result
+}
+
+struct GdbStub<'a, B: Bus> {
+    stream: TcpStream,
+    cpu: &'a mut CPU,
+    bus: &'a mut B,
+    // QStartNoAckMode was negotiated, packets are no longer acknowledged
+    no_ack: bool,
+    // inserted by GDB, `z` leaves the ones set up by whoever installed the debugger alone
+    breakpoints: Vec<Word>,
+    watchpoints: Vec<(RangeInclusive<Word>, Watch)>,
+}
+
+impl<B: Bus> GdbStub<'_, B> {
+    fn run(&mut self) -> io::Result<()> {
+        while let Some(packet) = self.receive()? {
+            let reply = match packet.as_bytes().first() {
+                Some(b'?') => stop_reply(SIGTRAP),
+                Some(b'g') => hex(&register_bytes(&self.cpu.registers())),
+                Some(b'G') => self.write_registers(&packet[1..]),
+                Some(b'p') => self.read_register(&packet[1..]),
+                Some(b'P') => self.write_register(&packet[1..]),
+                Some(b'm') => self.read_memory(&packet[1..]),
+                Some(b'M') => self.write_memory(&packet[1..]),
+                Some(b's') => self.resume(&packet[1..], false)?,
+                Some(b'c') => self.resume(&packet[1..], true)?,
+                Some(b'Z') => self.breakpoint(&packet[1..], true),
+                Some(b'z') => self.breakpoint(&packet[1..], false),
+                Some(b'H') => "OK".to_string(),
+                Some(b'D') => {
+                    self.send("OK")?;
+                    return Ok(());
+                }
+                Some(b'k') => return Ok(()),
+                Some(b'q' | b'Q') => self.query(&packet),
+                _ => String::new(),
+            };
+
+            self.send(&reply)?;
+        }
+
+        Ok(())
+    }
+
+    fn query(&mut self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            return format!("PacketSize={:x};qXfer:features:read+;QStartNoAckMode+", PACKET_SIZE);
+        }
+
+        if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            let Some((offset, len)) = range.split_once(',') else {
+                return "E01".to_string();
+            };
+            let (Some(offset), Some(len)) = (parse_hex(offset), parse_hex(len)) else {
+                return "E01".to_string();
+            };
+
+            let start = (offset as usize).min(TARGET_XML.len());
+            let end = (start + len as usize).min(TARGET_XML.len());
+            let marker = if end == TARGET_XML.len() { 'l' } else { 'm' };
+
+            return format!("{}{}", marker, &TARGET_XML[start..end]);
+        }
+
+        match packet {
+            "QStartNoAckMode" => {
+                self.no_ack = true;
+                "OK".to_string()
+            }
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn write_registers(&mut self, data: &str) -> String {
+        match parse_bytes(data).as_deref() {
+            Some(&[pc_low, pc_high, sp, a, x, y, p]) => {
+                let pc = Word::from_le_bytes([pc_low, pc_high]);
+                self.cpu.set_registers(Registers { pc, sp, a, x, y, p });
+                "OK".to_string()
+            }
+            _ => "E01".to_string(),
+        }
+    }
+
+    fn read_register(&mut self, number: &str) -> String {
+        let bytes = register_bytes(&self.cpu.registers());
+
+        match parse_hex(number) {
+            Some(0) => hex(&bytes[0..2]),
+            Some(number @ 1..=5) => hex(&bytes[number as usize + 1..number as usize + 2]),
+            _ => "E01".to_string(),
+        }
+    }
+
+    fn write_register(&mut self, assignment: &str) -> String {
+        let Some((number, value)) = assignment.split_once('=') else {
+            return "E01".to_string();
+        };
+
+        let mut registers = self.cpu.registers();
+
+        match (parse_hex(number), parse_bytes(value).as_deref()) {
+            (Some(0), Some(&[low, high])) => registers.pc = Word::from_le_bytes([low, high]),
+            (Some(1), Some(&[value])) => registers.sp = value,
+            (Some(2), Some(&[value])) => registers.a = value,
+            (Some(3), Some(&[value])) => registers.x = value,
+            (Some(4), Some(&[value])) => registers.y = value,
+            (Some(5), Some(&[value])) => registers.p = value,
+            _ => return "E01".to_string(),
+        }
+
+        self.cpu.set_registers(registers);
+        "OK".to_string()
+    }
+
+    /// `addr,length`, at most `PACKET_SIZE / 2` bytes
+    fn read_memory(&mut self, arguments: &str) -> String {
+        let Some((address, len)) = parse_address_length(arguments) else {
+            return "E01".to_string();
+        };
+
+        let bytes: Vec<Byte> = (0..len).map(|offset| self.bus.peek(address.wrapping_add(offset as Word))).collect();
+        hex(&bytes)
+    }
+
+    /// `addr,length:XX...`
+    fn write_memory(&mut self, arguments: &str) -> String {
+        let Some((range, data)) = arguments.split_once(':') else {
+            return "E01".to_string();
+        };
+
+        match (parse_address_length(range), parse_bytes(data)) {
+            (Some((address, len)), Some(bytes)) if bytes.len() == len => {
+                for (offset, byte) in bytes.into_iter().enumerate() {
+                    self.bus.write(address.wrapping_add(offset as Word), byte);
+                }
+                "OK".to_string()
+            }
+            _ => "E01".to_string(),
+        }
+    }
+
+    /// `type,addr,kind`, type 0 and 1 are breakpoints, 2 to 4 write, read and access watchpoints
+    fn breakpoint(&mut self, arguments: &str, insert: bool) -> String {
+        let fields: Vec<Option<u32>> = arguments.split(',').map(parse_hex).collect();
+        let (kind, address, len) = match fields[..] {
+            [Some(kind), Some(address), Some(len)] if address <= 0xFFFF => (kind, address as Word, len.max(1)),
+            _ => return "E01".to_string(),
+        };
+
+        let debugger = self.cpu.debugger.get_or_insert_with(Debugger::new);
+
+        let kinds = match kind {
+            0 | 1 if insert => {
+                debugger.add_breakpoint(address, None);
+                self.breakpoints.push(address);
+                return "OK".to_string();
+            }
+            0 | 1 => {
+                if let Some(index) = self.breakpoints.iter().position(|inserted| *inserted == address) {
+                    self.breakpoints.swap_remove(index);
+                    self.remove_breakpoint(address);
+                }
+                return "OK".to_string();
+            }
+            2 => Watch::WRITE,
+            3 => Watch::READ,
+            4 => Watch::READ | Watch::WRITE,
+            _ => return String::new(),
+        };
+
+        let range = address..=address.saturating_add((len - 1).min(0xFFFF) as Word);
+
+        if insert {
+            debugger.add_watchpoint(range.clone(), kinds, None);
+            self.watchpoints.push((range, kinds));
+        } else if let Some(index) = self.watchpoints.iter().position(|inserted| *inserted == (range.clone(), kinds)) {
+            self.watchpoints.swap_remove(index);
+            self.remove_watchpoint(&range, kinds);
+        }
+
+        "OK".to_string()
+    }
+
+    /// Removes one unconditional breakpoint at `address` from the debugger, the latest one added
+    fn remove_breakpoint(&mut self, address: Word) {
+        if let Some(debugger) = &mut self.cpu.debugger {
+            let found = debugger
+                .breakpoints
+                .iter()
+                .rposition(|breakpoint| breakpoint.address == address && breakpoint.condition.is_none());
+
+            if let Some(index) = found {
+                debugger.breakpoints.remove(index);
+            }
+        }
+    }
+
+    /// Removes one unconditional watchpoint on `range` for `kinds` from the debugger, the latest one added
+    fn remove_watchpoint(&mut self, range: &RangeInclusive<Word>, kinds: Watch) {
+        if let Some(debugger) = &mut self.cpu.debugger {
+            let found = debugger.watchpoints.iter().rposition(|watchpoint| {
+                watchpoint.range == *range && watchpoint.kinds == kinds && watchpoint.condition.is_none()
+            });
+
+            if let Some(index) = found {
+                debugger.watchpoints.remove(index);
+            }
+        }
+    }
+
+    /// `s` and `c`, with an optional address to resume at. Returns the stop reply
+    fn resume(&mut self, address: &str, continuing: bool) -> io::Result<String> {
+        if let Some(address) = parse_hex(address) {
+            self.cpu.pc = address as Word;
+        }
+
+        // leaving a breakpoint does not hit it again
+        let pc = self.cpu.pc;
+        if let Some(debugger) = &mut self.cpu.debugger {
+            debugger.resume(pc);
+        }
+
+        loop {
+            for _ in 0..INTERRUPT_POLL {
+                if self.cpu.stopped() {
+                    return Ok(stop_reply(SIGTRAP));
+                }
+
+                match self.cpu.step(&mut *self.bus) {
+                    Ok(_) if !continuing => return Ok(stop_reply(SIGTRAP)),
+                    Ok(_) => (),
+                    Err(EmulatorError::DebugStop { reason, .. }) => return Ok(debug_stop_reply(&reason)),
+                    Err(_) => return Ok(stop_reply(SIGILL)),
+                }
+            }
+
+            if self.interrupted()? {
+                return Ok(stop_reply(SIGINT));
+            }
+        }
+    }
+
+    /// Checks without blocking whether GDB sent an interrupt
+    fn interrupted(&mut self) -> io::Result<bool> {
+        let mut byte = [0];
+
+        self.stream.set_nonblocking(true)?;
+        let result = self.stream.read(&mut byte);
+        self.stream.set_nonblocking(false)?;
+
+        match result {
+            Ok(1) => Ok(byte[0] == INTERRUPT),
+            // the connection is gone, stop and let the next receive notice it
+            Ok(_) => Ok(true),
+            Err(error) if error.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Waits for the next `$data#checksum` packet and acknowledges it, `None` once the connection is closed
+    fn receive(&mut self) -> io::Result<Option<String>> {
+        loop {
+            // acknowledgements and interrupts while stopped are skipped
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'$') => (),
+                Some(_) => continue,
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(byte) => data.push(byte),
+                }
+            }
+
+            let mut checksum = [0; 2];
+            self.stream.read_exact(&mut checksum)?;
+
+            let expected = std::str::from_utf8(&checksum).ok().and_then(|text| u8::from_str_radix(text, 16).ok());
+            let valid = expected == Some(data.iter().fold(0, |sum: u8, byte| sum.wrapping_add(*byte)));
+
+            if !self.no_ack {
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+            }
+
+            if valid {
+                return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+            }
+        }
+    }
+
+    /// Sends `data` as a packet, resending it until GDB acknowledges it
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let checksum = data.bytes().fold(0, |sum: u8, byte| sum.wrapping_add(byte));
+        let packet = format!("${}#{:02x}", data, checksum);
+
+        loop {
+            self.stream.write_all(packet.as_bytes())?;
+
+            if self.no_ack {
+                return Ok(());
+            }
+
+            match self.read_byte()? {
+                Some(b'-') => continue,
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+/// pc (little endian), sp, a, x, y and p
+fn register_bytes(registers: &Registers) -> [Byte; 7] {
+    let [pc_low, pc_high] = registers.pc.to_le_bytes();
+    [pc_low, pc_high, registers.sp, registers.a, registers.x, registers.y, registers.p]
+}
+
+fn stop_reply(signal: u8) -> String {
+    format!("S{:02x}", signal)
+}
+
+fn debug_stop_reply(reason: &StopReason) -> String {
+    match reason {
+        StopReason::Watchpoint { address, kind, .. } if *kind != Watch::EXECUTE => {
+            let name = if *kind == Watch::WRITE { "watch" } else { "rwatch" };
+            format!("T{:02x}{}:{:04x};", SIGTRAP, name, address)
+        }
+        _ => stop_reply(SIGTRAP),
+    }
+}
+
+fn hex(bytes: &[Byte]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex(text: &str) -> Option<u32> {
+    u32::from_str_radix(text, 16).ok()
+}
+
+fn parse_bytes(text: &str) -> Option<Vec<Byte>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..text.len()).step_by(2).map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok()).collect()
+}
+
+fn parse_address_length(text: &str) -> Option<(Word, usize)> {
+    let (address, len) = text.split_once(',')?;
+    let address = parse_hex(address)?;
+
+    let len = parse_hex(len)? as usize;
+
+    if address > 0xFFFF || len > PACKET_SIZE / 2 {
+        return None;
+    }
+
+    Some((address as Word, len))
+}
@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+use crate::snapshot::CpuState;
+use crate::{Byte, Word};
+
+/// A memory write made by a journaled step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalWrite {
+    pub address: Word,
+    /// What `Bus::peek` returned before the write
+    pub old: Byte,
+    /// What `Bus::peek` returned after it
+    pub new: Byte,
+}
+
+/// What one step changed, enough to undo it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// The CPU before the step, `before.pc` is the instruction it executed
+    pub before: CpuState,
+    /// In the order they happened
+    pub writes: Vec<JournalWrite>,
+}
+
+/// The last `depth` steps of the CPU, install one with `CPU::journal` to be able to call `CPU::step_back`.\
+/// Only writes that change what `Bus::peek` sees are recorded, a write to ROM or open bus has
+/// nothing to undo. Steps that change nothing, like a debugger stop before an instruction, are not kept
+#[derive(Debug, Clone)]
+pub struct Journal {
+    depth: usize,
+    entries: VecDeque<JournalEntry>,
+    // the step in progress
+    recording: Option<JournalEntry>,
+}
+
+impl Journal {
+    pub fn new(depth: usize) -> Self {
+        Journal { depth, entries: VecDeque::with_capacity(depth.min(1024)), recording: None }
+    }
+
+    /// How many steps are kept at most, the oldest ones are dropped first
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recording = None;
+    }
+
+    /// Oldest first
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &JournalEntry> {
+        self.entries.iter()
+    }
+
+    /// How many steps `CPU::step_back` has to undo to get to just before the last write of `address`,
+    /// 1 when the last step made it
+    pub fn last_write(&self, address: Word) -> Option<usize> {
+        self.entries
+            .iter()
+            .rev()
+            .position(|entry| entry.writes.iter().any(|write| write.address == address))
+            .map(|index| index + 1)
+    }
+
+    pub(crate) fn begin(&mut self, before: CpuState) {
+        self.recording = Some(JournalEntry { before, writes: Vec::new() });
+    }
+
+    pub(crate) fn record_write(&mut self, address: Word, old: Byte, new: Byte) {
+        if let Some(entry) = &mut self.recording {
+            entry.writes.push(JournalWrite { address, old, new });
+        }
+    }
+
+    pub(crate) fn commit(&mut self, after: &CpuState) {
+        let Some(entry) = self.recording.take() else {
+            return;
+        };
+
+        if self.depth == 0 || (entry.writes.is_empty() && entry.before == *after) {
+            return;
+        }
+
+        if self.entries.len() == self.depth {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<JournalEntry> {
+        self.entries.pop_back()
+    }
+}
@@ -1,6 +1,17 @@
 mod types;
 pub use types::*;
 
+pub mod bus;
+pub mod error;
 pub mod memory;
 pub mod consts;
-pub mod cpu;
\ No newline at end of file
+pub mod cpu;
+pub mod trace;
+pub mod debug;
+pub mod opcodes;
+pub mod disasm;
+pub mod asm;
+pub mod monitor;
+pub mod gdb;
+pub mod snapshot;
+pub mod journal;
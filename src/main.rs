@@ -1,53 +1,119 @@
+use std::io::{self, BufRead, Write};
+use std::{env, fs, process};
 
-use std::fs::File;
-use std::io::Read;
+use emulator_6502::cpu::Variant;
+use emulator_6502::gdb;
+use emulator_6502::monitor::{Monitor, MonitorError};
+use emulator_6502::Word;
 
-use emulator_6502::consts::*;
-use emulator_6502::memory::Memory;
-use emulator_6502::cpu::CPU;
+const USAGE: &str = "usage: 6502-mon [--variant nmos|2a03|65c02|r65c02|w65c02] [--gdb HOST:PORT] [FILE [ADDRESS]]";
+
+fn variant(name: &str) -> Option<Variant> {
+    match name {
+        "nmos" => Some(Variant::Nmos6502),
+        "2a03" => Some(Variant::Ricoh2A03),
+        "65c02" => Some(Variant::Cmos65C02),
+        "r65c02" => Some(Variant::Rockwell65C02),
+        "w65c02" => Some(Variant::Wdc65C02),
+        _ => None,
+    }
+}
 
 fn main() {
-    let mut mem = Memory::new();
-    mem[0xFFFC] = 0x00;
-    mem[0xFFFD] = 0xE0;
-
-    let mut cpu = CPU::default();
-    cpu.reset(&mem);
-
-    mem[0xE000] = JSR; // 6
-    mem[0xE001] = 0x09;
-    mem[0xE002] = 0xE0;
-    mem[0xE003] = JSR; // 6
-    mem[0xE004] = 0x0C;
-    mem[0xE005] = 0xE0;
-    mem[0xE006] = JSR; // 2
-    mem[0xE007] = 0x12;
-    mem[0xE008] = 0xE0; // 2
-    mem[0xE009] = LDX_IM;
-    mem[0xE00A] = 0x00; // 6
-    mem[0xE00B] = RTS; // 2
-    mem[0xE00C] = INX;
-    mem[0xE00D] = CPX_IM;
-    mem[0xE00E] = 0x05;
-    mem[0xE00F] = BNE;
-    mem[0xE010] = 0xFB; // 6
-    mem[0xE011] = RTS; // 6
-    mem[0xE012] = LDY_IM; // 6
-    mem[0xE013] = 0x01; // 6
-
-    let mut file = File::open("./start").unwrap();
-    let written = file.read(&mut mem.bytes[..]).unwrap();
-
-    println!("written: {}", written);
-    println!("mem 0xE000: {:04X}", mem[0xE000]);
-
-    cpu.execute(68, &mut mem);
-
-    println!("final A: {}/{:02x}/{:08b}", cpu.a, cpu.a, cpu.a);
-    println!("final X: {}/{:02x}/{:08b}", cpu.x, cpu.x, cpu.x);
-    println!("final Y: {}/{:02x}/{:08b}", cpu.y, cpu.y, cpu.y);
-    println!("pc: {}/{:02x}/{:08b}", cpu.pc, cpu.pc, cpu.pc);
-    println!("sp: {}/{:02x}/{:08b}", cpu.sp, cpu.sp, cpu.sp);
-    println!("flags: {:08b}", cpu.p.bits());
-    println!("first 2 bytes of stack: {:2x} {:2x}", mem[0xff], mem[0xff - 1]);
+    let mut arguments: Vec<String> = env::args().skip(1).collect();
+    let mut selected = Variant::default();
+
+    if let Some(index) = arguments.iter().position(|argument| argument == "--variant") {
+        let Some(name) = arguments.get(index + 1).and_then(|name| variant(name)) else {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        };
+
+        selected = name;
+        arguments.drain(index..index + 2);
+    }
+
+    let mut listen = None;
+
+    if let Some(index) = arguments.iter().position(|argument| argument == "--gdb") {
+        let Some(address) = arguments.get(index + 1).cloned() else {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        };
+
+        listen = Some(address);
+        arguments.drain(index..index + 2);
+    }
+
+    let mut monitor = Monitor::new(selected);
+
+    // a file given on the command line is loaded and run from its reset vector
+    if let Some(path) = arguments.first() {
+        let address = match &arguments[1..] {
+            [] => Some(0),
+            [address] => Word::from_str_radix(address.strip_prefix('$').unwrap_or(address), 16).ok(),
+            _ => None,
+        };
+        let Some(address) = address else {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        };
+
+        let loaded = fs::read(path).map_err(MonitorError::from).and_then(|bytes| {
+            monitor.load(address, &bytes)?;
+            Ok(bytes.len())
+        });
+
+        match loaded {
+            Ok(len) => println!("loaded {} bytes at ${:04X}", len, address),
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            }
+        }
+
+        monitor.cpu.reset(&mut monitor.mem);
+    }
+
+    // with --gdb the debugger drives the CPU instead of the prompt
+    if let Some(address) = listen {
+        eprintln!("waiting for gdb on {}", address);
+
+        if let Err(error) = gdb::listen(address.as_str(), &mut monitor.cpu, &mut monitor.mem) {
+            eprintln!("gdb: {}", error);
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    print!("{}", monitor.registers());
+
+    let stdin = io::stdin();
+    let mut last = String::new();
+
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => (),
+        }
+
+        // an empty line repeats the previous command, handy for stepping
+        let line = if line.trim().is_empty() { last.clone() } else { line.trim().to_string() };
+
+        if matches!(line.as_str(), "q" | "quit") {
+            break;
+        }
+
+        match monitor.execute(&line) {
+            Ok(output) => print!("{}", output),
+            Err(error) => println!("error: {}", error),
+        }
+
+        last = line;
+    }
 }
@@ -1,5 +1,8 @@
-use std::ops::{self, Deref, DerefMut};
+use std::fmt;
+use std::ops::{self, Deref, DerefMut, RangeInclusive};
 
+use crate::bus::{Bus, BusFault};
+use crate::snapshot::{self, Reader, SnapshotError};
 use crate::{Byte, Word};
 
 pub struct Memory {
@@ -12,6 +15,56 @@ impl Memory {
     }
 }
 
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bus for Memory {
+    fn read(&mut self, address: Word) -> Byte {
+        self.bytes[address as usize]
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        self.bytes[address as usize] = value;
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        self.bytes[address as usize]
+    }
+
+    fn poke(&mut self, address: Word, value: Byte) {
+        self.bytes[address as usize] = value;
+    }
+
+    fn save_state(&self) -> Vec<Byte> {
+        self.bytes.to_vec()
+    }
+
+    fn load_state(&mut self, state: &[Byte]) -> Result<(), SnapshotError> {
+        if state.len() != self.bytes.len() {
+            return Err(SnapshotError::Mismatch { message: format!("expected 64kb of memory, found {} bytes", state.len()) });
+        }
+
+        self.bytes.copy_from_slice(state);
+        Ok(())
+    }
+}
+
+/// Zero filled memory with the given `(address, value)` pairs written into it
+impl FromIterator<(Word, Byte)> for Memory {
+    fn from_iter<I: IntoIterator<Item = (Word, Byte)>>(iter: I) -> Self {
+        let mut memory = Memory::new();
+
+        for (address, value) in iter {
+            memory.bytes[address as usize] = value;
+        }
+
+        memory
+    }
+}
+
 impl Deref for Memory {
     type Target = [u8];
 
@@ -40,4 +93,404 @@ impl ops::IndexMut<Word> for Memory {
     fn index_mut(&mut self, index: Word) -> &mut Self::Output {
         &mut self.bytes[index as usize]
     }
-}
\ No newline at end of file
+}
+
+/// What a ROM region does with the writes it receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomWrites {
+    /// Drop the write silently, like real hardware does
+    Ignore,
+    /// Drop the write but remember it, see `MemoryMap::take_rom_writes`.\
+    /// The CPU picks those up as `BusFault::ReadOnly` through `Bus::take_fault`
+    Report,
+}
+
+/// A write that hit a ROM region configured with `RomWrites::Report`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomWrite {
+    pub address: Word,
+    pub value: Byte,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    /// Two regions claim the same address
+    Overlap { first: RangeInclusive<Word>, second: RangeInclusive<Word> },
+    /// A mirror points at addresses that are not backed by a RAM, ROM or device region
+    MirrorTarget { mirror: RangeInclusive<Word>, target: RangeInclusive<Word> },
+    /// A ROM image does not fit in the address space starting at its base address
+    RomTooLarge { start: Word, len: usize },
+    /// A region or mirror target ends before it starts
+    EmptyRange { range: RangeInclusive<Word> },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Overlap { first, second } => write!(
+                f,
+                "region ${:04X}-${:04X} overlaps region ${:04X}-${:04X}",
+                second.start(), second.end(), first.start(), first.end()
+            ),
+            MapError::MirrorTarget { mirror, target } => write!(
+                f,
+                "mirror ${:04X}-${:04X} points at ${:04X}-${:04X} which is not a single backed region",
+                mirror.start(), mirror.end(), target.start(), target.end()
+            ),
+            MapError::RomTooLarge { start, len } => {
+                write!(f, "ROM of {} bytes does not fit at ${:04X}", len, start)
+            }
+            MapError::EmptyRange { range } => {
+                write!(f, "range ${:04X}-${:04X} is empty", range.start(), range.end())
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+enum Region {
+    Ram(Vec<Byte>),
+    Rom(Vec<Byte>, RomWrites),
+    Mirror(RangeInclusive<Word>),
+    Device(Box<dyn Bus>),
+    OpenBus,
+}
+
+struct Mapping {
+    range: RangeInclusive<Word>,
+    region: Region,
+}
+
+/// Builds a `MemoryMap` out of address ranges.\
+/// Addresses not covered by any region behave like open bus.
+///
+/// ```
+/// use emulator_6502::memory::{MemoryMapBuilder, RomWrites};
+///
+/// let rom = vec![0xEA; 0x2000];
+/// let map = MemoryMapBuilder::new()
+///     .ram(0x0000..=0x07FF)
+///     .mirror(0x0800..=0x1FFF, 0x0000..=0x07FF)
+///     .rom(0xE000, rom, RomWrites::Ignore)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct MemoryMapBuilder {
+    mappings: Vec<Mapping>,
+    errors: Vec<MapError>,
+}
+
+impl MemoryMapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Zero filled RAM covering `range`
+    pub fn ram(mut self, range: RangeInclusive<Word>) -> Self {
+        let len = range_len(&range);
+        self.mappings.push(Mapping { range, region: Region::Ram(vec![0; len]) });
+        self
+    }
+
+    /// Read-only `data` mapped starting at `start`
+    pub fn rom(mut self, start: Word, data: impl Into<Vec<Byte>>, writes: RomWrites) -> Self {
+        let data = data.into();
+
+        if data.is_empty() {
+            return self;
+        }
+
+        match start.checked_add((data.len() - 1).try_into().unwrap_or(Word::MAX)) {
+            Some(end) if data.len() <= 0x10000 => {
+                self.mappings.push(Mapping { range: start..=end, region: Region::Rom(data, writes) });
+            }
+            _ => self.errors.push(MapError::RomTooLarge { start, len: data.len() }),
+        }
+
+        self
+    }
+
+    /// Makes `range` repeat `target`, the n-th byte of `range` being the (n % target length)-th byte of `target`
+    pub fn mirror(mut self, range: RangeInclusive<Word>, target: RangeInclusive<Word>) -> Self {
+        self.mappings.push(Mapping { range, region: Region::Mirror(target) });
+        self
+    }
+
+    /// Hands every access inside `range` to `device`, with the full address
+    pub fn device(mut self, range: RangeInclusive<Word>, device: Box<dyn Bus>) -> Self {
+        self.mappings.push(Mapping { range, region: Region::Device(device) });
+        self
+    }
+
+    /// Nothing answers in `range`: reads return whatever was last on the data bus and writes are lost
+    pub fn open_bus(mut self, range: RangeInclusive<Word>) -> Self {
+        self.mappings.push(Mapping { range, region: Region::OpenBus });
+        self
+    }
+
+    pub fn build(mut self) -> Result<MemoryMap, MapError> {
+        if let Some(error) = self.errors.pop() {
+            return Err(error);
+        }
+
+        for mapping in &self.mappings {
+            if mapping.range.is_empty() {
+                return Err(MapError::EmptyRange { range: mapping.range.clone() });
+            }
+
+            if let Region::Mirror(target) = &mapping.region {
+                if target.is_empty() {
+                    return Err(MapError::EmptyRange { range: target.clone() });
+                }
+            }
+        }
+
+        self.mappings.sort_by_key(|mapping| *mapping.range.start());
+
+        for pair in self.mappings.windows(2) {
+            if pair[0].range.end() >= pair[1].range.start() {
+                return Err(MapError::Overlap { first: pair[0].range.clone(), second: pair[1].range.clone() });
+            }
+        }
+
+        for mapping in &self.mappings {
+            if let Region::Mirror(target) = &mapping.region {
+                let backed = self.mappings.iter().any(|other| {
+                    other.range.start() <= target.start()
+                        && other.range.end() >= target.end()
+                        && matches!(other.region, Region::Ram(_) | Region::Rom(..) | Region::Device(_))
+                });
+
+                if !backed {
+                    return Err(MapError::MirrorTarget { mirror: mapping.range.clone(), target: target.clone() });
+                }
+            }
+        }
+
+        Ok(MemoryMap { mappings: self.mappings, data_bus: 0, rom_writes: Vec::new() })
+    }
+}
+
+/// An address space made of RAM, ROM, mirrors, devices and open bus regions, see `MemoryMapBuilder`
+pub struct MemoryMap {
+    mappings: Vec<Mapping>,
+    data_bus: Byte,
+    rom_writes: Vec<RomWrite>,
+}
+
+impl MemoryMap {
+    /// Writes straight into the backing RAM or ROM without going through the bus,
+    /// meant for loading programs and patching ROM images.\
+    /// Returns false if nothing stores data at `address`
+    pub fn poke(&mut self, address: Word, value: Byte) -> bool {
+        let address = self.resolve(address);
+
+        match self.find_mut(address) {
+            Some((start, Region::Ram(bytes))) | Some((start, Region::Rom(bytes, _))) => {
+                bytes[(address - start) as usize] = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Writes `bytes` starting at `address` using `poke`
+    pub fn load(&mut self, address: Word, bytes: &[Byte]) {
+        for (offset, byte) in bytes.iter().enumerate() {
+            self.poke(address.wrapping_add(offset as Word), *byte);
+        }
+    }
+
+    /// Returns the writes that hit a `RomWrites::Report` region since the last call
+    pub fn take_rom_writes(&mut self) -> Vec<RomWrite> {
+        std::mem::take(&mut self.rom_writes)
+    }
+
+    /// Translates a mirrored address into the address it mirrors
+    fn resolve(&self, address: Word) -> Word {
+        match self.find(address) {
+            Some((start, Region::Mirror(target))) => {
+                let offset = (address - start) as usize % range_len(target);
+                target.start() + offset as Word
+            }
+            _ => address,
+        }
+    }
+
+    fn devices_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Bus>> {
+        self.mappings.iter_mut().filter_map(|mapping| match &mut mapping.region {
+            Region::Device(device) => Some(device),
+            _ => None,
+        })
+    }
+
+    fn find(&self, address: Word) -> Option<(Word, &Region)> {
+        self.mappings
+            .iter()
+            .find(|mapping| mapping.range.contains(&address))
+            .map(|mapping| (*mapping.range.start(), &mapping.region))
+    }
+
+    fn find_mut(&mut self, address: Word) -> Option<(Word, &mut Region)> {
+        self.mappings
+            .iter_mut()
+            .find(|mapping| mapping.range.contains(&address))
+            .map(|mapping| (*mapping.range.start(), &mut mapping.region))
+    }
+}
+
+impl Bus for MemoryMap {
+    fn read(&mut self, address: Word) -> Byte {
+        let address = self.resolve(address);
+
+        let value = match self.find_mut(address) {
+            Some((start, Region::Ram(bytes))) | Some((start, Region::Rom(bytes, _))) => bytes[(address - start) as usize],
+            Some((_, Region::Device(device))) => device.read(address),
+            _ => self.data_bus,
+        };
+
+        self.data_bus = value;
+        value
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        self.data_bus = value;
+
+        let address = self.resolve(address);
+
+        match self.find_mut(address) {
+            Some((start, Region::Ram(bytes))) => bytes[(address - start) as usize] = value,
+            Some((_, Region::Rom(_, RomWrites::Report))) => self.rom_writes.push(RomWrite { address, value }),
+            Some((_, Region::Device(device))) => device.write(address, value),
+            _ => (),
+        }
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        let address = self.resolve(address);
+
+        match self.find(address) {
+            Some((start, Region::Ram(bytes))) | Some((start, Region::Rom(bytes, _))) => bytes[(address - start) as usize],
+            Some((_, Region::Device(device))) => device.peek(address),
+            _ => self.data_bus,
+        }
+    }
+
+    /// Goes to the byte a mirror points at and leaves the data bus alone, devices get their own `poke`
+    fn poke(&mut self, address: Word, value: Byte) {
+        let address = self.resolve(address);
+
+        match self.find_mut(address) {
+            Some((start, Region::Ram(bytes))) | Some((start, Region::Rom(bytes, _))) => {
+                bytes[(address - start) as usize] = value;
+            }
+            Some((_, Region::Device(device))) => device.poke(address, value),
+            _ => (),
+        }
+    }
+
+    /// The first ROM write or device fault of the instruction. Everything else that went wrong
+    /// during it is dropped so it does not get blamed on the next one
+    fn take_fault(&mut self) -> Option<BusFault> {
+        let mut fault = self
+            .rom_writes
+            .drain(..)
+            .next()
+            .map(|RomWrite { address, value }| BusFault::ReadOnly { address, value });
+
+        for mapping in &mut self.mappings {
+            if let Region::Device(device) = &mut mapping.region {
+                if let Some(device_fault) = device.take_fault() {
+                    fault.get_or_insert(device_fault);
+                }
+            }
+        }
+
+        fault
+    }
+
+    /// The data bus value followed by the contents of every RAM region and the state of every
+    /// device, in address order. ROM is expected to be loaded again by whoever builds the map
+    fn save_state(&self) -> Vec<Byte> {
+        let mut state = vec![self.data_bus];
+
+        for mapping in &self.mappings {
+            match &mapping.region {
+                Region::Ram(bytes) => snapshot::write_block(&mut state, bytes),
+                Region::Device(device) => snapshot::write_block(&mut state, &device.save_state()),
+                _ => (),
+            }
+        }
+
+        state
+    }
+
+    fn load_state(&mut self, state: &[Byte]) -> Result<(), SnapshotError> {
+        let mut reader = Reader::new(state);
+        let data_bus = reader.byte()?;
+
+        // checked before anything changes so a state for another map leaves this one alone
+        let mut ram_blocks = Vec::new();
+        let mut device_blocks = Vec::new();
+        for mapping in &self.mappings {
+            if let Region::Ram(bytes) = &mapping.region {
+                let block = reader.block()?;
+
+                if block.len() != bytes.len() {
+                    return Err(SnapshotError::Mismatch {
+                        message: format!("RAM at ${:04X} has {} bytes, found {}", mapping.range.start(), bytes.len(), block.len()),
+                    });
+                }
+
+                ram_blocks.push(block);
+            } else if let Region::Device(_) = &mapping.region {
+                device_blocks.push(reader.block()?);
+            }
+        }
+
+        if !reader.is_empty() {
+            return Err(SnapshotError::Mismatch { message: "the memory map has fewer regions than the state".to_string() });
+        }
+
+        // devices can still refuse their state, the ones restored before a refusal are put back
+        let mut previous = Vec::new();
+        let mut refusal = None;
+        for (device, block) in self.devices_mut().zip(device_blocks) {
+            let state = device.save_state();
+
+            match device.load_state(block) {
+                Ok(()) => previous.push(state),
+                Err(error) => {
+                    refusal = Some(error);
+                    break;
+                }
+            }
+        }
+
+        if let Some(error) = refusal {
+            for (device, state) in self.devices_mut().zip(previous) {
+                let _ = device.load_state(&state);
+            }
+
+            return Err(error);
+        }
+
+        let mut ram_blocks = ram_blocks.into_iter();
+        for mapping in &mut self.mappings {
+            if let Region::Ram(bytes) = &mut mapping.region {
+                bytes.copy_from_slice(ram_blocks.next().unwrap());
+            }
+        }
+
+        self.data_bus = data_bus;
+        self.rom_writes.clear();
+
+        Ok(())
+    }
+}
+
+fn range_len(range: &RangeInclusive<Word>) -> usize {
+    (*range.end() as usize + 1).saturating_sub(*range.start() as usize)
+}
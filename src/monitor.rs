@@ -0,0 +1,468 @@
+use std::fmt::{self, Write};
+use std::ops::RangeInclusive;
+use std::{fs, io};
+
+use crate::asm::{self, AsmError};
+use crate::cpu::{Registers, Status, Undocumented, Variant, CPU};
+use crate::debug::{Condition, Debugger, StopReason, Watch};
+use crate::disasm;
+use crate::error::EmulatorError;
+use crate::journal::Journal;
+use crate::memory::Memory;
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::{Byte, Word};
+
+/// Instructions `continue` runs before giving control back when nothing stops it
+pub const CONTINUE_LIMIT: u64 = 10_000_000;
+
+/// Instructions `back` can undo
+pub const JOURNAL_DEPTH: usize = 10_000;
+
+pub const HELP: &str = "\
+addresses and values are hex, with an optional $, counts are decimal
+
+load FILE [ADDR]        copy a binary file into memory, at $0000 by default
+reset                   reset the CPU, PC comes from the vector at $FFFC
+save FILE               write the CPU and memory to a snapshot file
+restore FILE            go back to the state saved in a snapshot file
+s, step [N]             execute N instructions, 1 by default
+c, continue             run until a breakpoint, watchpoint, condition, error or STP
+back [N]                undo the last N instructions, 1 by default
+lastwrite ADDR          undo instructions until the one that last wrote to ADDR
+b, break [ADDR]         set a breakpoint at ADDR, list them without ADDR
+delete ADDR             remove the breakpoint at ADDR
+watch [KIND RANGE]      watch RANGE (ADDR or ADDR-ADDR) for KIND accesses, any of r, w and x
+unwatch ADDR            remove the watchpoints starting at ADDR
+cond [CONDITION]        stop before any instruction where CONDITION holds, like A == $42 && !C
+cond clear              remove all conditions
+r, regs [REG VALUE]     show the registers or set PC, SP, A, X, Y or P
+m, mem ADDR [LEN]       hex dump LEN bytes, 64 by default
+w, write ADDR BYTE...   store bytes starting at ADDR
+d, dis [ADDR] [N]       disassemble N instructions, 16 from PC by default
+a, asm ADDR SOURCE      assemble one line of source at ADDR
+q, quit                 leave the monitor
+
+break and watch take an optional `if CONDITION`, checked when they are hit.
+P is shown as PHP pushes it, with B and bit 5 set, the flag letters show them as -";
+
+#[derive(Debug)]
+pub enum MonitorError {
+    /// The command or its arguments do not make sense
+    Usage(String),
+    Io(io::Error),
+    Emulator(EmulatorError),
+    Asm(AsmError),
+    Snapshot(SnapshotError),
+}
+
+impl fmt::Display for MonitorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonitorError::Usage(message) => write!(f, "{}", message),
+            MonitorError::Io(error) => write!(f, "{}", error),
+            MonitorError::Emulator(error) => write!(f, "{}", error),
+            MonitorError::Asm(error) => write!(f, "{}", error),
+            MonitorError::Snapshot(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for MonitorError {}
+
+impl From<io::Error> for MonitorError {
+    fn from(error: io::Error) -> Self {
+        MonitorError::Io(error)
+    }
+}
+
+impl From<EmulatorError> for MonitorError {
+    fn from(error: EmulatorError) -> Self {
+        MonitorError::Emulator(error)
+    }
+}
+
+impl From<AsmError> for MonitorError {
+    fn from(error: AsmError) -> Self {
+        MonitorError::Asm(error)
+    }
+}
+
+impl From<SnapshotError> for MonitorError {
+    fn from(error: SnapshotError) -> Self {
+        MonitorError::Snapshot(error)
+    }
+}
+
+/// A CPU with 64kb of RAM driven by text commands, see `HELP` for the list.\
+/// Breakpoints and watchpoints live in the `Debugger` of the CPU.\
+/// The `6502-mon` binary reads the commands from a terminal
+pub struct Monitor {
+    pub cpu: CPU,
+    pub mem: Memory,
+}
+
+impl Monitor {
+    /// A monitor with zeroed memory, undocumented opcodes enabled and the last `JOURNAL_DEPTH`
+    /// instructions journaled
+    pub fn new(variant: Variant) -> Self {
+        let mut cpu = CPU::new(variant);
+        cpu.undocumented = Some(Undocumented::default());
+        cpu.debugger = Some(Debugger::new());
+        cpu.journal = Some(Journal::new(JOURNAL_DEPTH));
+
+        Monitor { cpu, mem: Memory::new() }
+    }
+
+    /// Copies `bytes` into memory starting at `address`, returns an error if they do not fit
+    pub fn load(&mut self, address: Word, bytes: &[Byte]) -> Result<(), MonitorError> {
+        let start = address as usize;
+
+        if start + bytes.len() > self.mem.bytes.len() {
+            return Err(MonitorError::Usage(format!("{} bytes do not fit at ${:04X}", bytes.len(), address)));
+        }
+
+        self.mem.bytes[start..start + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Runs one command line and returns what it prints
+    pub fn execute(&mut self, line: &str) -> Result<String, MonitorError> {
+        let Some(command) = line.split_whitespace().next() else {
+            return Ok(String::new());
+        };
+
+        // only breakpoints and watchpoints take a condition, other commands keep " if " in their arguments
+        let (line, condition) = match line.split_once(" if ") {
+            Some((line, condition)) if matches!(command, "b" | "break" | "watch") => {
+                (line, Some(condition_argument(condition)?))
+            }
+            _ => (line, None),
+        };
+
+        let arguments: Vec<&str> = line.split_whitespace().skip(1).collect();
+
+        match (command, &arguments[..]) {
+            ("help" | "?", []) => Ok(format!("{}\n", HELP)),
+            ("load", [path]) => self.load_file(path, 0),
+            ("load", [path, address]) => self.load_file(path, address_argument(address)?),
+            ("reset", []) => {
+                self.cpu.reset(&mut self.mem);
+                Ok(self.registers())
+            }
+            ("save", [path]) => {
+                fs::write(path, Snapshot::capture(&self.cpu, &self.mem).to_bytes())?;
+                Ok(format!("saved to {}\n", path))
+            }
+            ("restore", [path]) => {
+                Snapshot::from_bytes(&fs::read(path)?)?.restore(&mut self.cpu, &mut self.mem)?;
+                Ok(self.registers())
+            }
+            ("s" | "step", []) => self.step(1),
+            ("s" | "step", [count]) => self.step(count_argument(count)?),
+            ("c" | "continue", []) => self.continue_(),
+            ("back", []) => self.step_back(1),
+            ("back", [count]) => self.step_back(count_argument(count)? as usize),
+            ("lastwrite", [address]) => {
+                let address = address_argument(address)?;
+
+                match self.cpu.step_back_to_write(address, &mut self.mem) {
+                    Some(pc) => Ok(format!("${:04X} was last written by ${:04X}\n{}", address, pc, self.registers())),
+                    None => Err(MonitorError::Usage(format!("no write to ${:04X} in the journal", address))),
+                }
+            }
+            ("b" | "break", []) => Ok(self
+                .debugger()
+                .breakpoints
+                .iter()
+                .map(|breakpoint| format!("${:04X}{}\n", breakpoint.address, condition_suffix(&breakpoint.condition)))
+                .collect()),
+            ("b" | "break", [address]) => {
+                let address = address_argument(address)?;
+                self.debugger().add_breakpoint(address, condition);
+                Ok(format!("breakpoint at ${:04X}\n", address))
+            }
+            ("delete", [address]) => {
+                let address = address_argument(address)?;
+
+                if self.debugger().remove_breakpoint(address) {
+                    Ok(String::new())
+                } else {
+                    Err(MonitorError::Usage(format!("no breakpoint at ${:04X}", address)))
+                }
+            }
+            ("watch", []) => Ok(self
+                .debugger()
+                .watchpoints
+                .iter()
+                .map(|watchpoint| {
+                    format!(
+                        "{} ${:04X}-${:04X}{}\n",
+                        kinds_name(watchpoint.kinds),
+                        watchpoint.range.start(),
+                        watchpoint.range.end(),
+                        condition_suffix(&watchpoint.condition)
+                    )
+                })
+                .collect()),
+            ("watch", [kinds, range]) => {
+                let kinds = kinds_argument(kinds)?;
+                let range = range_argument(range)?;
+                let output = format!("watching ${:04X}-${:04X}\n", range.start(), range.end());
+
+                self.debugger().add_watchpoint(range, kinds, condition);
+                Ok(output)
+            }
+            ("unwatch", [address]) => {
+                let address = address_argument(address)?;
+                let watchpoints = &mut self.debugger().watchpoints;
+                let len = watchpoints.len();
+                watchpoints.retain(|watchpoint| *watchpoint.range.start() != address);
+
+                if watchpoints.len() != len {
+                    Ok(String::new())
+                } else {
+                    Err(MonitorError::Usage(format!("no watchpoint at ${:04X}", address)))
+                }
+            }
+            ("cond", []) => Ok(self
+                .debugger()
+                .conditions
+                .iter()
+                .enumerate()
+                .map(|(index, condition)| format!("{}: {}\n", index, condition))
+                .collect()),
+            ("cond", ["clear"]) => {
+                self.debugger().conditions.clear();
+                Ok(String::new())
+            }
+            ("cond", _) => {
+                let condition = condition_argument(line.trim_start()[command.len()..].trim())?;
+                self.debugger().conditions.push(condition);
+                Ok(String::new())
+            }
+            ("r" | "regs", []) => Ok(self.registers()),
+            ("r" | "regs", [register, value]) => {
+                self.set_register(register, value)?;
+                Ok(self.registers())
+            }
+            ("m" | "mem", [address]) => Ok(self.dump(address_argument(address)?, 64)),
+            ("m" | "mem", [address, len]) => Ok(self.dump(address_argument(address)?, count_argument(len)? as usize)),
+            ("w" | "write", [address, bytes @ ..]) if !bytes.is_empty() => {
+                let address = address_argument(address)?;
+
+                for (offset, byte) in bytes.iter().enumerate() {
+                    self.mem[address.wrapping_add(offset as Word)] = byte_argument(byte)?;
+                }
+
+                Ok(String::new())
+            }
+            ("d" | "dis", []) => Ok(self.disassemble(self.cpu.pc, 16)),
+            ("d" | "dis", [address]) => Ok(self.disassemble(address_argument(address)?, 16)),
+            ("d" | "dis", [address, count]) => {
+                Ok(self.disassemble(address_argument(address)?, count_argument(count)?))
+            }
+            ("a" | "asm", [address, ..]) => {
+                let source = line.trim_start()[command.len()..].trim_start()[address.len()..].trim();
+                let address = address_argument(address)?;
+                let assembly = asm::assemble(&format!(".org ${:04X}\n{}", address, source), self.cpu.variant)?;
+
+                for (address, byte) in assembly.bytes() {
+                    self.mem[address] = byte;
+                }
+
+                Ok(self.disassemble(address, 1))
+            }
+            _ => Err(MonitorError::Usage(format!("unknown command `{}`, try help", line.trim()))),
+        }
+    }
+
+    /// The registers and the cycle counter on one line.\
+    /// P is shown the way PHP would push it, the flag letters leave out B and bit 5 since the
+    /// register has neither
+    pub fn registers(&self) -> String {
+        let Registers { pc, sp, a, x, y, p } = self.cpu.registers();
+
+        let flags: String = "NV--DIZC"
+            .chars()
+            .enumerate()
+            .map(|(bit, name)| if p & (0x80 >> bit) != 0 { name } else { name.to_ascii_lowercase() })
+            .collect();
+
+        format!(
+            "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} SP:{:02X} P:{:02X} {} CYC:{}\n",
+            pc, a, x, y, sp, p, flags, self.cpu.cycles
+        )
+    }
+
+    fn load_file(&mut self, path: &str, address: Word) -> Result<String, MonitorError> {
+        let bytes = fs::read(path)?;
+        self.load(address, &bytes)?;
+
+        Ok(format!("loaded {} bytes at ${:04X}\n", bytes.len(), address))
+    }
+
+    fn debugger(&mut self) -> &mut Debugger {
+        self.cpu.debugger.get_or_insert_with(Debugger::new)
+    }
+
+    fn step(&mut self, count: u64) -> Result<String, MonitorError> {
+        let mut output = String::new();
+
+        // stepping off a breakpoint should not stop right away
+        let pc = self.cpu.pc;
+        self.debugger().resume(pc);
+
+        for _ in 0..count {
+            let instruction = disasm::disassemble(&self.mem, self.cpu.pc, self.cpu.variant);
+
+            match self.cpu.step(&mut self.mem) {
+                Ok(_) => {
+                    let _ = writeln!(output, "{}", disasm::listing_line(&instruction));
+                }
+                Err(EmulatorError::DebugStop { reason, .. }) => {
+                    // only read and write watchpoints let the instruction run
+                    if matches!(reason, StopReason::Watchpoint { kind, .. } if kind != Watch::EXECUTE) {
+                        let _ = writeln!(output, "{}", disasm::listing_line(&instruction));
+                    }
+
+                    let _ = writeln!(output, "{}", reason);
+                    break;
+                }
+                // the instructions that did run are still listed
+                Err(error) => {
+                    let _ = writeln!(output, "error: {}", error);
+                    break;
+                }
+            }
+        }
+
+        output.push_str(&self.registers());
+        Ok(output)
+    }
+
+    fn step_back(&mut self, count: usize) -> Result<String, MonitorError> {
+        let undone = self.cpu.step_back(count, &mut self.mem);
+
+        if undone < count {
+            return Ok(format!("undid {} instructions, the journal is empty\n{}", undone, self.registers()));
+        }
+
+        Ok(self.registers())
+    }
+
+    fn continue_(&mut self) -> Result<String, MonitorError> {
+        for _ in 0..CONTINUE_LIMIT {
+            if self.cpu.stopped() {
+                return Ok(format!("stopped\n{}", self.registers()));
+            }
+
+            match self.cpu.step(&mut self.mem) {
+                Ok(_) => (),
+                Err(EmulatorError::DebugStop { reason, .. }) => return Ok(format!("{}\n{}", reason, self.registers())),
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(format!("still running after {} instructions\n{}", CONTINUE_LIMIT, self.registers()))
+    }
+
+    fn set_register(&mut self, register: &str, value: &str) -> Result<(), MonitorError> {
+        let value = address_argument(value)?;
+        let byte = || Byte::try_from(value).map_err(|_| MonitorError::Usage(format!("${:X} does not fit in {}", value, register)));
+
+        match register.to_ascii_lowercase().as_str() {
+            "pc" => self.cpu.pc = value,
+            "sp" => self.cpu.sp = byte()?,
+            "a" => self.cpu.a = byte()?,
+            "x" => self.cpu.x = byte()?,
+            "y" => self.cpu.y = byte()?,
+            "p" => self.cpu.p = Status::from(byte()?),
+            _ => return Err(MonitorError::Usage(format!("unknown register `{}`", register))),
+        }
+
+        Ok(())
+    }
+
+    fn dump(&self, address: Word, len: usize) -> String {
+        let mut output = String::new();
+
+        for row in (0..len).step_by(16) {
+            let start = address.wrapping_add(row as Word);
+            let bytes: Vec<Byte> = (0..16.min(len - row)).map(|offset| self.mem[start.wrapping_add(offset as Word)]).collect();
+
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let text: String =
+                bytes.iter().map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' }).collect();
+
+            let _ = writeln!(output, "{:04X}  {:<47}  {}", start, hex.join(" "), text);
+        }
+
+        output
+    }
+
+    fn disassemble(&self, mut address: Word, count: u64) -> String {
+        let mut output = String::new();
+
+        for _ in 0..count {
+            let instruction = disasm::disassemble(&self.mem, address, self.cpu.variant);
+            let _ = writeln!(output, "{}", disasm::listing_line(&instruction));
+
+            address = instruction.next();
+        }
+
+        output
+    }
+}
+
+fn address_argument(text: &str) -> Result<Word, MonitorError> {
+    let digits = text.strip_prefix('$').unwrap_or(text);
+
+    Word::from_str_radix(digits, 16).map_err(|_| MonitorError::Usage(format!("invalid hex number `{}`", text)))
+}
+
+fn byte_argument(text: &str) -> Result<Byte, MonitorError> {
+    let digits = text.strip_prefix('$').unwrap_or(text);
+
+    Byte::from_str_radix(digits, 16).map_err(|_| MonitorError::Usage(format!("invalid byte `{}`", text)))
+}
+
+/// `ADDR` or `ADDR-ADDR`
+fn range_argument(text: &str) -> Result<RangeInclusive<Word>, MonitorError> {
+    match text.split_once('-') {
+        Some((start, end)) => Ok(address_argument(start)?..=address_argument(end)?),
+        None => {
+            let address = address_argument(text)?;
+            Ok(address..=address)
+        }
+    }
+}
+
+/// Any combination of `r`, `w` and `x`
+fn kinds_argument(text: &str) -> Result<Watch, MonitorError> {
+    text.chars().try_fold(Watch::empty(), |kinds, kind| match kind.to_ascii_lowercase() {
+        'r' => Ok(kinds | Watch::READ),
+        'w' => Ok(kinds | Watch::WRITE),
+        'x' => Ok(kinds | Watch::EXECUTE),
+        _ => Err(MonitorError::Usage(format!("invalid watch kind `{}`, use r, w and x", text))),
+    })
+}
+
+fn kinds_name(kinds: Watch) -> String {
+    [(Watch::READ, 'r'), (Watch::WRITE, 'w'), (Watch::EXECUTE, 'x')]
+        .iter()
+        .filter(|(kind, _)| kinds.contains(*kind))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+fn condition_argument(text: &str) -> Result<Condition, MonitorError> {
+    text.parse().map_err(|error| MonitorError::Usage(format!("invalid condition: {}", error)))
+}
+
+fn condition_suffix(condition: &Option<Condition>) -> String {
+    condition.as_ref().map(|condition| format!(" if {}", condition)).unwrap_or_default()
+}
+
+fn count_argument(text: &str) -> Result<u64, MonitorError> {
+    text.parse().map_err(|_| MonitorError::Usage(format!("invalid count `{}`", text)))
+}
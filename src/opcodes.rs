@@ -0,0 +1,634 @@
+use crate::consts::{STP, WAI};
+use crate::cpu::{Status, Variant};
+use crate::{Byte, Word};
+
+use Mode::*;
+
+/// How an instruction finds its operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Implied,
+    /// `ASL A`
+    Accumulator,
+    /// `LDA #$10`
+    Immediate,
+    /// `LDA $10`
+    ZeroPage,
+    /// `LDA $10,X`
+    ZeroPageX,
+    /// `LDX $10,Y`
+    ZeroPageY,
+    /// `LDA $1234`
+    Absolute,
+    /// `LDA $1234,X`
+    AbsoluteX,
+    /// `LDA $1234,Y`
+    AbsoluteY,
+    /// `JMP ($1234)`
+    Indirect,
+    /// `LDA ($10,X)`
+    IndirectX,
+    /// `LDA ($10),Y`
+    IndirectY,
+    /// `LDA ($10)`, 65C02 only
+    ZeroPageIndirect,
+    /// `JMP ($1234,X)`, 65C02 only
+    AbsoluteIndirectX,
+    /// `BNE $E00F`, the operand is a signed offset from the next instruction
+    Relative,
+    /// `BBR0 $10,$E00F`, a zero page address followed by a relative offset
+    ZeroPageRelative,
+}
+
+impl Mode {
+    /// Bytes following the opcode
+    pub const fn operand_len(self) -> Word {
+        match self {
+            Implied | Accumulator => 0,
+            Immediate | ZeroPage | ZeroPageX | ZeroPageY | IndirectX | IndirectY | ZeroPageIndirect | Relative => 1,
+            Absolute | AbsoluteX | AbsoluteY | Indirect | AbsoluteIndirectX | ZeroPageRelative => 2,
+        }
+    }
+}
+
+/// Cycles an instruction may take on top of `Opcode::cycles`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Penalty {
+    None,
+    /// One more when indexing crosses a page
+    PageCross,
+    /// One more when the branch is taken and another one when it lands on a different page
+    Branch,
+}
+
+/// What the CPU does with an opcode byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    pub mode: Mode,
+    /// Cycles without any `Penalty`.\
+    /// ADC and SBC take one more in decimal mode on the CMOS chips, JAM is 0 as it never finishes
+    pub cycles: u8,
+    pub penalty: Penalty,
+    /// Flags the instruction may change
+    pub flags: Status,
+}
+
+impl Opcode {
+    /// Bytes taken by the instruction, opcode included
+    pub const fn length(&self) -> Word {
+        1 + self.mode.operand_len()
+    }
+}
+
+/// The opcode metadata of `variant`
+pub fn lookup(variant: Variant, opcode: Byte) -> &'static Opcode {
+    /// The 65C02 executes opcodes it does not define as single cycle NOPs
+    static UNDEFINED: Opcode = op("NOP", Implied, 1, Penalty::None, NONE);
+
+    match variant {
+        Variant::Nmos6502 | Variant::Ricoh2A03 => &NMOS[opcode as usize],
+        Variant::Wdc65C02 => &CMOS[opcode as usize],
+        Variant::Rockwell65C02 if !matches!(opcode, WAI | STP) => &CMOS[opcode as usize],
+        // the bit instructions live in the x7 and xF columns
+        Variant::Cmos65C02 if opcode & 0x07 != 0x07 && !matches!(opcode, WAI | STP) => &CMOS[opcode as usize],
+        _ => &UNDEFINED,
+    }
+}
+
+const fn op(mnemonic: &'static str, mode: Mode, cycles: u8, penalty: Penalty, flags: Status) -> Opcode {
+    Opcode { mnemonic, mode, cycles, penalty, flags }
+}
+
+const NONE: Status = Status::empty();
+const C: Status = Status::C;
+const Z: Status = Status::Z;
+const I: Status = Status::I;
+const D: Status = Status::D;
+const V: Status = Status::V;
+const ID: Status = Status::I.union(Status::D);
+const NZ: Status = Status::N.union(Status::Z);
+const NZC: Status = NZ.union(Status::C);
+const NVZ: Status = NZ.union(Status::V);
+const NVZC: Status = NVZ.union(Status::C);
+const ALL: Status = NVZC.union(ID);
+
+/// NMOS 6502 and 2A03 opcodes, undocumented ones included
+pub static NMOS: [Opcode; 256] = [
+    op("BRK", Implied, 7, Penalty::None, I), // $00
+    op("ORA", IndirectX, 6, Penalty::None, NZ), // $01
+    op("JAM", Implied, 0, Penalty::None, NONE), // $02
+    op("SLO", IndirectX, 8, Penalty::None, NZC), // $03
+    op("NOP", ZeroPage, 3, Penalty::None, NONE), // $04
+    op("ORA", ZeroPage, 3, Penalty::None, NZ), // $05
+    op("ASL", ZeroPage, 5, Penalty::None, NZC), // $06
+    op("SLO", ZeroPage, 5, Penalty::None, NZC), // $07
+    op("PHP", Implied, 3, Penalty::None, NONE), // $08
+    op("ORA", Immediate, 2, Penalty::None, NZ), // $09
+    op("ASL", Accumulator, 2, Penalty::None, NZC), // $0A
+    op("ANC", Immediate, 2, Penalty::None, NZC), // $0B
+    op("NOP", Absolute, 4, Penalty::None, NONE), // $0C
+    op("ORA", Absolute, 4, Penalty::None, NZ), // $0D
+    op("ASL", Absolute, 6, Penalty::None, NZC), // $0E
+    op("SLO", Absolute, 6, Penalty::None, NZC), // $0F
+    op("BPL", Relative, 2, Penalty::Branch, NONE), // $10
+    op("ORA", IndirectY, 5, Penalty::PageCross, NZ), // $11
+    op("JAM", Implied, 0, Penalty::None, NONE), // $12
+    op("SLO", IndirectY, 8, Penalty::None, NZC), // $13
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $14
+    op("ORA", ZeroPageX, 4, Penalty::None, NZ), // $15
+    op("ASL", ZeroPageX, 6, Penalty::None, NZC), // $16
+    op("SLO", ZeroPageX, 6, Penalty::None, NZC), // $17
+    op("CLC", Implied, 2, Penalty::None, C), // $18
+    op("ORA", AbsoluteY, 4, Penalty::PageCross, NZ), // $19
+    op("NOP", Implied, 2, Penalty::None, NONE), // $1A
+    op("SLO", AbsoluteY, 7, Penalty::None, NZC), // $1B
+    op("NOP", AbsoluteX, 4, Penalty::PageCross, NONE), // $1C
+    op("ORA", AbsoluteX, 4, Penalty::PageCross, NZ), // $1D
+    op("ASL", AbsoluteX, 7, Penalty::None, NZC), // $1E
+    op("SLO", AbsoluteX, 7, Penalty::None, NZC), // $1F
+    op("JSR", Absolute, 6, Penalty::None, NONE), // $20
+    op("AND", IndirectX, 6, Penalty::None, NZ), // $21
+    op("JAM", Implied, 0, Penalty::None, NONE), // $22
+    op("RLA", IndirectX, 8, Penalty::None, NZC), // $23
+    op("BIT", ZeroPage, 3, Penalty::None, NVZ), // $24
+    op("AND", ZeroPage, 3, Penalty::None, NZ), // $25
+    op("ROL", ZeroPage, 5, Penalty::None, NZC), // $26
+    op("RLA", ZeroPage, 5, Penalty::None, NZC), // $27
+    op("PLP", Implied, 4, Penalty::None, ALL), // $28
+    op("AND", Immediate, 2, Penalty::None, NZ), // $29
+    op("ROL", Accumulator, 2, Penalty::None, NZC), // $2A
+    op("ANC", Immediate, 2, Penalty::None, NZC), // $2B
+    op("BIT", Absolute, 4, Penalty::None, NVZ), // $2C
+    op("AND", Absolute, 4, Penalty::None, NZ), // $2D
+    op("ROL", Absolute, 6, Penalty::None, NZC), // $2E
+    op("RLA", Absolute, 6, Penalty::None, NZC), // $2F
+    op("BMI", Relative, 2, Penalty::Branch, NONE), // $30
+    op("AND", IndirectY, 5, Penalty::PageCross, NZ), // $31
+    op("JAM", Implied, 0, Penalty::None, NONE), // $32
+    op("RLA", IndirectY, 8, Penalty::None, NZC), // $33
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $34
+    op("AND", ZeroPageX, 4, Penalty::None, NZ), // $35
+    op("ROL", ZeroPageX, 6, Penalty::None, NZC), // $36
+    op("RLA", ZeroPageX, 6, Penalty::None, NZC), // $37
+    op("SEC", Implied, 2, Penalty::None, C), // $38
+    op("AND", AbsoluteY, 4, Penalty::PageCross, NZ), // $39
+    op("NOP", Implied, 2, Penalty::None, NONE), // $3A
+    op("RLA", AbsoluteY, 7, Penalty::None, NZC), // $3B
+    op("NOP", AbsoluteX, 4, Penalty::PageCross, NONE), // $3C
+    op("AND", AbsoluteX, 4, Penalty::PageCross, NZ), // $3D
+    op("ROL", AbsoluteX, 7, Penalty::None, NZC), // $3E
+    op("RLA", AbsoluteX, 7, Penalty::None, NZC), // $3F
+    op("RTI", Implied, 6, Penalty::None, ALL), // $40
+    op("EOR", IndirectX, 6, Penalty::None, NZ), // $41
+    op("JAM", Implied, 0, Penalty::None, NONE), // $42
+    op("SRE", IndirectX, 8, Penalty::None, NZC), // $43
+    op("NOP", ZeroPage, 3, Penalty::None, NONE), // $44
+    op("EOR", ZeroPage, 3, Penalty::None, NZ), // $45
+    op("LSR", ZeroPage, 5, Penalty::None, NZC), // $46
+    op("SRE", ZeroPage, 5, Penalty::None, NZC), // $47
+    op("PHA", Implied, 3, Penalty::None, NONE), // $48
+    op("EOR", Immediate, 2, Penalty::None, NZ), // $49
+    op("LSR", Accumulator, 2, Penalty::None, NZC), // $4A
+    op("ALR", Immediate, 2, Penalty::None, NZC), // $4B
+    op("JMP", Absolute, 3, Penalty::None, NONE), // $4C
+    op("EOR", Absolute, 4, Penalty::None, NZ), // $4D
+    op("LSR", Absolute, 6, Penalty::None, NZC), // $4E
+    op("SRE", Absolute, 6, Penalty::None, NZC), // $4F
+    op("BVC", Relative, 2, Penalty::Branch, NONE), // $50
+    op("EOR", IndirectY, 5, Penalty::PageCross, NZ), // $51
+    op("JAM", Implied, 0, Penalty::None, NONE), // $52
+    op("SRE", IndirectY, 8, Penalty::None, NZC), // $53
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $54
+    op("EOR", ZeroPageX, 4, Penalty::None, NZ), // $55
+    op("LSR", ZeroPageX, 6, Penalty::None, NZC), // $56
+    op("SRE", ZeroPageX, 6, Penalty::None, NZC), // $57
+    op("CLI", Implied, 2, Penalty::None, I), // $58
+    op("EOR", AbsoluteY, 4, Penalty::PageCross, NZ), // $59
+    op("NOP", Implied, 2, Penalty::None, NONE), // $5A
+    op("SRE", AbsoluteY, 7, Penalty::None, NZC), // $5B
+    op("NOP", AbsoluteX, 4, Penalty::PageCross, NONE), // $5C
+    op("EOR", AbsoluteX, 4, Penalty::PageCross, NZ), // $5D
+    op("LSR", AbsoluteX, 7, Penalty::None, NZC), // $5E
+    op("SRE", AbsoluteX, 7, Penalty::None, NZC), // $5F
+    op("RTS", Implied, 6, Penalty::None, NONE), // $60
+    op("ADC", IndirectX, 6, Penalty::None, NVZC), // $61
+    op("JAM", Implied, 0, Penalty::None, NONE), // $62
+    op("RRA", IndirectX, 8, Penalty::None, NVZC), // $63
+    op("NOP", ZeroPage, 3, Penalty::None, NONE), // $64
+    op("ADC", ZeroPage, 3, Penalty::None, NVZC), // $65
+    op("ROR", ZeroPage, 5, Penalty::None, NZC), // $66
+    op("RRA", ZeroPage, 5, Penalty::None, NVZC), // $67
+    op("PLA", Implied, 4, Penalty::None, NZ), // $68
+    op("ADC", Immediate, 2, Penalty::None, NVZC), // $69
+    op("ROR", Accumulator, 2, Penalty::None, NZC), // $6A
+    op("ARR", Immediate, 2, Penalty::None, NVZC), // $6B
+    op("JMP", Indirect, 5, Penalty::None, NONE), // $6C
+    op("ADC", Absolute, 4, Penalty::None, NVZC), // $6D
+    op("ROR", Absolute, 6, Penalty::None, NZC), // $6E
+    op("RRA", Absolute, 6, Penalty::None, NVZC), // $6F
+    op("BVS", Relative, 2, Penalty::Branch, NONE), // $70
+    op("ADC", IndirectY, 5, Penalty::PageCross, NVZC), // $71
+    op("JAM", Implied, 0, Penalty::None, NONE), // $72
+    op("RRA", IndirectY, 8, Penalty::None, NVZC), // $73
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $74
+    op("ADC", ZeroPageX, 4, Penalty::None, NVZC), // $75
+    op("ROR", ZeroPageX, 6, Penalty::None, NZC), // $76
+    op("RRA", ZeroPageX, 6, Penalty::None, NVZC), // $77
+    op("SEI", Implied, 2, Penalty::None, I), // $78
+    op("ADC", AbsoluteY, 4, Penalty::PageCross, NVZC), // $79
+    op("NOP", Implied, 2, Penalty::None, NONE), // $7A
+    op("RRA", AbsoluteY, 7, Penalty::None, NVZC), // $7B
+    op("NOP", AbsoluteX, 4, Penalty::PageCross, NONE), // $7C
+    op("ADC", AbsoluteX, 4, Penalty::PageCross, NVZC), // $7D
+    op("ROR", AbsoluteX, 7, Penalty::None, NZC), // $7E
+    op("RRA", AbsoluteX, 7, Penalty::None, NVZC), // $7F
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $80
+    op("STA", IndirectX, 6, Penalty::None, NONE), // $81
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $82
+    op("SAX", IndirectX, 6, Penalty::None, NONE), // $83
+    op("STY", ZeroPage, 3, Penalty::None, NONE), // $84
+    op("STA", ZeroPage, 3, Penalty::None, NONE), // $85
+    op("STX", ZeroPage, 3, Penalty::None, NONE), // $86
+    op("SAX", ZeroPage, 3, Penalty::None, NONE), // $87
+    op("DEY", Implied, 2, Penalty::None, NZ), // $88
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $89
+    op("TXA", Implied, 2, Penalty::None, NZ), // $8A
+    op("ANE", Immediate, 2, Penalty::None, NZ), // $8B
+    op("STY", Absolute, 4, Penalty::None, NONE), // $8C
+    op("STA", Absolute, 4, Penalty::None, NONE), // $8D
+    op("STX", Absolute, 4, Penalty::None, NONE), // $8E
+    op("SAX", Absolute, 4, Penalty::None, NONE), // $8F
+    op("BCC", Relative, 2, Penalty::Branch, NONE), // $90
+    op("STA", IndirectY, 6, Penalty::None, NONE), // $91
+    op("JAM", Implied, 0, Penalty::None, NONE), // $92
+    op("SHA", IndirectY, 6, Penalty::None, NONE), // $93
+    op("STY", ZeroPageX, 4, Penalty::None, NONE), // $94
+    op("STA", ZeroPageX, 4, Penalty::None, NONE), // $95
+    op("STX", ZeroPageY, 4, Penalty::None, NONE), // $96
+    op("SAX", ZeroPageY, 4, Penalty::None, NONE), // $97
+    op("TYA", Implied, 2, Penalty::None, NZ), // $98
+    op("STA", AbsoluteY, 5, Penalty::None, NONE), // $99
+    op("TXS", Implied, 2, Penalty::None, NONE), // $9A
+    op("TAS", AbsoluteY, 5, Penalty::None, NONE), // $9B
+    op("SHY", AbsoluteX, 5, Penalty::None, NONE), // $9C
+    op("STA", AbsoluteX, 5, Penalty::None, NONE), // $9D
+    op("SHX", AbsoluteY, 5, Penalty::None, NONE), // $9E
+    op("SHA", AbsoluteY, 5, Penalty::None, NONE), // $9F
+    op("LDY", Immediate, 2, Penalty::None, NZ), // $A0
+    op("LDA", IndirectX, 6, Penalty::None, NZ), // $A1
+    op("LDX", Immediate, 2, Penalty::None, NZ), // $A2
+    op("LAX", IndirectX, 6, Penalty::None, NZ), // $A3
+    op("LDY", ZeroPage, 3, Penalty::None, NZ), // $A4
+    op("LDA", ZeroPage, 3, Penalty::None, NZ), // $A5
+    op("LDX", ZeroPage, 3, Penalty::None, NZ), // $A6
+    op("LAX", ZeroPage, 3, Penalty::None, NZ), // $A7
+    op("TAY", Implied, 2, Penalty::None, NZ), // $A8
+    op("LDA", Immediate, 2, Penalty::None, NZ), // $A9
+    op("TAX", Implied, 2, Penalty::None, NZ), // $AA
+    op("LXA", Immediate, 2, Penalty::None, NZ), // $AB
+    op("LDY", Absolute, 4, Penalty::None, NZ), // $AC
+    op("LDA", Absolute, 4, Penalty::None, NZ), // $AD
+    op("LDX", Absolute, 4, Penalty::None, NZ), // $AE
+    op("LAX", Absolute, 4, Penalty::None, NZ), // $AF
+    op("BCS", Relative, 2, Penalty::Branch, NONE), // $B0
+    op("LDA", IndirectY, 5, Penalty::PageCross, NZ), // $B1
+    op("JAM", Implied, 0, Penalty::None, NONE), // $B2
+    op("LAX", IndirectY, 5, Penalty::PageCross, NZ), // $B3
+    op("LDY", ZeroPageX, 4, Penalty::None, NZ), // $B4
+    op("LDA", ZeroPageX, 4, Penalty::None, NZ), // $B5
+    op("LDX", ZeroPageY, 4, Penalty::None, NZ), // $B6
+    op("LAX", ZeroPageY, 4, Penalty::None, NZ), // $B7
+    op("CLV", Implied, 2, Penalty::None, V), // $B8
+    op("LDA", AbsoluteY, 4, Penalty::PageCross, NZ), // $B9
+    op("TSX", Implied, 2, Penalty::None, NZ), // $BA
+    op("LAS", AbsoluteY, 4, Penalty::PageCross, NZ), // $BB
+    op("LDY", AbsoluteX, 4, Penalty::PageCross, NZ), // $BC
+    op("LDA", AbsoluteX, 4, Penalty::PageCross, NZ), // $BD
+    op("LDX", AbsoluteY, 4, Penalty::PageCross, NZ), // $BE
+    op("LAX", AbsoluteY, 4, Penalty::PageCross, NZ), // $BF
+    op("CPY", Immediate, 2, Penalty::None, NZC), // $C0
+    op("CMP", IndirectX, 6, Penalty::None, NZC), // $C1
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $C2
+    op("DCP", IndirectX, 8, Penalty::None, NZC), // $C3
+    op("CPY", ZeroPage, 3, Penalty::None, NZC), // $C4
+    op("CMP", ZeroPage, 3, Penalty::None, NZC), // $C5
+    op("DEC", ZeroPage, 5, Penalty::None, NZ), // $C6
+    op("DCP", ZeroPage, 5, Penalty::None, NZC), // $C7
+    op("INY", Implied, 2, Penalty::None, NZ), // $C8
+    op("CMP", Immediate, 2, Penalty::None, NZC), // $C9
+    op("DEX", Implied, 2, Penalty::None, NZ), // $CA
+    op("SBX", Immediate, 2, Penalty::None, NZC), // $CB
+    op("CPY", Absolute, 4, Penalty::None, NZC), // $CC
+    op("CMP", Absolute, 4, Penalty::None, NZC), // $CD
+    op("DEC", Absolute, 6, Penalty::None, NZ), // $CE
+    op("DCP", Absolute, 6, Penalty::None, NZC), // $CF
+    op("BNE", Relative, 2, Penalty::Branch, NONE), // $D0
+    op("CMP", IndirectY, 5, Penalty::PageCross, NZC), // $D1
+    op("JAM", Implied, 0, Penalty::None, NONE), // $D2
+    op("DCP", IndirectY, 8, Penalty::None, NZC), // $D3
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $D4
+    op("CMP", ZeroPageX, 4, Penalty::None, NZC), // $D5
+    op("DEC", ZeroPageX, 6, Penalty::None, NZ), // $D6
+    op("DCP", ZeroPageX, 6, Penalty::None, NZC), // $D7
+    op("CLD", Implied, 2, Penalty::None, D), // $D8
+    op("CMP", AbsoluteY, 4, Penalty::PageCross, NZC), // $D9
+    op("NOP", Implied, 2, Penalty::None, NONE), // $DA
+    op("DCP", AbsoluteY, 7, Penalty::None, NZC), // $DB
+    op("NOP", AbsoluteX, 4, Penalty::PageCross, NONE), // $DC
+    op("CMP", AbsoluteX, 4, Penalty::PageCross, NZC), // $DD
+    op("DEC", AbsoluteX, 7, Penalty::None, NZ), // $DE
+    op("DCP", AbsoluteX, 7, Penalty::None, NZC), // $DF
+    op("CPX", Immediate, 2, Penalty::None, NZC), // $E0
+    op("SBC", IndirectX, 6, Penalty::None, NVZC), // $E1
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $E2
+    op("ISC", IndirectX, 8, Penalty::None, NVZC), // $E3
+    op("CPX", ZeroPage, 3, Penalty::None, NZC), // $E4
+    op("SBC", ZeroPage, 3, Penalty::None, NVZC), // $E5
+    op("INC", ZeroPage, 5, Penalty::None, NZ), // $E6
+    op("ISC", ZeroPage, 5, Penalty::None, NVZC), // $E7
+    op("INX", Implied, 2, Penalty::None, NZ), // $E8
+    op("SBC", Immediate, 2, Penalty::None, NVZC), // $E9
+    op("NOP", Implied, 2, Penalty::None, NONE), // $EA
+    op("USBC", Immediate, 2, Penalty::None, NVZC), // $EB
+    op("CPX", Absolute, 4, Penalty::None, NZC), // $EC
+    op("SBC", Absolute, 4, Penalty::None, NVZC), // $ED
+    op("INC", Absolute, 6, Penalty::None, NZ), // $EE
+    op("ISC", Absolute, 6, Penalty::None, NVZC), // $EF
+    op("BEQ", Relative, 2, Penalty::Branch, NONE), // $F0
+    op("SBC", IndirectY, 5, Penalty::PageCross, NVZC), // $F1
+    op("JAM", Implied, 0, Penalty::None, NONE), // $F2
+    op("ISC", IndirectY, 8, Penalty::None, NVZC), // $F3
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $F4
+    op("SBC", ZeroPageX, 4, Penalty::None, NVZC), // $F5
+    op("INC", ZeroPageX, 6, Penalty::None, NZ), // $F6
+    op("ISC", ZeroPageX, 6, Penalty::None, NVZC), // $F7
+    op("SED", Implied, 2, Penalty::None, D), // $F8
+    op("SBC", AbsoluteY, 4, Penalty::PageCross, NVZC), // $F9
+    op("NOP", Implied, 2, Penalty::None, NONE), // $FA
+    op("ISC", AbsoluteY, 7, Penalty::None, NVZC), // $FB
+    op("NOP", AbsoluteX, 4, Penalty::PageCross, NONE), // $FC
+    op("SBC", AbsoluteX, 4, Penalty::PageCross, NVZC), // $FD
+    op("INC", AbsoluteX, 7, Penalty::None, NZ), // $FE
+    op("ISC", AbsoluteX, 7, Penalty::None, NVZC), // $FF
+];
+
+/// WDC 65C02 opcodes, the other CMOS chips lack some of them, see `lookup`
+pub static CMOS: [Opcode; 256] = [
+    op("BRK", Implied, 7, Penalty::None, ID), // $00
+    op("ORA", IndirectX, 6, Penalty::None, NZ), // $01
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $02
+    op("NOP", Implied, 1, Penalty::None, NONE), // $03
+    op("TSB", ZeroPage, 5, Penalty::None, Z), // $04
+    op("ORA", ZeroPage, 3, Penalty::None, NZ), // $05
+    op("ASL", ZeroPage, 5, Penalty::None, NZC), // $06
+    op("RMB0", ZeroPage, 5, Penalty::None, NONE), // $07
+    op("PHP", Implied, 3, Penalty::None, NONE), // $08
+    op("ORA", Immediate, 2, Penalty::None, NZ), // $09
+    op("ASL", Accumulator, 2, Penalty::None, NZC), // $0A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $0B
+    op("TSB", Absolute, 6, Penalty::None, Z), // $0C
+    op("ORA", Absolute, 4, Penalty::None, NZ), // $0D
+    op("ASL", Absolute, 6, Penalty::None, NZC), // $0E
+    op("BBR0", ZeroPageRelative, 5, Penalty::Branch, NONE), // $0F
+    op("BPL", Relative, 2, Penalty::Branch, NONE), // $10
+    op("ORA", IndirectY, 5, Penalty::PageCross, NZ), // $11
+    op("ORA", ZeroPageIndirect, 5, Penalty::None, NZ), // $12
+    op("NOP", Implied, 1, Penalty::None, NONE), // $13
+    op("TRB", ZeroPage, 5, Penalty::None, Z), // $14
+    op("ORA", ZeroPageX, 4, Penalty::None, NZ), // $15
+    op("ASL", ZeroPageX, 6, Penalty::None, NZC), // $16
+    op("RMB1", ZeroPage, 5, Penalty::None, NONE), // $17
+    op("CLC", Implied, 2, Penalty::None, C), // $18
+    op("ORA", AbsoluteY, 4, Penalty::PageCross, NZ), // $19
+    op("INC", Accumulator, 2, Penalty::None, NZ), // $1A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $1B
+    op("TRB", Absolute, 6, Penalty::None, Z), // $1C
+    op("ORA", AbsoluteX, 4, Penalty::PageCross, NZ), // $1D
+    op("ASL", AbsoluteX, 6, Penalty::PageCross, NZC), // $1E
+    op("BBR1", ZeroPageRelative, 5, Penalty::Branch, NONE), // $1F
+    op("JSR", Absolute, 6, Penalty::None, NONE), // $20
+    op("AND", IndirectX, 6, Penalty::None, NZ), // $21
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $22
+    op("NOP", Implied, 1, Penalty::None, NONE), // $23
+    op("BIT", ZeroPage, 3, Penalty::None, NVZ), // $24
+    op("AND", ZeroPage, 3, Penalty::None, NZ), // $25
+    op("ROL", ZeroPage, 5, Penalty::None, NZC), // $26
+    op("RMB2", ZeroPage, 5, Penalty::None, NONE), // $27
+    op("PLP", Implied, 4, Penalty::None, ALL), // $28
+    op("AND", Immediate, 2, Penalty::None, NZ), // $29
+    op("ROL", Accumulator, 2, Penalty::None, NZC), // $2A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $2B
+    op("BIT", Absolute, 4, Penalty::None, NVZ), // $2C
+    op("AND", Absolute, 4, Penalty::None, NZ), // $2D
+    op("ROL", Absolute, 6, Penalty::None, NZC), // $2E
+    op("BBR2", ZeroPageRelative, 5, Penalty::Branch, NONE), // $2F
+    op("BMI", Relative, 2, Penalty::Branch, NONE), // $30
+    op("AND", IndirectY, 5, Penalty::PageCross, NZ), // $31
+    op("AND", ZeroPageIndirect, 5, Penalty::None, NZ), // $32
+    op("NOP", Implied, 1, Penalty::None, NONE), // $33
+    op("BIT", ZeroPageX, 4, Penalty::None, NVZ), // $34
+    op("AND", ZeroPageX, 4, Penalty::None, NZ), // $35
+    op("ROL", ZeroPageX, 6, Penalty::None, NZC), // $36
+    op("RMB3", ZeroPage, 5, Penalty::None, NONE), // $37
+    op("SEC", Implied, 2, Penalty::None, C), // $38
+    op("AND", AbsoluteY, 4, Penalty::PageCross, NZ), // $39
+    op("DEC", Accumulator, 2, Penalty::None, NZ), // $3A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $3B
+    op("BIT", AbsoluteX, 4, Penalty::PageCross, NVZ), // $3C
+    op("AND", AbsoluteX, 4, Penalty::PageCross, NZ), // $3D
+    op("ROL", AbsoluteX, 6, Penalty::PageCross, NZC), // $3E
+    op("BBR3", ZeroPageRelative, 5, Penalty::Branch, NONE), // $3F
+    op("RTI", Implied, 6, Penalty::None, ALL), // $40
+    op("EOR", IndirectX, 6, Penalty::None, NZ), // $41
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $42
+    op("NOP", Implied, 1, Penalty::None, NONE), // $43
+    op("NOP", ZeroPage, 3, Penalty::None, NONE), // $44
+    op("EOR", ZeroPage, 3, Penalty::None, NZ), // $45
+    op("LSR", ZeroPage, 5, Penalty::None, NZC), // $46
+    op("RMB4", ZeroPage, 5, Penalty::None, NONE), // $47
+    op("PHA", Implied, 3, Penalty::None, NONE), // $48
+    op("EOR", Immediate, 2, Penalty::None, NZ), // $49
+    op("LSR", Accumulator, 2, Penalty::None, NZC), // $4A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $4B
+    op("JMP", Absolute, 3, Penalty::None, NONE), // $4C
+    op("EOR", Absolute, 4, Penalty::None, NZ), // $4D
+    op("LSR", Absolute, 6, Penalty::None, NZC), // $4E
+    op("BBR4", ZeroPageRelative, 5, Penalty::Branch, NONE), // $4F
+    op("BVC", Relative, 2, Penalty::Branch, NONE), // $50
+    op("EOR", IndirectY, 5, Penalty::PageCross, NZ), // $51
+    op("EOR", ZeroPageIndirect, 5, Penalty::None, NZ), // $52
+    op("NOP", Implied, 1, Penalty::None, NONE), // $53
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $54
+    op("EOR", ZeroPageX, 4, Penalty::None, NZ), // $55
+    op("LSR", ZeroPageX, 6, Penalty::None, NZC), // $56
+    op("RMB5", ZeroPage, 5, Penalty::None, NONE), // $57
+    op("CLI", Implied, 2, Penalty::None, I), // $58
+    op("EOR", AbsoluteY, 4, Penalty::PageCross, NZ), // $59
+    op("PHY", Implied, 3, Penalty::None, NONE), // $5A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $5B
+    op("NOP", Absolute, 8, Penalty::None, NONE), // $5C
+    op("EOR", AbsoluteX, 4, Penalty::PageCross, NZ), // $5D
+    op("LSR", AbsoluteX, 6, Penalty::PageCross, NZC), // $5E
+    op("BBR5", ZeroPageRelative, 5, Penalty::Branch, NONE), // $5F
+    op("RTS", Implied, 6, Penalty::None, NONE), // $60
+    op("ADC", IndirectX, 6, Penalty::None, NVZC), // $61
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $62
+    op("NOP", Implied, 1, Penalty::None, NONE), // $63
+    op("STZ", ZeroPage, 3, Penalty::None, NONE), // $64
+    op("ADC", ZeroPage, 3, Penalty::None, NVZC), // $65
+    op("ROR", ZeroPage, 5, Penalty::None, NZC), // $66
+    op("RMB6", ZeroPage, 5, Penalty::None, NONE), // $67
+    op("PLA", Implied, 4, Penalty::None, NZ), // $68
+    op("ADC", Immediate, 2, Penalty::None, NVZC), // $69
+    op("ROR", Accumulator, 2, Penalty::None, NZC), // $6A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $6B
+    op("JMP", Indirect, 6, Penalty::None, NONE), // $6C
+    op("ADC", Absolute, 4, Penalty::None, NVZC), // $6D
+    op("ROR", Absolute, 6, Penalty::None, NZC), // $6E
+    op("BBR6", ZeroPageRelative, 5, Penalty::Branch, NONE), // $6F
+    op("BVS", Relative, 2, Penalty::Branch, NONE), // $70
+    op("ADC", IndirectY, 5, Penalty::PageCross, NVZC), // $71
+    op("ADC", ZeroPageIndirect, 5, Penalty::None, NVZC), // $72
+    op("NOP", Implied, 1, Penalty::None, NONE), // $73
+    op("STZ", ZeroPageX, 4, Penalty::None, NONE), // $74
+    op("ADC", ZeroPageX, 4, Penalty::None, NVZC), // $75
+    op("ROR", ZeroPageX, 6, Penalty::None, NZC), // $76
+    op("RMB7", ZeroPage, 5, Penalty::None, NONE), // $77
+    op("SEI", Implied, 2, Penalty::None, I), // $78
+    op("ADC", AbsoluteY, 4, Penalty::PageCross, NVZC), // $79
+    op("PLY", Implied, 4, Penalty::None, NZ), // $7A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $7B
+    op("JMP", AbsoluteIndirectX, 6, Penalty::None, NONE), // $7C
+    op("ADC", AbsoluteX, 4, Penalty::PageCross, NVZC), // $7D
+    op("ROR", AbsoluteX, 6, Penalty::PageCross, NZC), // $7E
+    op("BBR7", ZeroPageRelative, 5, Penalty::Branch, NONE), // $7F
+    op("BRA", Relative, 3, Penalty::PageCross, NONE), // $80
+    op("STA", IndirectX, 6, Penalty::None, NONE), // $81
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $82
+    op("NOP", Implied, 1, Penalty::None, NONE), // $83
+    op("STY", ZeroPage, 3, Penalty::None, NONE), // $84
+    op("STA", ZeroPage, 3, Penalty::None, NONE), // $85
+    op("STX", ZeroPage, 3, Penalty::None, NONE), // $86
+    op("SMB0", ZeroPage, 5, Penalty::None, NONE), // $87
+    op("DEY", Implied, 2, Penalty::None, NZ), // $88
+    op("BIT", Immediate, 2, Penalty::None, Z), // $89
+    op("TXA", Implied, 2, Penalty::None, NZ), // $8A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $8B
+    op("STY", Absolute, 4, Penalty::None, NONE), // $8C
+    op("STA", Absolute, 4, Penalty::None, NONE), // $8D
+    op("STX", Absolute, 4, Penalty::None, NONE), // $8E
+    op("BBS0", ZeroPageRelative, 5, Penalty::Branch, NONE), // $8F
+    op("BCC", Relative, 2, Penalty::Branch, NONE), // $90
+    op("STA", IndirectY, 6, Penalty::None, NONE), // $91
+    op("STA", ZeroPageIndirect, 5, Penalty::None, NONE), // $92
+    op("NOP", Implied, 1, Penalty::None, NONE), // $93
+    op("STY", ZeroPageX, 4, Penalty::None, NONE), // $94
+    op("STA", ZeroPageX, 4, Penalty::None, NONE), // $95
+    op("STX", ZeroPageY, 4, Penalty::None, NONE), // $96
+    op("SMB1", ZeroPage, 5, Penalty::None, NONE), // $97
+    op("TYA", Implied, 2, Penalty::None, NZ), // $98
+    op("STA", AbsoluteY, 5, Penalty::None, NONE), // $99
+    op("TXS", Implied, 2, Penalty::None, NONE), // $9A
+    op("NOP", Implied, 1, Penalty::None, NONE), // $9B
+    op("STZ", Absolute, 4, Penalty::None, NONE), // $9C
+    op("STA", AbsoluteX, 5, Penalty::None, NONE), // $9D
+    op("STZ", AbsoluteX, 5, Penalty::None, NONE), // $9E
+    op("BBS1", ZeroPageRelative, 5, Penalty::Branch, NONE), // $9F
+    op("LDY", Immediate, 2, Penalty::None, NZ), // $A0
+    op("LDA", IndirectX, 6, Penalty::None, NZ), // $A1
+    op("LDX", Immediate, 2, Penalty::None, NZ), // $A2
+    op("NOP", Implied, 1, Penalty::None, NONE), // $A3
+    op("LDY", ZeroPage, 3, Penalty::None, NZ), // $A4
+    op("LDA", ZeroPage, 3, Penalty::None, NZ), // $A5
+    op("LDX", ZeroPage, 3, Penalty::None, NZ), // $A6
+    op("SMB2", ZeroPage, 5, Penalty::None, NONE), // $A7
+    op("TAY", Implied, 2, Penalty::None, NZ), // $A8
+    op("LDA", Immediate, 2, Penalty::None, NZ), // $A9
+    op("TAX", Implied, 2, Penalty::None, NZ), // $AA
+    op("NOP", Implied, 1, Penalty::None, NONE), // $AB
+    op("LDY", Absolute, 4, Penalty::None, NZ), // $AC
+    op("LDA", Absolute, 4, Penalty::None, NZ), // $AD
+    op("LDX", Absolute, 4, Penalty::None, NZ), // $AE
+    op("BBS2", ZeroPageRelative, 5, Penalty::Branch, NONE), // $AF
+    op("BCS", Relative, 2, Penalty::Branch, NONE), // $B0
+    op("LDA", IndirectY, 5, Penalty::PageCross, NZ), // $B1
+    op("LDA", ZeroPageIndirect, 5, Penalty::None, NZ), // $B2
+    op("NOP", Implied, 1, Penalty::None, NONE), // $B3
+    op("LDY", ZeroPageX, 4, Penalty::None, NZ), // $B4
+    op("LDA", ZeroPageX, 4, Penalty::None, NZ), // $B5
+    op("LDX", ZeroPageY, 4, Penalty::None, NZ), // $B6
+    op("SMB3", ZeroPage, 5, Penalty::None, NONE), // $B7
+    op("CLV", Implied, 2, Penalty::None, V), // $B8
+    op("LDA", AbsoluteY, 4, Penalty::PageCross, NZ), // $B9
+    op("TSX", Implied, 2, Penalty::None, NZ), // $BA
+    op("NOP", Implied, 1, Penalty::None, NONE), // $BB
+    op("LDY", AbsoluteX, 4, Penalty::PageCross, NZ), // $BC
+    op("LDA", AbsoluteX, 4, Penalty::PageCross, NZ), // $BD
+    op("LDX", AbsoluteY, 4, Penalty::PageCross, NZ), // $BE
+    op("BBS3", ZeroPageRelative, 5, Penalty::Branch, NONE), // $BF
+    op("CPY", Immediate, 2, Penalty::None, NZC), // $C0
+    op("CMP", IndirectX, 6, Penalty::None, NZC), // $C1
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $C2
+    op("NOP", Implied, 1, Penalty::None, NONE), // $C3
+    op("CPY", ZeroPage, 3, Penalty::None, NZC), // $C4
+    op("CMP", ZeroPage, 3, Penalty::None, NZC), // $C5
+    op("DEC", ZeroPage, 5, Penalty::None, NZ), // $C6
+    op("SMB4", ZeroPage, 5, Penalty::None, NONE), // $C7
+    op("INY", Implied, 2, Penalty::None, NZ), // $C8
+    op("CMP", Immediate, 2, Penalty::None, NZC), // $C9
+    op("DEX", Implied, 2, Penalty::None, NZ), // $CA
+    op("WAI", Implied, 3, Penalty::None, NONE), // $CB
+    op("CPY", Absolute, 4, Penalty::None, NZC), // $CC
+    op("CMP", Absolute, 4, Penalty::None, NZC), // $CD
+    op("DEC", Absolute, 6, Penalty::None, NZ), // $CE
+    op("BBS4", ZeroPageRelative, 5, Penalty::Branch, NONE), // $CF
+    op("BNE", Relative, 2, Penalty::Branch, NONE), // $D0
+    op("CMP", IndirectY, 5, Penalty::PageCross, NZC), // $D1
+    op("CMP", ZeroPageIndirect, 5, Penalty::None, NZC), // $D2
+    op("NOP", Implied, 1, Penalty::None, NONE), // $D3
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $D4
+    op("CMP", ZeroPageX, 4, Penalty::None, NZC), // $D5
+    op("DEC", ZeroPageX, 6, Penalty::None, NZ), // $D6
+    op("SMB5", ZeroPage, 5, Penalty::None, NONE), // $D7
+    op("CLD", Implied, 2, Penalty::None, D), // $D8
+    op("CMP", AbsoluteY, 4, Penalty::PageCross, NZC), // $D9
+    op("PHX", Implied, 3, Penalty::None, NONE), // $DA
+    op("STP", Implied, 3, Penalty::None, NONE), // $DB
+    op("NOP", Absolute, 4, Penalty::None, NONE), // $DC
+    op("CMP", AbsoluteX, 4, Penalty::PageCross, NZC), // $DD
+    op("DEC", AbsoluteX, 7, Penalty::None, NZ), // $DE
+    op("BBS5", ZeroPageRelative, 5, Penalty::Branch, NONE), // $DF
+    op("CPX", Immediate, 2, Penalty::None, NZC), // $E0
+    op("SBC", IndirectX, 6, Penalty::None, NVZC), // $E1
+    op("NOP", Immediate, 2, Penalty::None, NONE), // $E2
+    op("NOP", Implied, 1, Penalty::None, NONE), // $E3
+    op("CPX", ZeroPage, 3, Penalty::None, NZC), // $E4
+    op("SBC", ZeroPage, 3, Penalty::None, NVZC), // $E5
+    op("INC", ZeroPage, 5, Penalty::None, NZ), // $E6
+    op("SMB6", ZeroPage, 5, Penalty::None, NONE), // $E7
+    op("INX", Implied, 2, Penalty::None, NZ), // $E8
+    op("SBC", Immediate, 2, Penalty::None, NVZC), // $E9
+    op("NOP", Implied, 2, Penalty::None, NONE), // $EA
+    op("NOP", Implied, 1, Penalty::None, NONE), // $EB
+    op("CPX", Absolute, 4, Penalty::None, NZC), // $EC
+    op("SBC", Absolute, 4, Penalty::None, NVZC), // $ED
+    op("INC", Absolute, 6, Penalty::None, NZ), // $EE
+    op("BBS6", ZeroPageRelative, 5, Penalty::Branch, NONE), // $EF
+    op("BEQ", Relative, 2, Penalty::Branch, NONE), // $F0
+    op("SBC", IndirectY, 5, Penalty::PageCross, NVZC), // $F1
+    op("SBC", ZeroPageIndirect, 5, Penalty::None, NVZC), // $F2
+    op("NOP", Implied, 1, Penalty::None, NONE), // $F3
+    op("NOP", ZeroPageX, 4, Penalty::None, NONE), // $F4
+    op("SBC", ZeroPageX, 4, Penalty::None, NVZC), // $F5
+    op("INC", ZeroPageX, 6, Penalty::None, NZ), // $F6
+    op("SMB7", ZeroPage, 5, Penalty::None, NONE), // $F7
+    op("SED", Implied, 2, Penalty::None, D), // $F8
+    op("SBC", AbsoluteY, 4, Penalty::PageCross, NVZC), // $F9
+    op("PLX", Implied, 4, Penalty::None, NZ), // $FA
+    op("NOP", Implied, 1, Penalty::None, NONE), // $FB
+    op("NOP", Absolute, 4, Penalty::None, NONE), // $FC
+    op("SBC", AbsoluteX, 4, Penalty::PageCross, NVZC), // $FD
+    op("INC", AbsoluteX, 7, Penalty::None, NZ), // $FE
+    op("BBS7", ZeroPageRelative, 5, Penalty::Branch, NONE), // $FF
+];
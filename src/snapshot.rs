@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::bus::Bus;
+use crate::cpu::{Status, Undocumented, Variant, CPU};
+use crate::{Byte, Word};
+
+/// Format version written by `Snapshot::to_bytes`, the only one `Snapshot::from_bytes` accepts
+pub const VERSION: u16 = 1;
+
+/// Start of every binary snapshot
+pub const MAGIC: [Byte; 8] = *b"6502SNAP";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The data does not start with `MAGIC`
+    BadMagic,
+    /// The snapshot was written in another version of the format
+    UnsupportedVersion { version: u16 },
+    /// The data ends in the middle of the snapshot
+    Truncated,
+    /// A field holds a value no snapshot can contain
+    Invalid { message: String },
+    /// The bus state does not fit the bus it is restored into, a memory map with other regions for instance
+    Mismatch { message: String },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::BadMagic => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion { version } => {
+                write!(f, "snapshot version {} is not supported, expected version {}", version, VERSION)
+            }
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::Invalid { message } => write!(f, "invalid snapshot: {}", message),
+            SnapshotError::Mismatch { message } => write!(f, "snapshot does not fit the bus: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Everything that makes the CPU behave the way it does, see `CPU::state` and `CPU::set_state`.\
+/// The tracer and the debugger are tools attached to the CPU and are not part of it
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuState {
+    pub pc: Word,
+    pub sp: Byte,
+    pub a: Byte,
+    pub x: Byte,
+    pub y: Byte,
+    pub p: Status,
+    pub cycles: u64,
+    pub variant: Variant,
+    pub undocumented: Option<Undocumented>,
+    /// Level of the IRQ line, see `CPU::set_irq_line`
+    pub irq_line: bool,
+    /// An NMI edge was seen and not serviced yet
+    pub nmi_pending: bool,
+    /// Interrupt disable flag seen by the next interrupt poll, when CLI, SEI or PLP just changed it
+    pub delayed_interrupt_flag: Option<bool>,
+    /// WAI was executed and no interrupt came in yet
+    pub waiting: bool,
+    /// STP was executed
+    pub stopped: bool,
+}
+
+/// The CPU and its bus frozen at one point, to get back to later or to write to a file.\
+/// The bus part is whatever `Bus::save_state` returned, so a snapshot has to be restored into
+/// the same kind of bus it was taken from.
+///
+/// ```
+/// use emulator_6502::cpu::CPU;
+/// use emulator_6502::memory::Memory;
+/// use emulator_6502::snapshot::Snapshot;
+///
+/// let mut cpu = CPU::default();
+/// let mut mem = Memory::new();
+///
+/// let bytes = Snapshot::capture(&cpu, &mem).to_bytes();
+/// // ...
+/// Snapshot::from_bytes(&bytes).unwrap().restore(&mut cpu, &mut mem).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    pub cpu: CpuState,
+    pub bus: Vec<Byte>,
+}
+
+impl Snapshot {
+    pub fn capture<B: Bus + ?Sized>(cpu: &CPU, bus: &B) -> Self {
+        Snapshot { cpu: cpu.state(), bus: bus.save_state() }
+    }
+
+    /// Puts `cpu` and `bus` back in the captured state, the CPU is left alone if the bus state does not fit.\
+    /// The journal of the CPU is cleared since its steps belong to another run
+    pub fn restore<B: Bus + ?Sized>(&self, cpu: &mut CPU, bus: &mut B) -> Result<(), SnapshotError> {
+        bus.load_state(&self.bus)?;
+        cpu.set_state(&self.cpu);
+
+        if let Some(journal) = &mut cpu.journal {
+            journal.clear();
+        }
+
+        Ok(())
+    }
+
+    /// Binary format, all numbers little endian:\
+    /// `MAGIC`, version (u16), PC (u16), SP, A, X, Y, P, cycles (u64), variant, undocumented
+    /// (0, or 1 followed by the ANE and LXA magic constants), line flags (bit 0 IRQ line, 1 NMI
+    /// pending, 2 waiting, 3 stopped), delayed interrupt flag (0 none, 1 clear, 2 set), bus state
+    /// length (u32) and bus state
+    pub fn to_bytes(&self) -> Vec<Byte> {
+        let cpu = &self.cpu;
+        let mut bytes = Vec::with_capacity(40 + self.bus.len());
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&cpu.pc.to_le_bytes());
+        bytes.extend_from_slice(&[cpu.sp, cpu.a, cpu.x, cpu.y, cpu.p.bits()]);
+        bytes.extend_from_slice(&cpu.cycles.to_le_bytes());
+        bytes.push(VARIANTS.iter().position(|variant| *variant == cpu.variant).unwrap() as Byte);
+
+        match cpu.undocumented {
+            Some(Undocumented { ane_magic, lxa_magic }) => bytes.extend_from_slice(&[1, ane_magic, lxa_magic]),
+            None => bytes.push(0),
+        }
+
+        let lines = [cpu.irq_line, cpu.nmi_pending, cpu.waiting, cpu.stopped];
+        bytes.push(lines.iter().enumerate().fold(0, |flags, (bit, set)| flags | (*set as Byte) << bit));
+        bytes.push(match cpu.delayed_interrupt_flag {
+            None => 0,
+            Some(false) => 1,
+            Some(true) => 2,
+        });
+
+        write_block(&mut bytes, &self.bus);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[Byte]) -> Result<Self, SnapshotError> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(MAGIC.len()).map_err(|_| SnapshotError::BadMagic)? != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+
+        let version = reader.word()?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion { version });
+        }
+
+        let pc = reader.word()?;
+        let [sp, a, x, y, p] = reader.array()?;
+        let cycles = u64::from_le_bytes(reader.array()?);
+
+        let variant = reader.byte()?;
+        let variant = *VARIANTS
+            .get(variant as usize)
+            .ok_or_else(|| SnapshotError::Invalid { message: format!("unknown variant {}", variant) })?;
+
+        let undocumented = match reader.byte()? {
+            0 => None,
+            1 => {
+                let [ane_magic, lxa_magic] = reader.array()?;
+                Some(Undocumented { ane_magic, lxa_magic })
+            }
+            other => return Err(SnapshotError::Invalid { message: format!("undocumented opcodes setting {}", other) }),
+        };
+
+        let lines = reader.byte()?;
+        let delayed_interrupt_flag = match reader.byte()? {
+            0 => None,
+            1 => Some(false),
+            2 => Some(true),
+            other => return Err(SnapshotError::Invalid { message: format!("delayed interrupt flag {}", other) }),
+        };
+
+        let bus = reader.block()?.to_vec();
+
+        if !reader.is_empty() {
+            return Err(SnapshotError::Invalid { message: "data after the end of the snapshot".to_string() });
+        }
+
+        let cpu = CpuState {
+            pc,
+            sp,
+            a,
+            x,
+            y,
+            p: Status::from_bits_truncate(p),
+            cycles,
+            variant,
+            undocumented,
+            irq_line: lines & 0b0001 != 0,
+            nmi_pending: lines & 0b0010 != 0,
+            waiting: lines & 0b0100 != 0,
+            stopped: lines & 0b1000 != 0,
+            delayed_interrupt_flag,
+        };
+
+        Ok(Snapshot { cpu, bus })
+    }
+}
+
+/// The order variants are numbered in the binary format, new ones go at the end
+const VARIANTS: [Variant; 5] =
+    [Variant::Nmos6502, Variant::Cmos65C02, Variant::Rockwell65C02, Variant::Wdc65C02, Variant::Ricoh2A03];
+
+/// Appends `data` preceded by its length as a u32
+pub(crate) fn write_block(bytes: &mut Vec<Byte>, data: &[Byte]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+}
+
+/// Reads the binary format back, every read fails with `SnapshotError::Truncated` past the end
+pub(crate) struct Reader<'a> {
+    bytes: &'a [Byte],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [Byte]) -> Self {
+        Reader { bytes }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [Byte], SnapshotError> {
+        if len > self.bytes.len() {
+            return Err(SnapshotError::Truncated);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[Byte; N], SnapshotError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<Byte, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn word(&mut self) -> Result<Word, SnapshotError> {
+        Ok(Word::from_le_bytes(self.array()?))
+    }
+
+    /// Data written by `write_block`
+    pub(crate) fn block(&mut self) -> Result<&'a [Byte], SnapshotError> {
+        let len = u32::from_le_bytes(self.array()?);
+        self.take(len as usize)
+    }
+}
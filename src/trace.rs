@@ -0,0 +1,97 @@
+use std::io::Write;
+
+use crate::cpu::{Interrupt, Registers, Variant};
+use crate::disasm::{self, Instruction};
+use crate::Byte;
+
+/// State of the CPU right before it executes an instruction or enters an interrupt, see `Tracer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub registers: Registers,
+    /// The opcode at PC followed by the two bytes after it, whether they belong to the instruction or not
+    pub bytes: [Byte; 3],
+    /// Cycles elapsed before the instruction
+    pub cycles: u64,
+    /// Decides how `bytes` decode
+    pub variant: Variant,
+    /// Set when the CPU enters this interrupt instead of executing the instruction at PC
+    pub interrupt: Option<Interrupt>,
+}
+
+impl TraceEvent {
+    pub const fn opcode(&self) -> Byte {
+        self.bytes[0]
+    }
+
+    /// The instruction about to execute
+    pub fn instruction(&self) -> Instruction {
+        disasm::decode(self.bytes, self.registers.pc, self.variant)
+    }
+}
+
+/// Receives every instruction the CPU is about to execute and every interrupt it is about to
+/// enter, install one with `CPU::tracer`
+pub trait Tracer {
+    fn trace(&mut self, event: &TraceEvent);
+}
+
+impl<F: FnMut(&TraceEvent)> Tracer for F {
+    fn trace(&mut self, event: &TraceEvent) {
+        self(event)
+    }
+}
+
+/// Writes one line per instruction in the layout of the nestest log, which most emulators can
+/// produce and diff against:
+///
+/// ```text
+/// C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7
+/// ```
+///
+/// Write errors are ignored, tracing is a debugging aid that should not stop execution
+pub struct NestestLogger<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NestestLogger<W> {
+    pub fn new(writer: W) -> Self {
+        NestestLogger { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Formats `event` without the trailing newline
+    pub fn format(event: &TraceEvent) -> String {
+        let registers = event.registers;
+        let instruction = event.instruction();
+
+        // an interrupt entry has no bytes of its own
+        let (bytes, text) = match event.interrupt {
+            Some(Interrupt::Irq) => (String::new(), "*IRQ".to_string()),
+            Some(Interrupt::Nmi) => (String::new(), "*NMI".to_string()),
+            None => (disasm::hex_bytes(&instruction), instruction.to_string()),
+        };
+
+        format!(
+            "{:04X}  {:<8}  {:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+            registers.pc,
+            bytes,
+            text,
+            registers.a,
+            registers.x,
+            registers.y,
+            // nestest shows the status with bit 5 set and B clear
+            registers.p & !0b00010000,
+            registers.sp,
+            event.cycles
+        )
+    }
+}
+
+impl<W: Write> Tracer for NestestLogger<W> {
+    fn trace(&mut self, event: &TraceEvent) {
+        let _ = writeln!(self.writer, "{}", Self::format(event));
+    }
+}
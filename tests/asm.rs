@@ -0,0 +1,214 @@
+use emulator_6502::asm::{self, AsmError, Segment};
+use emulator_6502::cpu::{Variant, CPU};
+use emulator_6502::memory::Memory;
+use emulator_6502::consts::*;
+
+fn bytes(source: &str) -> Vec<u8> {
+    let assembly = asm::assemble(source, Variant::Nmos6502).unwrap();
+    assert_eq!(assembly.segments.len(), 1);
+
+    assembly.segments[0].bytes.clone()
+}
+
+#[test]
+fn addressing_modes() {
+    let source = "
+        NOP
+        ASL
+        ROL A
+        LDA #$10
+        LDA $10
+        LDA $10,X
+        LDX $10,Y
+        LDA $1234
+        LDA $1234,X
+        LDA $1234,Y
+        JMP ($1234)
+        LDA ($10,X)
+        LDA ($10),y
+    ";
+
+    assert_eq!(
+        bytes(source),
+        [
+            NOP, ASL_A, ROL_A, LDA_IM, 0x10, LDA_ZP, 0x10, LDA_ZPX, 0x10, LDX_ZPY, 0x10, LDA_ABS, 0x34, 0x12,
+            LDA_ABSX, 0x34, 0x12, LDA_ABSY, 0x34, 0x12, JMP_IND, 0x34, 0x12, LDA_INDX, 0x10, LDA_INDY, 0x10,
+        ]
+    );
+}
+
+#[test]
+fn zero_page_only_when_known_in_first_pass() {
+    let source = "
+        .org $0200
+        LDA early
+        LDA late
+        STX late,Y
+    early = $10
+    late = $20
+    ";
+
+    // `early` is used before it is defined too, both get absolute addressing except STX which has no absolute,Y
+    assert_eq!(bytes(source), [LDA_ABS, 0x10, 0x00, LDA_ABS, 0x20, 0x00, STX_ZPY, 0x20]);
+
+    assert_eq!(bytes("zp = $10\n LDA zp\n LDA zp+$100"), [LDA_ZP, 0x10, LDA_ABS, 0x10, 0x01]);
+}
+
+#[test]
+fn labels_and_branches() {
+    let assembly = asm::assemble(
+        "
+            .org $E000
+        main:
+            JSR sub
+            BEQ main
+        sub:
+            RTS
+        ",
+        Variant::Nmos6502,
+    )
+    .unwrap();
+
+    assert_eq!(assembly.segments, [Segment { start: 0xE000, bytes: vec![JSR, 0x05, 0xE0, BEQ, 0xFB, RTS] }]);
+    assert_eq!(assembly.symbol("main"), Some(0xE000));
+    assert_eq!(assembly.symbol("sub"), Some(0xE005));
+}
+
+#[test]
+fn local_labels_are_scoped() {
+    let assembly = asm::assemble(
+        "
+        first:
+        @loop:
+            BNE @loop
+        second:
+        @loop:
+            BNE @loop
+        ",
+        Variant::Nmos6502,
+    )
+    .unwrap();
+
+    assert_eq!(assembly.symbol("first@loop"), Some(0x0000));
+    assert_eq!(assembly.symbol("second@loop"), Some(0x0002));
+    assert_eq!(assembly.segments[0].bytes, [BNE, 0xFE, BNE, 0xFE]);
+}
+
+#[test]
+fn directives_and_expressions() {
+    let source = "
+        .org $1000
+    table:
+        .byte 1, $FF, %101, 'A', \"Hi\", -1
+        .word table, $1234, *  ; * is where the statement starts
+        .byte <table + 2, >table, (2 + 3) * 4, 1 << 4 | 1, ~0 & $0F  ; a comment
+    ";
+
+    assert_eq!(
+        bytes(source),
+        [1, 0xFF, 5, b'A', b'H', b'i', 0xFF, 0x00, 0x10, 0x34, 0x12, 0x07, 0x10, 0x02, 0x10, 20, 0x11, 0x0F]
+    );
+}
+
+#[test]
+fn constants_defined_from_later_constants() {
+    let source = "
+        LDA #FOO
+        FOO = BAR + 1
+    BAR:
+        .byte FOO
+    ";
+
+    assert_eq!(bytes(source), [LDA_IM, 0x03, 0x03]);
+    assert_eq!(
+        asm::assemble("A = B\nB = A", Variant::Nmos6502),
+        Err(AsmError::UndefinedSymbol { line: 1, name: "B".to_string() })
+    );
+}
+
+#[test]
+fn org_starts_segments() {
+    let assembly = asm::assemble(".org $E000\n NOP\n .org $FFFC\n .word $E000", Variant::Nmos6502).unwrap();
+
+    assert_eq!(
+        assembly.segments,
+        [Segment { start: 0xE000, bytes: vec![NOP] }, Segment { start: 0xFFFC, bytes: vec![0x00, 0xE0] }]
+    );
+}
+
+#[test]
+fn cmos_instructions() {
+    let assembly = asm::assemble(
+        "
+        loop:
+            INC
+            LDA ($10)
+            JMP ($1234,X)
+            BBR0 $10,loop
+            STZ $10
+        ",
+        Variant::Wdc65C02,
+    )
+    .unwrap();
+
+    assert_eq!(
+        assembly.segments[0].bytes,
+        [INC_A, LDA_ZPIND, 0x10, JMP_INDX, 0x34, 0x12, BBR0, 0x10, 0xF7, STZ_ZP, 0x10]
+    );
+
+    assert_eq!(
+        asm::assemble("STZ $10", Variant::Nmos6502),
+        Err(AsmError::UnknownMnemonic { line: 1, mnemonic: "STZ".to_string() })
+    );
+}
+
+#[test]
+fn errors_report_the_line() {
+    let cases = [
+        ("NOP\nLDA missing", AsmError::UndefinedSymbol { line: 2, name: "missing".to_string() }),
+        ("a:\na:", AsmError::DuplicateSymbol { line: 2, name: "a".to_string() }),
+        ("LDA #$100", AsmError::OutOfRange { line: 1, value: 0x100 }),
+        ("JMP ($10),Y", AsmError::InvalidMode { line: 1, mnemonic: "JMP".to_string() }),
+        ("far:\n.org $1000\nBNE far", AsmError::BranchOutOfRange { line: 3, target: 0x0000 }),
+        (".bogus", AsmError::Syntax { line: 1, message: "unknown directive .bogus".to_string() }),
+    ];
+
+    for (source, error) in cases {
+        assert_eq!(asm::assemble(source, Variant::Nmos6502), Err(error), "{}", source);
+    }
+}
+
+#[test]
+fn assembled_program_runs() {
+    let assembly = asm::assemble(
+        "
+            .org $E000
+        reset:
+            LDX #0
+        @loop:
+            INX
+            CPX #5
+            BNE @loop
+            STX result
+        done:
+            JMP done
+
+        result = $0200
+
+            .org $FFFC
+            .word reset
+        ",
+        Variant::Nmos6502,
+    )
+    .unwrap();
+
+    let mut mem: Memory = assembly.bytes().collect();
+    let mut cpu = CPU::default();
+    cpu.reset(&mut mem);
+
+    while cpu.pc != assembly.symbol("done").unwrap() {
+        cpu.step(&mut mem).unwrap();
+    }
+
+    assert_eq!(mem[0x0200], 5);
+}
@@ -0,0 +1,66 @@
+use emulator_6502::bus::Bus;
+use emulator_6502::cpu::CPU;
+use emulator_6502::consts::*;
+use emulator_6502::{Byte, Word};
+
+/// RAM with a single output port at $4000 that records everything written to it
+struct PortBus {
+    ram: [Byte; 65536],
+    port_writes: Vec<Byte>,
+}
+
+impl PortBus {
+    fn new() -> Self {
+        PortBus { ram: [0; 65536], port_writes: Vec::new() }
+    }
+}
+
+impl Bus for PortBus {
+    fn read(&mut self, address: Word) -> Byte {
+        self.ram[address as usize]
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        match address {
+            0x4000 => self.port_writes.push(value),
+            _ => self.ram[address as usize] = value,
+        }
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        self.ram[address as usize]
+    }
+}
+
+#[test]
+fn custom_bus_sees_writes() {
+    let mut bus = PortBus::new();
+    bus.ram[0xFFFC] = 0x00;
+    bus.ram[0xFFFD] = 0xE0;
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut bus);
+
+    bus.ram[0xE000] = LDA_IM;
+    bus.ram[0xE001] = 0x42;
+    bus.ram[0xE002] = STA_ABS;
+    bus.ram[0xE003] = 0x00;
+    bus.ram[0xE004] = 0x40;
+
+    cpu.execute(6, &mut bus).unwrap();
+
+    assert_eq!(bus.port_writes, vec![0x42]);
+    assert_eq!(bus.ram[0x4000], 0x00);
+}
+
+#[test]
+fn custom_bus_reset_vector() {
+    let mut bus = PortBus::new();
+    bus.ram[0xFFFC] = 0x34;
+    bus.ram[0xFFFD] = 0x12;
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut bus);
+
+    assert_eq!(cpu.pc, 0x1234);
+}
@@ -0,0 +1,194 @@
+use emulator_6502::cpu::Variant;
+use emulator_6502::consts::*;
+
+mod common;
+
+#[test]
+fn bra_always_branches() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[BRA, 0x10]);
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 3);
+    assert_eq!(cpu.pc, 0xE012);
+}
+
+#[test]
+fn push_and_pull_x_and_y() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[PHX, PHY, PLX, PLY]);
+    cpu.x = 0x11;
+    cpu.y = 0x80;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 3);
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(mem[0x01FD], 0x11);
+    assert_eq!(mem[0x01FC], 0x80);
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 4);
+    cpu.step(&mut mem).unwrap();
+    assert_eq!((cpu.x, cpu.y), (0x80, 0x11));
+    assert!(!cpu.p.negative_flag());
+    assert_eq!(cpu.sp, 0xFD);
+}
+
+#[test]
+fn stz_stores_zero() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[STZ_ZP, 0x10, STZ_ABSX, 0x00, 0x20]);
+    cpu.x = 0x01;
+    mem[0x0010] = 0xFF;
+    mem[0x2001] = 0xFF;
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 5);
+
+    assert_eq!(mem[0x0010], 0x00);
+    assert_eq!(mem[0x2001], 0x00);
+}
+
+#[test]
+fn trb_and_tsb() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[TSB_ZP, 0x10, TRB_ABS, 0x00, 0x20]);
+    cpu.a = 0x0F;
+    mem[0x0010] = 0xF0;
+    mem[0x2000] = 0xFF;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 5);
+    assert_eq!(mem[0x0010], 0xFF);
+    assert!(cpu.p.zero_flag());
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 6);
+    assert_eq!(mem[0x2000], 0xF0);
+    assert!(!cpu.p.zero_flag());
+}
+
+#[test]
+fn zero_page_indirect() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[LDA_ZPIND, 0x10, STA_ZPIND, 0x12]);
+    mem[0x0010] = 0x00;
+    mem[0x0011] = 0x30;
+    mem[0x0012] = 0x00;
+    mem[0x0013] = 0x40;
+    mem[0x3000] = 0x99;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 5);
+    assert_eq!(cpu.a, 0x99);
+    assert!(cpu.p.negative_flag());
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 5);
+    assert_eq!(mem[0x4000], 0x99);
+}
+
+#[test]
+fn inc_and_dec_accumulator() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[INC_A, DEC_A, DEC_A]);
+    cpu.a = 0xFF;
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.a, 0x00);
+    assert!(cpu.p.zero_flag());
+
+    cpu.step(&mut mem).unwrap();
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.a, 0xFE);
+    assert!(cpu.p.negative_flag());
+}
+
+#[test]
+fn bit_immediate_only_sets_zero() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[BIT_IM, 0xC0]);
+    cpu.a = 0x01;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert!(cpu.p.zero_flag());
+    assert!(!cpu.p.negative_flag() && !cpu.p.overflow_flag());
+}
+
+#[test]
+fn bit_indexed() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[BIT_ZPX, 0x10]);
+    cpu.a = 0xFF;
+    cpu.x = 0x01;
+    mem[0x0011] = 0xC0;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert!(!cpu.p.zero_flag() && cpu.p.negative_flag() && cpu.p.overflow_flag());
+}
+
+#[test]
+fn jmp_indexed_indirect() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[JMP_INDX, 0x00, 0x30]);
+    cpu.x = 0x02;
+    mem[0x3002] = 0x34;
+    mem[0x3003] = 0x12;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 6);
+    assert_eq!(cpu.pc, 0x1234);
+}
+
+#[test]
+fn jmp_indirect_page_boundary() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[JMP_IND, 0xFF, 0x30]);
+    mem[0x30FF] = 0x34;
+    mem[0x3100] = 0x12;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 6);
+    assert_eq!(cpu.pc, 0x1234);
+}
+
+#[test]
+fn read_modify_write_reads_twice() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[ASL_ABSX, 0x00, 0x20, INC_ABSX, 0x00, 0x20]);
+    cpu.x = 0x01;
+    mem[0x2001] = 0x01;
+
+    // no page crossed, shifts skip the fix up cycle
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 6);
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 7);
+    assert_eq!(mem[0x2001], 0x03);
+}
+
+#[test]
+fn decimal_mode_takes_extra_cycle() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[ADC_IM, 0x01, SED, ADC_IM, 0x01]);
+    cpu.a = 0x09;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 2);
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 3);
+    assert_eq!(cpu.a, 0x11);
+}
+
+#[test]
+fn interrupts_clear_decimal() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[BRK, 0x00]);
+    mem[0xFFFE] = 0x00;
+    mem[0xFFFF] = 0x30;
+    cpu.p.set_decimal(true);
+
+    cpu.step(&mut mem).unwrap();
+
+    assert!(!cpu.p.decimal_flag());
+    assert_eq!(mem[0x01FB] & 0b00001000, 0b00001000);
+}
+
+#[test]
+fn undefined_opcodes_are_nops() {
+    let cases: &[(&[u8], u32)] = &[
+        (&[0x03], 1),
+        (&[0x0B], 1),
+        (&[0x02, 0x00], 2),
+        (&[0x44, 0x00], 3),
+        (&[0x54, 0x00], 4),
+        (&[0xDC, 0x00, 0x20], 4),
+        (&[0x5C, 0x00, 0x20], 8),
+    ];
+
+    for (program, cycles) in cases {
+        let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, program);
+
+        assert_eq!(cpu.step(&mut mem).unwrap().cycles, *cycles, "opcode {:02X}", program[0]);
+        assert_eq!(cpu.pc, 0xE000 + program.len() as u16);
+    }
+}
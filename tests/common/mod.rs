@@ -0,0 +1,58 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use emulator_6502::asm;
+use emulator_6502::cpu::{Variant, CPU};
+use emulator_6502::memory::Memory;
+
+/// An NMOS CPU out of reset with `program` loaded at $E000, where the reset vector points
+pub fn setup(program: &[u8]) -> (CPU, Memory) {
+    setup_variant(Variant::Nmos6502, program)
+}
+
+/// `setup` for another chip
+pub fn setup_variant(variant: Variant, program: &[u8]) -> (CPU, Memory) {
+    let mut mem = memory(program);
+
+    let mut cpu = CPU::new(variant);
+    cpu.reset(&mut mem);
+
+    (cpu, mem)
+}
+
+/// `program` at $E000 and the reset vector pointing to it, for tests that reset the CPU themselves
+pub fn memory(program: &[u8]) -> Memory {
+    let mut mem = Memory::new();
+    mem[0xFFFC] = 0x00;
+    mem[0xFFFD] = 0xE0;
+    mem.bytes[0xE000..0xE000 + program.len()].copy_from_slice(program);
+
+    mem
+}
+
+/// Increments X and stores it at $0200, then reads $0300, forever. X is 1 after the first pass,
+/// `INX` is at $E002, `LDA $0300` at $E006 and `JMP loop` at $E009
+pub const COUNTER: &str = "
+        .org $E000
+    reset:
+        LDX #0
+    loop:
+        INX
+        STX $0200
+        LDA $0300
+        JMP loop
+
+        .org $FFFC
+        .word reset
+";
+
+/// A CPU of `variant` reset into `source`, which sets its own reset vector
+pub fn assembled(variant: Variant, source: &str) -> (CPU, Memory) {
+    let assembly = asm::assemble(source, variant).unwrap();
+    let mut mem: Memory = assembly.bytes().collect();
+
+    let mut cpu = CPU::new(variant);
+    cpu.reset(&mut mem);
+
+    (cpu, mem)
+}
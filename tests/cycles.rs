@@ -0,0 +1,203 @@
+use emulator_6502::bus::Bus;
+use emulator_6502::cpu::CPU;
+use emulator_6502::consts::*;
+use emulator_6502::memory::Memory;
+use emulator_6502::{Byte, Word};
+
+mod common;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read(Word),
+    Write(Word, Byte),
+}
+
+/// RAM that records every access the CPU makes, in order
+struct LogBus {
+    ram: Memory,
+    log: Vec<Access>,
+}
+
+impl Bus for LogBus {
+    fn read(&mut self, address: Word) -> Byte {
+        self.log.push(Access::Read(address));
+        self.ram[address]
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        self.log.push(Access::Write(address, value));
+        self.ram[address] = value;
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        self.ram[address]
+    }
+}
+
+/// `common::setup` with logging starting after the reset sequence
+fn setup(program: &[Byte]) -> (CPU, LogBus) {
+    let (cpu, ram) = common::setup(program);
+
+    (cpu, LogBus { ram, log: Vec::new() })
+}
+
+/// Steps one instruction and checks that every cycle was a bus access
+fn step(cpu: &mut CPU, bus: &mut LogBus) -> Vec<Access> {
+    bus.log.clear();
+    let info = cpu.step(bus).unwrap();
+
+    assert_eq!(info.cycles as usize, bus.log.len());
+
+    bus.log.clone()
+}
+
+#[test]
+fn implied_reads_next_opcode() {
+    let (mut cpu, mut bus) = setup(&[INX]);
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(log, vec![Access::Read(0xE000), Access::Read(0xE001)]);
+}
+
+#[test]
+fn zero_page_x_reads_base_address() {
+    let (mut cpu, mut bus) = setup(&[LDA_ZPX, 0x80]);
+    cpu.x = 0x0F;
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(
+        log,
+        vec![Access::Read(0xE000), Access::Read(0xE001), Access::Read(0x0080), Access::Read(0x008F)]
+    );
+}
+
+#[test]
+fn absolute_x_page_cross_reads_unfixed_address() {
+    let (mut cpu, mut bus) = setup(&[LDA_ABSX, 0xFF, 0x20]);
+    cpu.x = 0x01;
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(log[3..], [Access::Read(0x2000), Access::Read(0x2100)]);
+}
+
+#[test]
+fn store_absolute_x_always_reads_first() {
+    let (mut cpu, mut bus) = setup(&[STA_ABSX, 0x00, 0x20]);
+    cpu.a = 0x42;
+    cpu.x = 0x01;
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(log[3..], [Access::Read(0x2001), Access::Write(0x2001, 0x42)]);
+}
+
+#[test]
+fn read_modify_write_writes_twice() {
+    let (mut cpu, mut bus) = setup(&[INC_ABSX, 0x00, 0x20]);
+    cpu.x = 0x01;
+    bus.ram[0x2001] = 0x41;
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(
+        log[3..],
+        [
+            Access::Read(0x2001),
+            Access::Read(0x2001),
+            Access::Write(0x2001, 0x41),
+            Access::Write(0x2001, 0x42),
+        ]
+    );
+    assert_eq!(bus.ram[0x2001], 0x42);
+}
+
+#[test]
+fn jsr_and_rts_bus_activity() {
+    let (mut cpu, mut bus) = setup(&[JSR, 0x00, 0xE1]);
+    bus.ram[0xE100] = RTS;
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(
+        log,
+        vec![
+            Access::Read(0xE000),
+            Access::Read(0xE001),
+            Access::Read(0x01FD),
+            Access::Write(0x01FD, 0xE0),
+            Access::Write(0x01FC, 0x02),
+            Access::Read(0xE002),
+        ]
+    );
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(
+        log,
+        vec![
+            Access::Read(0xE100),
+            Access::Read(0xE101),
+            Access::Read(0x01FB),
+            Access::Read(0x01FC),
+            Access::Read(0x01FD),
+            Access::Read(0xE002),
+        ]
+    );
+    assert_eq!(cpu.pc, 0xE003);
+}
+
+#[test]
+fn taken_branch_across_page() {
+    let (mut cpu, mut bus) = setup(&[]);
+    cpu.pc = 0xE0F0;
+    bus.ram[0xE0F0] = BNE;
+    bus.ram[0xE0F1] = 0x10;
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(
+        log,
+        vec![Access::Read(0xE0F0), Access::Read(0xE0F1), Access::Read(0xE0F2), Access::Read(0xE002)]
+    );
+    assert_eq!(cpu.pc, 0xE102);
+}
+
+#[test]
+fn backwards_branch() {
+    let (mut cpu, mut bus) = setup(&[BEQ, 0xFE]);
+    cpu.p.set_zero(true);
+
+    let log = step(&mut cpu, &mut bus);
+
+    assert_eq!(log.len(), 3);
+    assert_eq!(cpu.pc, 0xE000);
+}
+
+#[test]
+fn documented_cycle_counts() {
+    let cases: &[(&[Byte], u32)] = &[
+        (&[TAX], 2),
+        (&[DEX], 2),
+        (&[NOP], 2),
+        (&[PHA], 3),
+        (&[PLA], 4),
+        (&[BIT_ABS, 0x00, 0x20], 4),
+        (&[INC_ZP, 0x10], 5),
+        (&[INC_ABSX, 0x00, 0x20], 7),
+        (&[STA_INDY, 0x10], 6),
+        (&[LDA_INDX, 0x10], 6),
+        (&[ASL_A], 2),
+        (&[ROR_ZPX, 0x10], 6),
+        (&[RTI], 6),
+        (&[BRK], 7),
+    ];
+
+    for (program, cycles) in cases {
+        let (mut cpu, mut bus) = setup(program);
+
+        assert_eq!(cpu.step(&mut bus).unwrap().cycles, *cycles, "opcode {:02X}", program[0]);
+    }
+}
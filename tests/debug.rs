@@ -0,0 +1,140 @@
+use emulator_6502::cpu::{Registers, Status, Variant, CPU};
+use emulator_6502::debug::{Comparison, Condition, Debugger, Register, StopReason, Watch};
+use emulator_6502::error::EmulatorError;
+use emulator_6502::memory::Memory;
+
+mod common;
+
+/// `common::COUNTER` with an empty debugger
+fn setup() -> (CPU, Memory) {
+    let (mut cpu, mem) = common::assembled(Variant::Nmos6502, common::COUNTER);
+    cpu.debugger = Some(Debugger::new());
+
+    (cpu, mem)
+}
+
+fn debugger(cpu: &mut CPU) -> &mut Debugger {
+    cpu.debugger.as_mut().unwrap()
+}
+
+#[test]
+fn breakpoint_stops_before_instruction_and_resumes() {
+    let (mut cpu, mut mem) = setup();
+    debugger(&mut cpu).add_breakpoint(0xE002, None);
+
+    let result = cpu.execute(1000, &mut mem);
+
+    assert_eq!(result, Err(EmulatorError::DebugStop { pc: 0xE002, reason: StopReason::Breakpoint { address: 0xE002 } }));
+    assert_eq!((cpu.pc, cpu.x), (0xE002, 0));
+
+    // the instruction at the breakpoint runs on the next call
+    let result = cpu.execute(1000, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::DebugStop { pc: 0xE002, .. })));
+    assert_eq!(cpu.x, 1);
+}
+
+#[test]
+fn conditional_breakpoint() {
+    let (mut cpu, mut mem) = setup();
+    debugger(&mut cpu).add_breakpoint(0xE002, Some("X == 5".parse().unwrap()));
+
+    assert!(matches!(cpu.execute(1000, &mut mem), Err(EmulatorError::DebugStop { .. })));
+    assert_eq!(cpu.x, 5);
+}
+
+#[test]
+fn write_watchpoint_stops_after_instruction() {
+    let (mut cpu, mut mem) = setup();
+    debugger(&mut cpu).add_watchpoint(0x0200..=0x02FF, Watch::WRITE, Some("X >= 3".parse().unwrap()));
+
+    let result = cpu.run_for(1000, &mut mem);
+
+    // STX $0200 is at $E003 and has completed
+    assert_eq!(
+        result,
+        Err(EmulatorError::DebugStop {
+            pc: 0xE003,
+            reason: StopReason::Watchpoint { address: 0x0200, kind: Watch::WRITE, value: 3 }
+        })
+    );
+    assert_eq!(cpu.pc, 0xE006);
+    assert_eq!(mem[0x0200], 3);
+}
+
+#[test]
+fn read_watchpoint_ignores_writes() {
+    let (mut cpu, mut mem) = setup();
+    debugger(&mut cpu).add_watchpoint(0x0200..=0x0300, Watch::READ, None);
+    mem[0x0300] = 0x42;
+
+    let result = cpu.execute(1000, &mut mem);
+
+    assert_eq!(
+        result,
+        Err(EmulatorError::DebugStop {
+            pc: 0xE006,
+            reason: StopReason::Watchpoint { address: 0x0300, kind: Watch::READ, value: 0x42 }
+        })
+    );
+}
+
+#[test]
+fn execute_watchpoint_on_range() {
+    let (mut cpu, mut mem) = setup();
+    debugger(&mut cpu).add_watchpoint(0xE006..=0xE0FF, Watch::EXECUTE, None);
+
+    let result = cpu.execute(1000, &mut mem);
+
+    assert!(matches!(
+        result,
+        Err(EmulatorError::DebugStop { reason: StopReason::Watchpoint { address: 0xE006, kind: Watch::EXECUTE, .. }, .. })
+    ));
+    assert_eq!(cpu.pc, 0xE006);
+}
+
+#[test]
+fn condition_stops_anywhere() {
+    let (mut cpu, mut mem) = setup();
+    debugger(&mut cpu).conditions.push("X == $10 && !Z".parse().unwrap());
+
+    let result = cpu.execute(10_000, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::DebugStop { reason: StopReason::Condition { index: 0 }, .. })));
+    assert_eq!((cpu.pc, cpu.x), (0xE003, 0x10));
+}
+
+#[test]
+fn removed_breakpoints_no_longer_stop() {
+    let (mut cpu, mut mem) = setup();
+    debugger(&mut cpu).add_breakpoint(0xE002, None);
+
+    assert!(debugger(&mut cpu).remove_breakpoint(0xE002));
+    assert!(!debugger(&mut cpu).remove_breakpoint(0xE002));
+    assert!(cpu.run_for(100, &mut mem).is_ok());
+}
+
+#[test]
+fn parse_and_evaluate_conditions() {
+    let condition: Condition = "a == $42 || C && pc < 1000".parse().unwrap();
+
+    assert_eq!(
+        condition,
+        Condition::Any(vec![
+            Condition::Compare(Register::A, Comparison::Equal, 0x42),
+            Condition::All(vec![
+                Condition::Flag(Status::C, true),
+                Condition::Compare(Register::Pc, Comparison::Less, 1000),
+            ]),
+        ])
+    );
+    assert_eq!(condition.to_string(), "A == $42 || C && PC < $3E8");
+
+    let registers = Registers { pc: 0x0100, p: 0x31, ..Default::default() };
+    assert!(condition.evaluate(&registers));
+    assert!(!condition.evaluate(&Registers { pc: 0x1000, ..registers }));
+
+    assert!("Q == 1".parse::<Condition>().is_err());
+    assert!("A == zz".parse::<Condition>().is_err());
+    assert!("A".parse::<Condition>().is_err());
+}
@@ -0,0 +1,163 @@
+use emulator_6502::cpu::{Variant, CPU};
+use emulator_6502::consts::*;
+
+mod common;
+
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+/// Runs `opcode` with an immediate operand in decimal mode and returns the CPU afterwards
+fn run(variant: Variant, opcode: u8, a: u8, operand: u8, carry: bool) -> CPU {
+    let (mut cpu, mut mem) = common::setup_variant(variant, &[opcode, operand]);
+
+    cpu.a = a;
+    cpu.p.set_decimal(true);
+    cpu.p.set_carry(carry);
+
+    cpu.step(&mut mem).unwrap();
+
+    cpu
+}
+
+#[test]
+fn adc_all_valid_bcd() {
+    for variant in [Variant::Nmos6502, Variant::Cmos65C02] {
+        for a in 0..100 {
+            for b in 0..100 {
+                for carry in [false, true] {
+                    let cpu = run(variant, ADC_IM, to_bcd(a), to_bcd(b), carry);
+                    let sum = a as u16 + b as u16 + carry as u16;
+
+                    assert_eq!(cpu.a, to_bcd((sum % 100) as u8), "{:?} {} + {} + {}", variant, a, b, carry);
+                    assert_eq!(cpu.p.carry_flag(), sum >= 100, "{:?} {} + {} + {}", variant, a, b, carry);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn sbc_all_valid_bcd() {
+    for variant in [Variant::Nmos6502, Variant::Cmos65C02] {
+        for a in 0..100 {
+            for b in 0..100 {
+                for carry in [false, true] {
+                    let cpu = run(variant, SBC_IM, to_bcd(a), to_bcd(b), carry);
+                    let difference = a as i16 - b as i16 - !carry as i16;
+
+                    assert_eq!(cpu.a, to_bcd(difference.rem_euclid(100) as u8), "{:?} {} - {} - {}", variant, a, b, !carry);
+                    assert_eq!(cpu.p.carry_flag(), difference >= 0, "{:?} {} - {} - {}", variant, a, b, !carry);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cmos_flags_follow_result() {
+    for a in 0..100 {
+        for b in 0..100 {
+            for carry in [false, true] {
+                let cpu = run(Variant::Cmos65C02, ADC_IM, to_bcd(a), to_bcd(b), carry);
+
+                assert_eq!(cpu.p.zero_flag(), cpu.a == 0);
+                assert_eq!(cpu.p.negative_flag(), cpu.a & 0x80 == 0x80);
+
+                let cpu = run(Variant::Cmos65C02, SBC_IM, to_bcd(a), to_bcd(b), carry);
+
+                assert_eq!(cpu.p.zero_flag(), cpu.a == 0);
+                assert_eq!(cpu.p.negative_flag(), cpu.a & 0x80 == 0x80);
+            }
+        }
+    }
+}
+
+#[test]
+fn nmos_adc_zero_flag_is_binary() {
+    // 99 + 1 = 00 with carry, but the binary sum is $9A so Z stays clear and N is set
+    let cpu = run(Variant::Nmos6502, ADC_IM, 0x99, 0x01, false);
+
+    assert_eq!(cpu.a, 0x00);
+    assert!(cpu.p.carry_flag());
+    assert!(!cpu.p.zero_flag());
+    assert!(cpu.p.negative_flag());
+    assert!(!cpu.p.overflow_flag());
+}
+
+#[test]
+fn cmos_adc_zero_flag_is_decimal() {
+    let cpu = run(Variant::Cmos65C02, ADC_IM, 0x99, 0x01, false);
+
+    assert_eq!(cpu.a, 0x00);
+    assert!(cpu.p.carry_flag());
+    assert!(cpu.p.zero_flag());
+    assert!(!cpu.p.negative_flag());
+}
+
+#[test]
+fn nmos_adc_overflow() {
+    // 79 + 0 + carry: the unadjusted high digits are $70 + $10 = $80, which overflows
+    let cpu = run(Variant::Nmos6502, ADC_IM, 0x79, 0x00, true);
+
+    assert_eq!(cpu.a, 0x80);
+    assert!(cpu.p.overflow_flag());
+    assert!(cpu.p.negative_flag());
+}
+
+#[test]
+fn nmos_adc_invalid_bcd() {
+    // documented results for operands that are not valid BCD
+    let cpu = run(Variant::Nmos6502, ADC_IM, 0x0F, 0x0F, false);
+    assert_eq!(cpu.a, 0x14);
+
+    let cpu = run(Variant::Nmos6502, ADC_IM, 0xFF, 0xFF, true);
+    assert_eq!(cpu.a, 0x55);
+    assert!(cpu.p.carry_flag());
+}
+
+#[test]
+fn nmos_sbc_flags_are_binary() {
+    // 0 - 1 = 99 with borrow, the binary result is $FF
+    let cpu = run(Variant::Nmos6502, SBC_IM, 0x00, 0x01, true);
+
+    assert_eq!(cpu.a, 0x99);
+    assert!(!cpu.p.carry_flag());
+    assert!(cpu.p.negative_flag());
+    assert!(!cpu.p.zero_flag());
+    assert!(!cpu.p.overflow_flag());
+}
+
+#[test]
+fn binary_mode_unchanged() {
+    let (mut cpu, mut mem) = common::setup(&[ADC_IM, 0x50]);
+    cpu.a = 0x50;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0xA0);
+    assert!(cpu.p.overflow_flag());
+    assert!(cpu.p.negative_flag());
+    assert!(!cpu.p.carry_flag());
+}
+
+#[test]
+fn ricoh_2a03_ignores_decimal_flag() {
+    let cpu = run(Variant::Ricoh2A03, ADC_IM, 0x09, 0x01, false);
+    assert_eq!(cpu.a, 0x0A);
+    assert!(cpu.p.decimal_flag());
+
+    let cpu = run(Variant::Ricoh2A03, SBC_IM, 0x10, 0x01, true);
+    assert_eq!(cpu.a, 0x0F);
+}
+
+#[test]
+fn ricoh_2a03_sed_and_cld_toggle_flag() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Ricoh2A03, &[SED, CLD]);
+
+    cpu.step(&mut mem).unwrap();
+    assert!(cpu.p.decimal_flag());
+
+    cpu.step(&mut mem).unwrap();
+    assert!(!cpu.p.decimal_flag());
+}
@@ -0,0 +1,93 @@
+use emulator_6502::cpu::Variant;
+use emulator_6502::disasm;
+use emulator_6502::opcodes::Mode;
+use emulator_6502::memory::Memory;
+use emulator_6502::consts::*;
+
+mod common;
+
+#[test]
+fn decodes_operand_and_length() {
+    let mem = common::memory(&[LDA_ABSX, 0x34, 0x12]);
+
+    let instruction = disasm::disassemble(&mem, 0xE000, Variant::Nmos6502);
+
+    assert_eq!(instruction.mnemonic, "LDA");
+    assert_eq!(instruction.mode, Mode::AbsoluteX);
+    assert_eq!(instruction.operand, 0x1234);
+    assert_eq!(instruction.length, 3);
+    assert_eq!(instruction.target, None);
+    assert_eq!(instruction.to_string(), "LDA $1234,X");
+}
+
+#[test]
+fn formats_addressing_modes() {
+    let cases: &[(&[u8], &str)] = &[
+        (&[ASL_A], "ASL A"),
+        (&[LDA_IM, 0x10], "LDA #$10"),
+        (&[LDX_ZPY, 0x10], "LDX $10,Y"),
+        (&[JMP_IND, 0xFF, 0x10], "JMP ($10FF)"),
+        (&[LDA_INDX, 0x10], "LDA ($10,X)"),
+        (&[STA_INDY, 0x10], "STA ($10),Y"),
+        (&[LAX_ZP, 0x10], "LAX $10"),
+        (&[JAM_02], "JAM"),
+    ];
+
+    for (program, text) in cases {
+        let mem = common::memory(program);
+
+        assert_eq!(disasm::disassemble(&mem, 0xE000, Variant::Nmos6502).to_string(), *text);
+    }
+}
+
+#[test]
+fn resolves_branch_targets() {
+    let mem = common::memory(&[BNE, 0xFE, BEQ, 0x10]);
+
+    let backwards = disasm::disassemble(&mem, 0xE000, Variant::Nmos6502);
+    let forwards = disasm::disassemble(&mem, 0xE002, Variant::Nmos6502);
+
+    assert_eq!(backwards.target, Some(0xE000));
+    assert_eq!(forwards.target, Some(0xE014));
+    assert_eq!(forwards.to_string(), "BEQ $E014");
+}
+
+#[test]
+fn cmos_opcodes_depend_on_variant() {
+    let mem = common::memory(&[BBR0, 0x10, 0xFD, LDA_ZPIND, 0x20, WAI]);
+
+    let bbr = disasm::disassemble(&mem, 0xE000, Variant::Rockwell65C02);
+    assert_eq!(bbr.to_string(), "BBR0 $10,$E000");
+    assert_eq!(bbr.length, 3);
+
+    assert_eq!(disasm::disassemble(&mem, 0xE000, Variant::Cmos65C02).to_string(), "NOP");
+    assert_eq!(disasm::disassemble(&mem, 0xE003, Variant::Cmos65C02).to_string(), "LDA ($20)");
+    assert_eq!(disasm::disassemble(&mem, 0xE003, Variant::Nmos6502).to_string(), "JAM");
+
+    assert_eq!(disasm::disassemble(&mem, 0xE005, Variant::Wdc65C02).to_string(), "WAI");
+    assert_eq!(disasm::disassemble(&mem, 0xE005, Variant::Rockwell65C02).to_string(), "NOP");
+}
+
+#[test]
+fn listing_of_a_range() {
+    let mem = common::memory(&[LDA_IM, 0x10, STA_ABS, 0x00, 0x02, BNE, 0xF9]);
+
+    let listing = disasm::listing(&mem, 0xE000..=0xE005, Variant::Nmos6502);
+
+    assert_eq!(
+        listing,
+        "E000  A9 10     LDA #$10\n\
+         E002  8D 00 02  STA $0200\n\
+         E005  D0 F9     BNE $E000\n"
+    );
+}
+
+#[test]
+fn listing_stops_at_end_of_memory() {
+    let mut mem = Memory::new();
+    mem[0xFFFE] = JMP_ABS;
+
+    let listing = disasm::listing(&mem, 0xFFFE..=0xFFFF, Variant::Nmos6502);
+
+    assert_eq!(listing.lines().count(), 1);
+}
@@ -0,0 +1,66 @@
+use emulator_6502::bus::BusFault;
+use emulator_6502::consts::*;
+use emulator_6502::cpu::CPU;
+use emulator_6502::error::EmulatorError;
+use emulator_6502::memory::{Memory, MemoryMapBuilder, RomWrites};
+
+#[test]
+fn illegal_opcode() {
+    let mut mem = Memory::new();
+    mem[0xFFFC] = 0x00;
+    mem[0xFFFD] = 0xE0;
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut mem);
+
+    mem[0xE000] = NOP;
+    mem[0xE001] = 0x02;
+
+    let result = cpu.execute(4, &mut mem);
+
+    assert_eq!(result, Err(EmulatorError::IllegalOpcode { opcode: 0x02, pc: 0xE001 }));
+    assert_eq!(cpu.pc, 0xE001);
+}
+
+#[test]
+fn budget_exhausted() {
+    let mut mem = Memory::new();
+    mem[0xFFFC] = 0x00;
+    mem[0xFFFD] = 0xE0;
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut mem);
+
+    mem[0xE000] = LDA_ZP;
+    mem[0xE001] = 0x10;
+
+    let result = cpu.execute(2, &mut mem);
+
+    assert_eq!(result, Err(EmulatorError::BudgetExhausted { pc: 0xE000, needed: 3, left: 2 }));
+}
+
+#[test]
+fn rom_write_fault() {
+    let mut rom = vec![0; 0x1000];
+    rom[0x0000] = STA_ABS;
+    rom[0x0001] = 0x00;
+    rom[0x0002] = 0xF8;
+    rom[0x0FFC] = 0x00;
+    rom[0x0FFD] = 0xF0;
+
+    let mut map = MemoryMapBuilder::new()
+        .rom(0xF000, rom, RomWrites::Report)
+        .build()
+        .unwrap();
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut map);
+    cpu.a = 0x42;
+
+    let result = cpu.step(&mut map);
+
+    assert_eq!(
+        result,
+        Err(EmulatorError::BusFault { pc: 0xF000, fault: BusFault::ReadOnly { address: 0xF800, value: 0x42 } })
+    );
+}
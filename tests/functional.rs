@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::PathBuf;
+
+use emulator_6502::cpu::CPU;
+use emulator_6502::error::EmulatorError;
+use emulator_6502::memory::Memory;
+
+/// Address of the `success` trap in the default build of the functional test
+const FUNCTIONAL_SUCCESS: u16 = 0x3469;
+
+/// The functional test keeps the number of the test it is running at $0200
+const FUNCTIONAL_TEST_CASE: u16 = 0x0200;
+
+/// The decimal test stores 0 at $0B when every combination passed
+const DECIMAL_ERROR: u16 = 0x000B;
+
+/// Upper bound on instructions, the functional test needs about 30 million
+const MAX_STEPS: u64 = 100_000_000;
+
+/// The tests using this are ignored by default since the binaries are not checked in,
+/// run them with `cargo test -- --ignored` once they are in place
+fn fixture(name: &str) -> Vec<u8> {
+    let directory = std::env::var_os("EMULATOR_6502_FIXTURES")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+
+    fs::read(directory.join(name))
+        .unwrap_or_else(|error| panic!("cannot read {} in {}: {}", name, directory.display(), error))
+}
+
+/// Runs from `start` until the program jumps to itself, which is how the test suites signal both
+/// success and failure, or until it hits an opcode the CPU does not execute.\
+/// Returns the address the program stopped at
+fn run_until_trap(cpu: &mut CPU, mem: &mut Memory, start: u16) -> u16 {
+    cpu.pc = start;
+
+    for _ in 0..MAX_STEPS {
+        let pc = cpu.pc;
+
+        match cpu.step(mem) {
+            Ok(_) if cpu.pc == pc => return pc,
+            Ok(_) => (),
+            Err(EmulatorError::IllegalOpcode { pc, .. }) => return pc,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    panic!("no trap after {} instructions, pc is ${:04X}", MAX_STEPS, cpu.pc);
+}
+
+#[test]
+#[ignore = "needs Klaus fixtures in EMULATOR_6502_FIXTURES"]
+fn klaus_functional_test() {
+    let image = fixture("6502_functional_test.bin");
+
+    let mut mem = Memory::new();
+    mem.bytes[..image.len()].copy_from_slice(&image);
+
+    let mut cpu = CPU::default();
+    cpu.power_on(&mut mem);
+
+    let trap = run_until_trap(&mut cpu, &mut mem, 0x0400);
+
+    assert_eq!(
+        trap, FUNCTIONAL_SUCCESS,
+        "trapped at ${:04X} in test ${:02X}",
+        trap, mem[FUNCTIONAL_TEST_CASE]
+    );
+}
+
+#[test]
+#[ignore = "needs Klaus fixtures in EMULATOR_6502_FIXTURES"]
+fn klaus_decimal_test() {
+    let image = fixture("6502_decimal_test.bin");
+
+    let mut mem = Memory::new();
+    mem.bytes[0x0200..0x0200 + image.len()].copy_from_slice(&image);
+
+    let mut cpu = CPU::default();
+    cpu.power_on(&mut mem);
+
+    let trap = run_until_trap(&mut cpu, &mut mem, 0x0200);
+
+    assert_eq!(mem[DECIMAL_ERROR], 0, "decimal test failed, stopped at ${:04X}", trap);
+}
@@ -0,0 +1,207 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use emulator_6502::cpu::{Variant, CPU};
+use emulator_6502::debug::{Breakpoint, Debugger, Watch};
+use emulator_6502::gdb;
+use emulator_6502::memory::Memory;
+
+mod common;
+
+/// The GDB side of the connection
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn send(&mut self, data: &str) {
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.stream, "${}#{:02x}", data, checksum).unwrap();
+        assert_eq!(self.byte(), b'+', "{}", data);
+    }
+
+    /// Sends a packet and returns the reply, checking both acknowledgements
+    fn request(&mut self, data: &str) -> String {
+        self.send(data);
+        self.reply()
+    }
+
+    fn reply(&mut self) -> String {
+        assert_eq!(self.byte(), b'$');
+
+        let mut data = Vec::new();
+        loop {
+            match self.byte() {
+                b'#' => break,
+                byte => data.push(byte),
+            }
+        }
+
+        let checksum = [self.byte(), self.byte()];
+        let expected = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        assert_eq!(std::str::from_utf8(&checksum).unwrap(), format!("{:02x}", expected));
+
+        self.stream.write_all(b"+").unwrap();
+        String::from_utf8(data).unwrap()
+    }
+}
+
+/// Serves `cpu` on a loopback port while `session` talks to it from another thread
+fn debug_session(cpu: &mut CPU, mem: &mut Memory, session: impl FnOnce(&mut Client) + Send + 'static) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let mut client = Client { stream: TcpStream::connect(address).unwrap() };
+        session(&mut client);
+    });
+
+    let (stream, _) = listener.accept().unwrap();
+    gdb::serve(stream, cpu, mem).unwrap();
+
+    client.join().unwrap();
+}
+
+#[test]
+fn registers_and_memory() {
+    let (mut cpu, mut mem) = common::assembled(Variant::Nmos6502, common::COUNTER);
+
+    debug_session(&mut cpu, &mut mem, |gdb| {
+        assert!(gdb.request("qSupported:swbreak+").contains("qXfer:features:read+"));
+        assert_eq!(gdb.request("?"), "S05");
+
+        // pc little endian, sp, a, x, y, p
+        assert_eq!(gdb.request("g"), "00e0fd00000034");
+        assert_eq!(gdb.request("p0"), "00e0");
+        assert_eq!(gdb.request("p1"), "fd");
+
+        assert_eq!(gdb.request("P2=55"), "OK");
+        assert_eq!(gdb.request("G02e0fc11223301"), "OK");
+        assert_eq!(gdb.request("g"), "02e0fc11223331");
+
+        assert_eq!(gdb.request("me000,3"), "a200e8");
+        assert_eq!(gdb.request("M0300,2:7f80"), "OK");
+        assert_eq!(gdb.request("m0300,2"), "7f80");
+        assert_eq!(gdb.request("m0000,800").len(), 0x1000);
+        assert_eq!(gdb.request("m0000,801"), "E01");
+        assert_eq!(gdb.request("m0000,ffffffff"), "E01");
+
+        assert!(gdb.request("qXfer:features:read:target.xml:0,fff").starts_with("l<?xml"));
+        assert_eq!(gdb.request("vMustReplyEmpty"), "");
+
+        assert_eq!(gdb.request("D"), "OK");
+    });
+
+    assert_eq!((cpu.pc, cpu.sp, cpu.a, cpu.x, cpu.y), (0xE002, 0xFC, 0x11, 0x22, 0x33));
+    assert_eq!((mem[0x0300], mem[0x0301]), (0x7F, 0x80));
+}
+
+#[test]
+fn step_continue_and_breakpoints() {
+    let (mut cpu, mut mem) = common::assembled(Variant::Nmos6502, common::COUNTER);
+
+    debug_session(&mut cpu, &mut mem, |gdb| {
+        assert_eq!(gdb.request("s"), "S05");
+        assert_eq!(gdb.request("p0"), "02e0");
+
+        assert_eq!(gdb.request("Z0,e006,1"), "OK");
+        assert_eq!(gdb.request("c"), "S05");
+        assert_eq!((gdb.request("p0").as_str(), gdb.request("p3").as_str()), ("06e0", "01"));
+
+        // continuing from the breakpoint does not hit it right away
+        assert_eq!(gdb.request("c"), "S05");
+        assert_eq!((gdb.request("p0").as_str(), gdb.request("p3").as_str()), ("06e0", "02"));
+
+        assert_eq!(gdb.request("z0,e006,1"), "OK");
+        assert_eq!(gdb.request("s"), "S05");
+        assert_eq!(gdb.request("p0"), "09e0");
+
+        gdb.send("k");
+    });
+
+    assert!(cpu.debugger.unwrap().breakpoints.is_empty());
+}
+
+#[test]
+fn removes_only_its_own_breakpoints() {
+    let (mut cpu, mut mem) = common::assembled(Variant::Nmos6502, common::COUNTER);
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0xE006, None);
+    debugger.add_watchpoint(0x0200..=0x0200, Watch::WRITE, None);
+    cpu.debugger = Some(debugger);
+
+    debug_session(&mut cpu, &mut mem, |gdb| {
+        assert_eq!(gdb.request("Z0,e006,1"), "OK");
+        assert_eq!(gdb.request("z0,e006,1"), "OK");
+        // never inserted by GDB
+        assert_eq!(gdb.request("z0,e006,1"), "OK");
+        assert_eq!(gdb.request("z2,0200,1"), "OK");
+
+        assert_eq!(gdb.request("D"), "OK");
+    });
+
+    let debugger = cpu.debugger.unwrap();
+    assert_eq!(debugger.breakpoints, vec![Breakpoint { address: 0xE006, condition: None }]);
+    assert_eq!(debugger.watchpoints.len(), 1);
+}
+
+#[test]
+fn session_end_removes_inserted_breakpoints() {
+    let (mut cpu, mut mem) = common::assembled(Variant::Nmos6502, common::COUNTER);
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0xE006, None);
+    cpu.debugger = Some(debugger);
+
+    debug_session(&mut cpu, &mut mem, |gdb| {
+        assert_eq!(gdb.request("Z0,e006,1"), "OK");
+        assert_eq!(gdb.request("Z2,0200,1"), "OK");
+        assert_eq!(gdb.request("D"), "OK");
+    });
+
+    assert_eq!(cpu.debugger.as_ref().unwrap().breakpoints, vec![Breakpoint { address: 0xE006, condition: None }]);
+    assert!(cpu.debugger.as_ref().unwrap().watchpoints.is_empty());
+
+    // the connection drops without a detach
+    debug_session(&mut cpu, &mut mem, |gdb| {
+        assert_eq!(gdb.request("Z0,e009,1"), "OK");
+        assert_eq!(gdb.request("Z3,0300,1"), "OK");
+    });
+
+    assert_eq!(cpu.debugger.as_ref().unwrap().breakpoints, vec![Breakpoint { address: 0xE006, condition: None }]);
+    assert!(cpu.debugger.as_ref().unwrap().watchpoints.is_empty());
+}
+
+#[test]
+fn watchpoints_and_interrupt() {
+    let (mut cpu, mut mem) = common::assembled(Variant::Nmos6502, common::COUNTER);
+
+    debug_session(&mut cpu, &mut mem, |gdb| {
+        assert_eq!(gdb.request("Z2,0200,1"), "OK");
+        assert_eq!(gdb.request("c"), "T05watch:0200;");
+
+        // the store has completed
+        assert_eq!(gdb.request("p0"), "06e0");
+        assert_eq!(gdb.request("m0200,1"), "01");
+
+        assert_eq!(gdb.request("z2,0200,1"), "OK");
+        assert_eq!(gdb.request("Z3,0300,1"), "OK");
+        assert_eq!(gdb.request("c"), "T05rwatch:0300;");
+        assert_eq!(gdb.request("z3,0300,1"), "OK");
+
+        // the program loops forever until GDB interrupts it
+        gdb.send("c");
+        gdb.stream.write_all(&[0x03]).unwrap();
+        assert_eq!(gdb.reply(), "S02");
+
+        assert_eq!(gdb.request("D"), "OK");
+    });
+
+    assert!(cpu.debugger.unwrap().watchpoints.is_empty());
+}
@@ -0,0 +1,64 @@
+use emulator_6502::consts::*;
+
+mod common;
+
+#[test]
+fn compare_sets_flags_from_difference() {
+    let (mut cpu, mut mem) = common::setup(&[CMP_IM, 0x20, CPX_IM, 0x01, CPY_IM, 0x10]);
+    cpu.a = 0x10;
+    cpu.x = 0x01;
+    cpu.y = 0x20;
+
+    cpu.step(&mut mem).unwrap();
+    assert!(!cpu.p.carry_flag() && !cpu.p.zero_flag() && cpu.p.negative_flag());
+
+    cpu.step(&mut mem).unwrap();
+    assert!(cpu.p.carry_flag() && cpu.p.zero_flag() && !cpu.p.negative_flag());
+
+    cpu.step(&mut mem).unwrap();
+    assert!(cpu.p.carry_flag() && !cpu.p.zero_flag() && !cpu.p.negative_flag());
+}
+
+#[test]
+fn shifts_and_rotates_move_carry() {
+    let (mut cpu, mut mem) = common::setup(&[LSR_A, ROR_A, ASL_A, ROL_A]);
+    cpu.a = 0x01;
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.a, 0x00);
+    assert!(cpu.p.carry_flag() && cpu.p.zero_flag());
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.a, 0x80);
+    assert!(!cpu.p.carry_flag() && cpu.p.negative_flag());
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.a, 0x00);
+    assert!(cpu.p.carry_flag() && cpu.p.zero_flag());
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.a, 0x01);
+    assert!(!cpu.p.carry_flag());
+}
+
+#[test]
+fn bit_copies_operand_bits() {
+    let (mut cpu, mut mem) = common::setup(&[BIT_ZP, 0x10]);
+    cpu.a = 0x01;
+    mem[0x0010] = 0xC0;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert!(cpu.p.zero_flag() && cpu.p.overflow_flag() && cpu.p.negative_flag());
+}
+
+#[test]
+fn sty_stores_y() {
+    let (mut cpu, mut mem) = common::setup(&[STY_ZP, 0x10]);
+    cpu.x = 0x11;
+    cpu.y = 0x22;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(mem[0x0010], 0x22);
+}
@@ -0,0 +1,234 @@
+use emulator_6502::bus::BusFault;
+use emulator_6502::cpu::{Interrupt, CPU};
+use emulator_6502::debug::{Debugger, StopReason, Watch};
+use emulator_6502::error::EmulatorError;
+use emulator_6502::memory::{Memory, MemoryMapBuilder, RomWrites};
+use emulator_6502::consts::*;
+
+mod common;
+
+fn setup() -> (CPU, Memory) {
+    let (cpu, mut mem) = common::setup(&[NOP; 16]);
+    // IRQ/BRK handler at $F000, NMI handler at $F100
+    mem[0xFFFE] = 0x00;
+    mem[0xFFFF] = 0xF0;
+    mem[0xFFFA] = 0x00;
+    mem[0xFFFB] = 0xF1;
+    mem[0xF000] = RTI;
+    mem[0xF100] = RTI;
+
+    (cpu, mem)
+}
+
+#[test]
+fn irq_taken() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.p.set_interrupt(false);
+    cpu.set_irq_line(true);
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, Some(Interrupt::Irq));
+    assert_eq!(step.cycles, 7);
+    assert_eq!(cpu.pc, 0xF000);
+    assert!(cpu.p.interrupt_flag());
+    assert_eq!(mem[0x01FD], 0xE0);
+    assert_eq!(mem[0x01FC], 0x00);
+    // B clear, bit 5 set
+    assert_eq!(mem[0x01FB] & 0b00110000, 0b00100000);
+    assert_eq!(cpu.sp, 0xFA);
+}
+
+#[test]
+fn irq_masked() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.p.set_interrupt(true);
+    cpu.set_irq_line(true);
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, None);
+    assert_eq!(cpu.pc, 0xE001);
+}
+
+#[test]
+fn irq_is_level_triggered() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.p.set_interrupt(false);
+    cpu.set_irq_line(true);
+
+    cpu.step(&mut mem).unwrap();
+    // RTI restores the clear I flag with the line still asserted
+    cpu.step(&mut mem).unwrap();
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, Some(Interrupt::Irq));
+
+    cpu.set_irq_line(false);
+    cpu.step(&mut mem).unwrap();
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, None);
+    assert_eq!(step.opcode, NOP);
+}
+
+#[test]
+fn nmi_ignores_interrupt_flag() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.p.set_interrupt(true);
+    cpu.trigger_nmi();
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, Some(Interrupt::Nmi));
+    assert_eq!(cpu.pc, 0xF100);
+    assert!(!cpu.nmi_pending());
+}
+
+#[test]
+fn nmi_is_edge_triggered() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.trigger_nmi();
+
+    cpu.step(&mut mem).unwrap();
+    cpu.step(&mut mem).unwrap();
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, None);
+    assert_eq!(cpu.pc, 0xE001);
+}
+
+#[test]
+fn nmi_before_irq() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.p.set_interrupt(false);
+    cpu.set_irq_line(true);
+    cpu.trigger_nmi();
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, Some(Interrupt::Nmi));
+}
+
+#[test]
+fn cli_delays_irq_by_one_instruction() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.p.set_interrupt(true);
+    cpu.set_irq_line(true);
+    mem[0xE000] = CLI;
+
+    cpu.step(&mut mem).unwrap();
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, None);
+    assert_eq!(step.opcode, NOP);
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, Some(Interrupt::Irq));
+    assert_eq!(mem[0x01FC], 0x02);
+}
+
+#[test]
+fn irq_right_after_sei() {
+    let (mut cpu, mut mem) = setup();
+
+    cpu.p.set_interrupt(false);
+    mem[0xE000] = SEI;
+
+    cpu.step(&mut mem).unwrap();
+    cpu.set_irq_line(true);
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, Some(Interrupt::Irq));
+    // the pushed status already has I set
+    assert_eq!(mem[0x01FB] & 0b00000100, 0b00000100);
+}
+
+#[test]
+fn brk_pushes_break_flag() {
+    let (mut cpu, mut mem) = setup();
+
+    mem[0xE000] = BRK;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 7);
+    assert_eq!(cpu.pc, 0xF000);
+    assert_eq!(mem[0x01FB] & 0b00110000, 0b00110000);
+    assert!(!cpu.p.break_flag());
+
+    cpu.step(&mut mem).unwrap();
+
+    // BRK skips the byte following it
+    assert_eq!(cpu.pc, 0xE002);
+}
+
+#[test]
+fn plp_ignores_break_and_bit_5() {
+    let (mut cpu, mut mem) = setup();
+
+    mem[0xE000] = PLP;
+    mem[0x0100] = 0xFF;
+    cpu.sp = 0xFF;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.p.bits(), 0b11001111);
+}
+
+#[test]
+fn irq_push_into_rom_faults() {
+    let mut rom = vec![0; 0x100];
+    rom[0xFC] = 0x00;
+    rom[0xFD] = 0xF0;
+    rom[0xFE] = 0x00;
+    rom[0xFF] = 0xF1;
+
+    let mut map = MemoryMapBuilder::new()
+        .ram(0x0000..=0x00FF)
+        .rom(0x0100, vec![0; 0x100], RomWrites::Report)
+        .ram(0x0200..=0xFEFF)
+        .rom(0xFF00, rom, RomWrites::Ignore)
+        .build()
+        .unwrap();
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut map);
+    cpu.p.set_interrupt(false);
+    cpu.set_irq_line(true);
+
+    assert_eq!(
+        cpu.step(&mut map),
+        Err(EmulatorError::BusFault { pc: 0xF000, fault: BusFault::ReadOnly { address: 0x01FD, value: 0xF0 } })
+    );
+    assert_eq!(cpu.pc, 0xF100);
+}
+
+#[test]
+fn irq_push_hits_write_watchpoint() {
+    let (mut cpu, mut mem) = setup();
+    let mut debugger = Debugger::new();
+    debugger.add_watchpoint(0x0100..=0x01FF, Watch::WRITE, None);
+    cpu.debugger = Some(debugger);
+
+    cpu.p.set_interrupt(false);
+    cpu.set_irq_line(true);
+
+    assert_eq!(
+        cpu.step(&mut mem),
+        Err(EmulatorError::DebugStop {
+            pc: 0xE000,
+            reason: StopReason::Watchpoint { address: 0x01FD, kind: Watch::WRITE, value: 0xE0 },
+        })
+    );
+    // the interrupt has been entered
+    assert_eq!(cpu.pc, 0xF000);
+}
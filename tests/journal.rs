@@ -0,0 +1,165 @@
+use emulator_6502::bus::Bus;
+use emulator_6502::cpu::{Variant, CPU};
+use emulator_6502::journal::{Journal, JournalWrite};
+use emulator_6502::memory::{Memory, MemoryMapBuilder, RomWrites};
+use emulator_6502::snapshot::Snapshot;
+
+mod common;
+
+/// Increments X, stores it at $0200 and pushes it, forever. X is 1 after the first pass
+const PROGRAM: &str = "
+        .org $E000
+    reset:
+        LDX #0
+    loop:
+        INX
+        STX $0200
+        TXA
+        PHA
+        JMP loop
+
+        .org $FFFC
+        .word reset
+";
+
+/// `PROGRAM` journaling the last `depth` steps
+fn setup(depth: usize) -> (CPU, Memory) {
+    let (mut cpu, mem) = common::assembled(Variant::Nmos6502, PROGRAM);
+    cpu.journal = Some(Journal::new(depth));
+
+    (cpu, mem)
+}
+
+#[test]
+fn step_back_undoes_registers_and_memory() {
+    let (mut cpu, mut mem) = setup(100);
+    let mut history = Vec::new();
+
+    for _ in 0..30 {
+        history.push((cpu.state(), mem.bytes));
+        cpu.step(&mut mem).unwrap();
+    }
+
+    let end = (cpu.state(), mem.bytes);
+
+    for expected in history.iter().rev() {
+        assert_eq!(cpu.step_back(1, &mut mem), 1);
+        assert_eq!(&(cpu.state(), mem.bytes), expected);
+    }
+
+    assert_eq!(cpu.step_back(1, &mut mem), 0);
+
+    // running forward again takes the same path
+    for _ in 0..30 {
+        cpu.step(&mut mem).unwrap();
+    }
+
+    assert_eq!((cpu.state(), mem.bytes), end);
+}
+
+#[test]
+fn journal_keeps_the_last_steps_only() {
+    let (mut cpu, mut mem) = setup(3);
+
+    for _ in 0..10 {
+        cpu.step(&mut mem).unwrap();
+    }
+
+    assert_eq!(cpu.journal.as_ref().unwrap().len(), 3);
+    assert_eq!(cpu.step_back(10, &mut mem), 3);
+    assert!(cpu.journal.as_ref().unwrap().is_empty());
+}
+
+#[test]
+fn step_back_to_last_write() {
+    let (mut cpu, mut mem) = setup(100);
+
+    // LDX, then INX STX TXA PHA JMP twice, then INX
+    for _ in 0..12 {
+        cpu.step(&mut mem).unwrap();
+    }
+
+    let journal = cpu.journal.as_ref().unwrap();
+    assert_eq!(journal.last_write(0x0200), Some(5));
+    assert_eq!(journal.entries().nth(2).unwrap().writes, [JournalWrite { address: 0x0200, old: 0x00, new: 0x01 }]);
+
+    assert_eq!(cpu.step_back_to_write(0x0200, &mut mem), Some(0xE003));
+    assert_eq!((cpu.x, mem[0x0200]), (2, 1));
+
+    assert_eq!(cpu.step_back_to_write(0x0300, &mut mem), None);
+    assert_eq!(cpu.pc, 0xE003);
+}
+
+#[test]
+fn restore_and_reset_start_a_new_journal() {
+    let (mut cpu, mut mem) = setup(100);
+
+    for _ in 0..3 {
+        cpu.step(&mut mem).unwrap();
+    }
+    let snapshot = Snapshot::capture(&cpu, &mem);
+
+    for _ in 0..10 {
+        cpu.step(&mut mem).unwrap();
+    }
+    snapshot.restore(&mut cpu, &mut mem).unwrap();
+
+    // the steps after the snapshot are not undone on top of it
+    assert_eq!(cpu.step_back(1, &mut mem), 0);
+    assert_eq!((cpu.state(), mem[0x0200]), (snapshot.cpu, 1));
+
+    cpu.step(&mut mem).unwrap();
+    cpu.reset(&mut mem);
+
+    assert_eq!(cpu.step_back(1, &mut mem), 0);
+    assert_eq!(cpu.pc, 0xE000);
+}
+
+#[test]
+fn writes_that_change_nothing_are_not_recorded() {
+    let mut map = MemoryMapBuilder::new()
+        .ram(0x0000..=0x07FF)
+        .rom(0xE000, vec![0x8D, 0x00, 0xE0, 0x8D, 0x00, 0x02], RomWrites::Report)
+        .build()
+        .unwrap();
+
+    let mut cpu = CPU::default();
+    cpu.pc = 0xE000;
+    cpu.journal = Some(Journal::new(10));
+
+    // STA $E000 into ROM, STA $0200 into RAM that already holds the same value
+    assert!(cpu.step(&mut map).is_err());
+    cpu.step(&mut map).unwrap();
+
+    let journal = cpu.journal.as_ref().unwrap();
+    assert_eq!(journal.len(), 2);
+    assert!(journal.entries().all(|entry| entry.writes.is_empty()));
+
+    assert_eq!(cpu.step_back(2, &mut map), 2);
+    assert_eq!((cpu.pc, map.take_fault()), (0xE000, None));
+}
+
+#[test]
+fn step_back_leaves_the_data_bus_alone() {
+    let mut map = MemoryMapBuilder::new()
+        .ram(0x0000..=0x07FF)
+        .mirror(0x0800..=0x1FFF, 0x0000..=0x07FF)
+        .open_bus(0x2000..=0x2FFF)
+        .rom(0xE000, vec![0xA9, 0x42, 0x8D, 0x00, 0x0A], RomWrites::Ignore)
+        .build()
+        .unwrap();
+
+    let mut cpu = CPU::default();
+    cpu.pc = 0xE000;
+    cpu.journal = Some(Journal::new(10));
+
+    // LDA #$42, STA $0A00 through the mirror of $0200
+    cpu.step(&mut map).unwrap();
+    cpu.step(&mut map).unwrap();
+    assert_eq!((map.peek(0x0200), map.peek(0x2000)), (0x42, 0x42));
+
+    assert_eq!(cpu.step_back(1, &mut map), 1);
+    assert_eq!(cpu.pc, 0xE002);
+    assert_eq!((map.peek(0x0200), map.peek(0x0A00)), (0x00, 0x00));
+    assert_eq!(map.peek(0x2000), 0x42);
+}
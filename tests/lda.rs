@@ -1,6 +1,7 @@
 use emulator_6502::cpu::CPU;
 use emulator_6502::memory::Memory;
 use emulator_6502::consts::*;
+use emulator_6502::error::EmulatorError;
 
 #[test]
 fn lda_immediate_accum() {
@@ -9,30 +10,31 @@ fn lda_immediate_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     mem[0xE000] = LDA_IM;
     mem[0xE001] = 0x99;
 
-    cpu.execute(2, &mut mem);
+    cpu.execute(2, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_immediate_cycle_panic() {
+fn lda_immediate_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     mem[0xE000] = LDA_IM;
     mem[0xE001] = 0x99;
 
-    cpu.execute(3, &mut mem);
+    let result = cpu.execute(3, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 
     assert_eq!(cpu.a, 0x99);
 }
@@ -44,32 +46,33 @@ fn lda_zero_page_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     mem[0xE000] = LDA_ZP;
     mem[0xE001] = 0xFF;
     mem[0xFF] = 0x99;
 
-    cpu.execute(3, &mut mem);
+    cpu.execute(3, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_zero_page_cycle_panic() {
+fn lda_zero_page_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     mem[0xE000] = LDA_ZP;
     mem[0xE001] = 0xFF;
     mem[0xFF] = 0x99;
 
-    cpu.execute(4, &mut mem);
+    let result = cpu.execute(4, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 
     assert_eq!(cpu.a, 0x99);
 }
@@ -81,34 +84,35 @@ fn lda_zero_page_x_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.x = 0x0F;
     mem[0xE000] = LDA_ZPX;
     mem[0xE001] = 0x80;
     mem[0x008F] = 0x99;
 
-    cpu.execute(4, &mut mem);
+    cpu.execute(4, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_zero_page_x_cycle_panic() {
+fn lda_zero_page_x_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.x = 0x0F;
     mem[0xE000] = LDA_ZPX;
     mem[0xE001] = 0x80;
     mem[0x008F] = 0x99;
 
-    cpu.execute(5, &mut mem);
+    let result = cpu.execute(5, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 }
 
 #[test]
@@ -118,34 +122,35 @@ fn lda_absolute_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     mem[0xE000] = LDA_ABSX;
     mem[0xE001] = 0x00;
     mem[0xE002] = 0x20;
     mem[0x2000] = 0x99;
 
-    cpu.execute(4, &mut mem);
+    cpu.execute(4, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_absolute_cycle_panic() {
+fn lda_absolute_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     mem[0xE000] = LDA_ABSX;
     mem[0xE001] = 0x00;
     mem[0xE002] = 0x20;
     mem[0x2000] = 0x99;
 
-    cpu.execute(5, &mut mem);
+    let result = cpu.execute(5, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 }
 
 #[test]
@@ -155,7 +160,7 @@ fn lda_absolute_x_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.x = 0x92;
     mem[0xE000] = LDA_ABSX;
@@ -163,7 +168,7 @@ fn lda_absolute_x_accum() {
     mem[0xE002] = 0x20;
     mem[0x2092] = 0x99;
 
-    cpu.execute(4, &mut mem);
+    cpu.execute(4, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
@@ -175,7 +180,7 @@ fn lda_absolute_x_page_cross() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.x = 0x01;
     mem[0xE000] = LDA_ABSX;
@@ -183,20 +188,19 @@ fn lda_absolute_x_page_cross() {
     mem[0xE002] = 0x1F;
     mem[0x2000] = 0x99;
 
-    cpu.execute(5, &mut mem);
+    cpu.execute(5, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_absolute_x_cycle_panic() {
+fn lda_absolute_x_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.x = 0x92;
     mem[0xE000] = LDA_ABSX;
@@ -204,7 +208,9 @@ fn lda_absolute_x_cycle_panic() {
     mem[0xE002] = 0x20;
     mem[0x2092] = 0x99;
 
-    cpu.execute(5, &mut mem);
+    let result = cpu.execute(5, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 }
 
 #[test]
@@ -214,7 +220,7 @@ fn lda_absolute_y_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.y = 0x92;
     mem[0xE000] = LDA_ABSY;
@@ -222,7 +228,7 @@ fn lda_absolute_y_accum() {
     mem[0xE002] = 0x20;
     mem[0x2092] = 0x99;
 
-    cpu.execute(4, &mut mem);
+    cpu.execute(4, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
@@ -234,7 +240,7 @@ fn lda_absolute_y_page_cross() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.y = 0x01;
     mem[0xE000] = LDA_ABSY;
@@ -242,20 +248,19 @@ fn lda_absolute_y_page_cross() {
     mem[0xE002] = 0x1F;
     mem[0x2000] = 0x99;
 
-    cpu.execute(5, &mut mem);
+    cpu.execute(5, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_absolute_y_cycle_panic() {
+fn lda_absolute_y_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.y = 0x92;
     mem[0xE000] = LDA_ABSY;
@@ -263,7 +268,9 @@ fn lda_absolute_y_cycle_panic() {
     mem[0xE002] = 0x20;
     mem[0x2092] = 0x99;
 
-    cpu.execute(5, &mut mem);
+    let result = cpu.execute(5, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 }
 
 #[test]
@@ -273,7 +280,7 @@ fn lda_indexed_indirect_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.x = 0x04;
     mem[0xE000] = LDA_INDX;
@@ -282,20 +289,19 @@ fn lda_indexed_indirect_accum() {
     mem[0x25] = 0x20;
     mem[0x2074] = 0x99;
 
-    cpu.execute(6, &mut mem);
+    cpu.execute(6, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_indexed_indirect_cycle_panic() {
+fn lda_indexed_indirect_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.x = 0x04;
     mem[0xE000] = LDA_INDX;
@@ -304,7 +310,9 @@ fn lda_indexed_indirect_cycle_panic() {
     mem[0x25] = 0x20;
     mem[0x2074] = 0x99;
 
-    cpu.execute(7, &mut mem);
+    let result = cpu.execute(7, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 }
 
 #[test]
@@ -314,7 +322,7 @@ fn lda_indirect_indexed_accum() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.y = 0x10;
     mem[0xE000] = LDA_INDY;
@@ -323,7 +331,7 @@ fn lda_indirect_indexed_accum() {
     mem[0x87] = 0x40;
     mem[0x4038] = 0x99;
 
-    cpu.execute(5, &mut mem);
+    cpu.execute(5, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
@@ -335,7 +343,7 @@ fn lda_indirect_indexed_page_cross() {
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.y = 0x01;
     mem[0xE000] = LDA_INDY;
@@ -344,20 +352,19 @@ fn lda_indirect_indexed_page_cross() {
     mem[0x87] = 0x1F;
     mem[0x2000] = 0x99;
 
-    cpu.execute(6, &mut mem);
+    cpu.execute(6, &mut mem).unwrap();
 
     assert_eq!(cpu.a, 0x99);
 }
 
 #[test]
-#[should_panic]
-fn lda_indirect_indexed_cycle_panic() {
+fn lda_indirect_indexed_budget_exhausted() {
     let mut mem = Memory::new();
     mem[0xFFFC] = 0x00;
     mem[0xFFFD] = 0xE0;
 
     let mut cpu = CPU::default();
-    cpu.reset(&mem);
+    cpu.reset(&mut mem);
 
     cpu.y = 0x01;
     mem[0xE000] = LDA_INDY;
@@ -366,5 +373,7 @@ fn lda_indirect_indexed_cycle_panic() {
     mem[0x87] = 0x1F;
     mem[0x2000] = 0x99;
 
-    cpu.execute(7, &mut mem);
+    let result = cpu.execute(7, &mut mem);
+
+    assert!(matches!(result, Err(EmulatorError::BudgetExhausted { .. })));
 }
\ No newline at end of file
@@ -0,0 +1,226 @@
+use emulator_6502::bus::{Bus, BusFault};
+use emulator_6502::consts::*;
+use emulator_6502::cpu::CPU;
+use emulator_6502::error::EmulatorError;
+use emulator_6502::memory::{MapError, MemoryMapBuilder, RomWrite, RomWrites};
+use emulator_6502::{Byte, Word};
+
+#[test]
+fn ram_read_write() {
+    let mut map = MemoryMapBuilder::new().ram(0x0000..=0x07FF).build().unwrap();
+
+    map.write(0x0123, 0x99);
+
+    assert_eq!(map.read(0x0123), 0x99);
+}
+
+#[test]
+fn rom_ignores_writes() {
+    let mut map = MemoryMapBuilder::new()
+        .rom(0xF000, vec![0x11; 0x1000], RomWrites::Ignore)
+        .build()
+        .unwrap();
+
+    map.write(0xF000, 0x99);
+
+    assert_eq!(map.read(0xF000), 0x11);
+    assert!(map.take_rom_writes().is_empty());
+}
+
+#[test]
+fn rom_reports_writes() {
+    let mut map = MemoryMapBuilder::new()
+        .rom(0xF000, vec![0x11; 0x1000], RomWrites::Report)
+        .build()
+        .unwrap();
+
+    map.write(0xF001, 0x99);
+
+    assert_eq!(map.read(0xF001), 0x11);
+    assert_eq!(map.take_rom_writes(), vec![RomWrite { address: 0xF001, value: 0x99 }]);
+    assert!(map.take_rom_writes().is_empty());
+}
+
+#[test]
+fn mirrored_ram() {
+    let mut map = MemoryMapBuilder::new()
+        .ram(0x0000..=0x07FF)
+        .mirror(0x0800..=0x1FFF, 0x0000..=0x07FF)
+        .build()
+        .unwrap();
+
+    map.write(0x1801, 0x99);
+
+    assert_eq!(map.read(0x0001), 0x99);
+    assert_eq!(map.read(0x0801), 0x99);
+    assert_eq!(map.peek(0x1001), 0x99);
+}
+
+#[test]
+fn open_bus_returns_last_value() {
+    let mut map = MemoryMapBuilder::new()
+        .ram(0x0000..=0x00FF)
+        .open_bus(0x4000..=0x4FFF)
+        .build()
+        .unwrap();
+
+    map.write(0x0010, 0x5A);
+    map.read(0x0010);
+
+    assert_eq!(map.read(0x4000), 0x5A);
+    // unmapped addresses behave the same way
+    assert_eq!(map.read(0x8000), 0x5A);
+}
+
+#[test]
+fn overlapping_regions() {
+    let result = MemoryMapBuilder::new()
+        .ram(0x0000..=0x0FFF)
+        .ram(0x0800..=0x17FF)
+        .build();
+
+    assert!(matches!(result, Err(MapError::Overlap { .. })));
+}
+
+#[test]
+fn mirror_of_nothing() {
+    let result = MemoryMapBuilder::new()
+        .mirror(0x0800..=0x1FFF, 0x0000..=0x07FF)
+        .build();
+
+    assert!(matches!(result, Err(MapError::MirrorTarget { .. })));
+}
+
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn empty_ranges() {
+    let result = MemoryMapBuilder::new()
+        .ram(0x0000..=0x07FF)
+        .mirror(0x0800..=0x1FFF, 0x0010..=0x000F)
+        .build();
+
+    assert_eq!(result.err(), Some(MapError::EmptyRange { range: 0x0010..=0x000F }));
+
+    let result = MemoryMapBuilder::new().ram(0x0800..=0x07FF).build();
+
+    assert_eq!(result.err(), Some(MapError::EmptyRange { range: 0x0800..=0x07FF }));
+}
+
+struct Latch(Byte);
+
+impl Bus for Latch {
+    fn read(&mut self, _address: Word) -> Byte {
+        self.0
+    }
+
+    fn write(&mut self, _address: Word, value: Byte) {
+        self.0 = value.wrapping_add(1);
+    }
+
+    fn peek(&self, _address: Word) -> Byte {
+        self.0
+    }
+}
+
+#[test]
+fn cpu_runs_from_rom() {
+    let mut rom = vec![0; 0x1000];
+    rom[0x0000] = LDA_IM;
+    rom[0x0001] = 0x41;
+    rom[0x0002] = STA_ABS;
+    rom[0x0003] = 0x00;
+    rom[0x0004] = 0x40;
+    rom[0x0005] = LDA_ABS;
+    rom[0x0006] = 0x00;
+    rom[0x0007] = 0x40;
+    rom[0x0FFC] = 0x00;
+    rom[0x0FFD] = 0xF0;
+
+    let mut map = MemoryMapBuilder::new()
+        .ram(0x0000..=0x07FF)
+        .device(0x4000..=0x4000, Box::new(Latch(0)))
+        .rom(0xF000, rom, RomWrites::Ignore)
+        .build()
+        .unwrap();
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut map);
+
+    cpu.execute(10, &mut map).unwrap();
+
+    assert_eq!(cpu.a, 0x42);
+}
+
+#[test]
+fn rom_double_write_reports_one_fault() {
+    let mut rom = vec![0; 0x1000];
+    rom[0x0F00] = INC_ABS;
+    rom[0x0F01] = 0x00;
+    rom[0x0F02] = 0xF8;
+    rom[0x0F03] = NOP;
+    rom[0x0FFC] = 0x00;
+    rom[0x0FFD] = 0xFF;
+
+    let mut map = MemoryMapBuilder::new()
+        .rom(0xF000, rom, RomWrites::Report)
+        .build()
+        .unwrap();
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut map);
+
+    // the NMOS dummy write comes first
+    assert_eq!(
+        cpu.step(&mut map),
+        Err(EmulatorError::BusFault { pc: 0xFF00, fault: BusFault::ReadOnly { address: 0xF800, value: 0x00 } })
+    );
+    assert!(cpu.step(&mut map).is_ok());
+}
+
+/// Refuses every write
+struct Locked(Option<Word>);
+
+impl Bus for Locked {
+    fn read(&mut self, _address: Word) -> Byte {
+        0
+    }
+
+    fn write(&mut self, address: Word, _value: Byte) {
+        self.0 = Some(address);
+    }
+
+    fn peek(&self, _address: Word) -> Byte {
+        0
+    }
+
+    fn take_fault(&mut self) -> Option<BusFault> {
+        self.0.take().map(|address| BusFault::Device { address, reason: "locked".to_string() })
+    }
+}
+
+#[test]
+fn device_fault_reaches_cpu() {
+    let mut rom = vec![0; 0x1000];
+    rom[0x0000] = STA_ABS;
+    rom[0x0001] = 0x00;
+    rom[0x0002] = 0x40;
+    rom[0x0FFC] = 0x00;
+    rom[0x0FFD] = 0xF0;
+
+    let mut map = MemoryMapBuilder::new()
+        .device(0x4000..=0x4000, Box::new(Locked(None)))
+        .rom(0xF000, rom, RomWrites::Ignore)
+        .build()
+        .unwrap();
+
+    let mut cpu = CPU::default();
+    cpu.reset(&mut map);
+
+    assert_eq!(
+        cpu.step(&mut map),
+        Err(EmulatorError::BusFault {
+            pc: 0xF000,
+            fault: BusFault::Device { address: 0x4000, reason: "locked".to_string() },
+        })
+    );
+}
@@ -0,0 +1,151 @@
+use emulator_6502::cpu::Variant;
+use emulator_6502::monitor::{Monitor, MonitorError};
+
+fn monitor(program: &[u8]) -> Monitor {
+    let mut monitor = Monitor::new(Variant::Nmos6502);
+    monitor.load(0xE000, program).unwrap();
+    monitor.load(0xFFFC, &[0x00, 0xE0]).unwrap();
+    monitor.execute("reset").unwrap();
+
+    monitor
+}
+
+#[test]
+fn step_prints_instructions_and_registers() {
+    // LDA #$10, TAX
+    let mut monitor = monitor(&[0xA9, 0x10, 0xAA]);
+
+    let output = monitor.execute("step 2").unwrap();
+
+    assert_eq!(
+        output,
+        "E000  A9 10     LDA #$10\n\
+         E002  AA        TAX\n\
+         PC:E003 A:10 X:10 Y:00 SP:FD P:34 nv--dIzc CYC:11\n"
+    );
+}
+
+#[test]
+fn step_keeps_the_listing_on_error() {
+    // LDA #$10, then a JAM opcode
+    let mut monitor = monitor(&[0xA9, 0x10, 0x02]);
+
+    let output = monitor.execute("s 10").unwrap();
+
+    assert_eq!(
+        output,
+        "E000  A9 10     LDA #$10\n\
+         error: CPU jammed by opcode $02 at $E002\n\
+         PC:E002 A:10 X:00 Y:00 SP:FD P:34 nv--dIzc CYC:10\n"
+    );
+}
+
+#[test]
+fn continue_stops_at_breakpoints() {
+    // INX, JMP $E000
+    let mut monitor = monitor(&[0xE8, 0x4C, 0x00, 0xE0]);
+
+    monitor.execute("b E001").unwrap();
+    assert!(monitor.execute("c").unwrap().starts_with("breakpoint at $E001"));
+    assert!(monitor.execute("continue").unwrap().starts_with("breakpoint at $E001"));
+    assert_eq!(monitor.cpu.x, 2);
+
+    monitor.execute("delete $E001").unwrap();
+    assert_eq!(monitor.execute("break").unwrap(), "");
+}
+
+#[test]
+fn edit_and_view_memory_and_registers() {
+    let mut monitor = monitor(&[]);
+
+    monitor.execute("w 0200 48 69 00").unwrap();
+    assert_eq!(monitor.execute("m 200 3").unwrap(), format!("0200  {:<47}  Hi.\n", "48 69 00"));
+
+    monitor.execute("r a 42").unwrap();
+    monitor.execute("r PC 1234").unwrap();
+    assert_eq!((monitor.cpu.a, monitor.cpu.pc), (0x42, 0x1234));
+}
+
+#[test]
+fn assemble_and_disassemble() {
+    let mut monitor = monitor(&[]);
+
+    monitor.execute("a E000 LDA ($10),Y").unwrap();
+    monitor.execute("  a  E002   BNE $E000 ; loop if not done").unwrap();
+
+    assert_eq!(monitor.execute("d E000 2").unwrap(), "E000  B1 10     LDA ($10),Y\nE002  D0 FC     BNE $E000\n");
+}
+
+#[test]
+fn errors() {
+    let mut monitor = monitor(&[]);
+
+    assert!(matches!(monitor.execute("frobnicate"), Err(MonitorError::Usage(_))));
+    assert!(matches!(monitor.execute("m zz"), Err(MonitorError::Usage(_))));
+    assert!(matches!(monitor.execute("r x 100"), Err(MonitorError::Usage(_))));
+    assert!(matches!(monitor.execute("load /nonexistent/file"), Err(MonitorError::Io(_))));
+    assert!(matches!(monitor.execute("a 0 BOGUS"), Err(MonitorError::Asm(_))));
+    assert!(monitor.load(0xFFFF, &[1, 2]).is_err());
+}
+
+#[test]
+fn watchpoints_and_conditions() {
+    // INX, STX $0200, JMP $E000
+    let mut monitor = monitor(&[0xE8, 0x8E, 0x00, 0x02, 0x4C, 0x00, 0xE0]);
+
+    monitor.execute("watch w 0200 if X == 3").unwrap();
+    assert_eq!(monitor.execute("watch").unwrap(), "w $0200-$0200 if X == $3\n");
+    assert!(monitor.execute("c").unwrap().starts_with("watchpoint: write of $03 at $0200"));
+
+    monitor.execute("unwatch 0200").unwrap();
+    monitor.execute("cond X == 7").unwrap();
+    assert!(monitor.execute("c").unwrap().starts_with("condition 0 is true"));
+    assert_eq!(monitor.cpu.x, 7);
+
+    monitor.execute("cond clear").unwrap();
+    monitor.execute("b E004 if X == 9").unwrap();
+    assert_eq!(monitor.execute("b").unwrap(), "$E004 if X == $9\n");
+    monitor.execute("c").unwrap();
+    assert_eq!(monitor.cpu.x, 9);
+
+    assert!(matches!(monitor.execute("m 0 if A == 1"), Err(MonitorError::Usage(_))));
+}
+
+#[test]
+fn save_and_restore() {
+    // INX, JMP $E000
+    let mut monitor = monitor(&[0xE8, 0x4C, 0x00, 0xE0]);
+    let path = std::env::temp_dir().join(format!("6502-mon-{}.snap", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    monitor.execute("step 3").unwrap();
+    monitor.execute(&format!("save {}", path)).unwrap();
+    let saved = monitor.registers();
+
+    monitor.execute("step 5").unwrap();
+    monitor.execute("w 0300 AA").unwrap();
+
+    assert_eq!(monitor.execute(&format!("restore {}", path)).unwrap(), saved);
+    assert_eq!((monitor.cpu.x, monitor.mem[0x0300]), (2, 0));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn back_and_lastwrite() {
+    // INX, STX $0200, JMP $E000
+    let mut monitor = monitor(&[0xE8, 0x8E, 0x00, 0x02, 0x4C, 0x00, 0xE0]);
+
+    monitor.execute("step 7").unwrap();
+    assert_eq!((monitor.cpu.x, monitor.mem[0x0200]), (3, 2));
+
+    monitor.execute("back 2").unwrap();
+    assert_eq!((monitor.cpu.pc, monitor.cpu.x, monitor.mem[0x0200]), (0xE004, 2, 2));
+
+    assert!(monitor.execute("lastwrite 0200").unwrap().starts_with("$0200 was last written by $E001\n"));
+    assert_eq!((monitor.cpu.pc, monitor.cpu.x, monitor.mem[0x0200]), (0xE001, 2, 1));
+
+    assert!(matches!(monitor.execute("lastwrite 0300"), Err(MonitorError::Usage(_))));
+    assert!(monitor.execute("back 100").unwrap().starts_with("undid 4 instructions, the journal is empty\n"));
+    assert_eq!(monitor.cpu.pc, 0xE000);
+}
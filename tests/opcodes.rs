@@ -0,0 +1,108 @@
+use emulator_6502::cpu::{Status, Undocumented, Variant, CPU};
+use emulator_6502::error::EmulatorError;
+use emulator_6502::memory::Memory;
+use emulator_6502::opcodes::{self, Mode, Penalty};
+use emulator_6502::{Byte, Word};
+use emulator_6502::consts::*;
+
+const VARIANTS: [Variant; 5] =
+    [Variant::Nmos6502, Variant::Ricoh2A03, Variant::Cmos65C02, Variant::Rockwell65C02, Variant::Wdc65C02];
+
+/// Program address, operand bytes and index registers, chosen so indexing and branches cross pages in some of them
+const SETUPS: [(Word, [Byte; 2], Byte); 3] = [(0x0400, [0x10, 0x20], 0x00), (0x0400, [0xFF, 0x20], 0x01), (0x04F0, [0x20, 0x20], 0x00)];
+
+/// Runs `opcode` once and compares the cycles it took with what the table predicts
+fn check(variant: Variant, opcode: Byte, (pc, operand, index): (Word, [Byte; 2], Byte), status: Byte) {
+    let metadata = opcodes::lookup(variant, opcode);
+
+    let mut mem = Memory::new();
+    mem[pc] = opcode;
+    mem[pc + 1] = operand[0];
+    mem[pc + 2] = operand[1];
+    // ($FF),Y points at $20FF
+    mem[0x00FF] = 0xFF;
+    mem[0x0000] = 0x20;
+
+    let mut cpu = CPU::new(variant);
+    cpu.undocumented = Some(Undocumented::default());
+    cpu.pc = pc;
+    cpu.sp = 0xFD;
+    cpu.x = index;
+    cpu.y = index;
+    cpu.p = Status::from(status);
+
+    let step = match cpu.step(&mut mem) {
+        Ok(step) => step,
+        Err(EmulatorError::Jammed { .. }) => {
+            assert_eq!(metadata.cycles, 0, "{:?} ${:02X}", variant, opcode);
+            return;
+        }
+        Err(error) => panic!("{:?} ${:02X}: {}", variant, opcode, error),
+    };
+
+    let next = pc + metadata.length();
+    let penalty = match metadata.penalty {
+        Penalty::None => 0,
+        Penalty::PageCross => step.page_crossed as u32,
+        // taken branches do not end up at the next instruction with these operands
+        Penalty::Branch if cpu.pc != next => 1 + step.page_crossed as u32,
+        Penalty::Branch => 0,
+    };
+
+    assert_eq!(
+        step.cycles,
+        metadata.cycles as u32 + penalty,
+        "{:?} ${:02X} {} with status {:02X} at ${:04X}",
+        variant,
+        opcode,
+        metadata.mnemonic,
+        status,
+        pc
+    );
+
+    // everything but jumps, branches, calls and returns continues with the next instruction
+    if !matches!(metadata.mode, Mode::Relative | Mode::ZeroPageRelative)
+        && !matches!(metadata.mnemonic, "JMP" | "JSR" | "RTS" | "RTI" | "BRK" | "WAI" | "STP")
+    {
+        assert_eq!(cpu.pc, next, "{:?} ${:02X} {}", variant, opcode, metadata.mnemonic);
+    }
+}
+
+#[test]
+fn table_matches_cpu_cycles() {
+    for variant in VARIANTS {
+        for opcode in 0..=255 {
+            for setup in SETUPS {
+                // decimal mode stays off, the CMOS chips spend an extra cycle in it
+                check(variant, opcode, setup, 0b00000000);
+                check(variant, opcode, setup, 0b11000111);
+            }
+        }
+    }
+}
+
+#[test]
+fn lengths_and_flags() {
+    let lda = opcodes::lookup(Variant::Nmos6502, LDA_ABSX);
+    assert_eq!((lda.mnemonic, lda.mode, lda.length()), ("LDA", Mode::AbsoluteX, 3));
+    assert_eq!(lda.penalty, Penalty::PageCross);
+    assert_eq!(lda.flags, Status::N | Status::Z);
+
+    let adc = opcodes::lookup(Variant::Nmos6502, ADC_IM);
+    assert_eq!(adc.flags, Status::N | Status::V | Status::Z | Status::C);
+
+    let bit = opcodes::lookup(Variant::Cmos65C02, BIT_IM);
+    assert_eq!((bit.length(), bit.flags), (2, Status::Z));
+
+    let bbr = opcodes::lookup(Variant::Rockwell65C02, BBR0);
+    assert_eq!((bbr.length(), bbr.penalty), (3, Penalty::Branch));
+}
+
+#[test]
+fn undefined_cmos_opcodes_are_nops() {
+    assert_eq!(opcodes::lookup(Variant::Cmos65C02, BBR0).mnemonic, "NOP");
+    assert_eq!(opcodes::lookup(Variant::Cmos65C02, RMB0).length(), 1);
+    assert_eq!(opcodes::lookup(Variant::Rockwell65C02, WAI).mnemonic, "NOP");
+    assert_eq!(opcodes::lookup(Variant::Wdc65C02, WAI).mnemonic, "WAI");
+    assert_eq!(opcodes::lookup(Variant::Nmos6502, WAI).mnemonic, "SBX");
+}
@@ -0,0 +1,108 @@
+use emulator_6502::bus::Bus;
+use emulator_6502::cpu::CPU;
+use emulator_6502::memory::Memory;
+use emulator_6502::{Byte, Word};
+
+mod common;
+
+#[test]
+fn power_on_state() {
+    let mut mem = common::memory(&[]);
+    let mut cpu = CPU::default();
+
+    cpu.a = 0x12;
+    cpu.power_on(&mut mem);
+
+    assert_eq!(cpu.pc, 0xE000);
+    assert_eq!(cpu.sp, 0xFD);
+    assert_eq!(cpu.a, 0x00);
+    assert_eq!(cpu.x, 0x00);
+    assert_eq!(cpu.y, 0x00);
+    assert!(cpu.p.interrupt_flag());
+    assert_eq!(cpu.cycles, 7);
+}
+
+#[test]
+fn warm_reset_keeps_registers() {
+    let mut mem = common::memory(&[]);
+    let mut cpu = CPU::default();
+    cpu.power_on(&mut mem);
+
+    cpu.a = 0x11;
+    cpu.x = 0x22;
+    cpu.y = 0x33;
+    cpu.sp = 0x80;
+    cpu.p.set_carry(true);
+    cpu.p.set_interrupt(false);
+    cpu.reset(&mut mem);
+
+    assert_eq!(cpu.a, 0x11);
+    assert_eq!(cpu.x, 0x22);
+    assert_eq!(cpu.y, 0x33);
+    assert_eq!(cpu.sp, 0x7D);
+    assert!(cpu.p.carry_flag());
+    assert!(cpu.p.interrupt_flag());
+    assert_eq!(cpu.cycles, 14);
+}
+
+#[test]
+fn reset_sp_wraps() {
+    let mut mem = common::memory(&[]);
+    let mut cpu = CPU::default();
+
+    cpu.sp = 0x01;
+    cpu.reset(&mut mem);
+
+    assert_eq!(cpu.sp, 0xFE);
+}
+
+/// Records writes to make sure reset never writes to the stack
+struct WriteCounter {
+    mem: Memory,
+    writes: usize,
+}
+
+impl Bus for WriteCounter {
+    fn read(&mut self, address: Word) -> Byte {
+        self.mem.read(address)
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        self.writes += 1;
+        self.mem.write(address, value);
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        self.mem.peek(address)
+    }
+}
+
+#[test]
+fn reset_does_not_write() {
+    let mut bus = WriteCounter { mem: common::memory(&[]), writes: 0 };
+    let mut cpu = CPU::default();
+
+    cpu.power_on(&mut bus);
+    cpu.reset(&mut bus);
+
+    assert_eq!(bus.writes, 0);
+}
+
+#[test]
+fn randomized_power_on() {
+    let mut mem = common::memory(&[]);
+
+    let mut first = CPU::default();
+    first.power_on_randomized(&mut mem, 42);
+
+    let mut second = CPU::default();
+    second.power_on_randomized(&mut mem, 42);
+
+    let mut third = CPU::default();
+    third.power_on_randomized(&mut mem, 43);
+
+    assert_eq!((first.a, first.x, first.y, first.sp), (second.a, second.x, second.y, second.sp));
+    assert_ne!((first.a, first.x, first.y, first.sp), (third.a, third.x, third.y, third.sp));
+    assert!(first.p.interrupt_flag());
+    assert_eq!(first.pc, 0xE000);
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use emulator_6502::bus::{Access, BusCycle, LoggingBus};
+use emulator_6502::cpu::{Registers, Undocumented, Variant, CPU};
+use emulator_6502::error::EmulatorError;
+use emulator_6502::memory::Memory;
+use emulator_6502::opcodes;
+use emulator_6502::{Byte, Word};
+
+/// Failures printed per file before giving up on it
+const MAX_REPORTED: usize = 10;
+
+struct State {
+    registers: Registers,
+    ram: Vec<(Word, Byte)>,
+}
+
+fn fixtures() -> PathBuf {
+    std::env::var_os("EMULATOR_6502_FIXTURES")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"))
+        .join("single_step")
+}
+
+fn number(value: &Value) -> u64 {
+    value.as_u64().expect("expected a number")
+}
+
+fn parse_state(value: &Value) -> State {
+    let registers = Registers {
+        pc: number(&value["pc"]) as Word,
+        sp: number(&value["s"]) as Byte,
+        a: number(&value["a"]) as Byte,
+        x: number(&value["x"]) as Byte,
+        y: number(&value["y"]) as Byte,
+        p: number(&value["p"]) as Byte,
+    };
+
+    let ram = value["ram"]
+        .as_array()
+        .expect("expected a ram array")
+        .iter()
+        .map(|entry| (number(&entry[0]) as Word, number(&entry[1]) as Byte))
+        .collect();
+
+    State { registers, ram }
+}
+
+fn parse_cycles(value: &Value) -> Vec<BusCycle> {
+    value
+        .as_array()
+        .expect("expected a cycles array")
+        .iter()
+        .map(|cycle| BusCycle {
+            address: number(&cycle[0]) as Word,
+            value: number(&cycle[1]) as Byte,
+            access: match cycle[2].as_str() {
+                Some("read") => Access::Read,
+                Some("write") => Access::Write,
+                other => panic!("unknown access {:?}", other),
+            },
+        })
+        .collect()
+}
+
+/// Runs one vector and describes the first mismatch, if any.\
+/// Returns `Ok(None)` for the JAM opcodes of `variant`, those vectors cannot be checked
+fn run_vector(variant: Variant, test: &Value) -> Result<Option<()>, String> {
+    let initial = parse_state(&test["initial"]);
+    let expected = parse_state(&test["final"]);
+    let expected_cycles = parse_cycles(&test["cycles"]);
+
+    let mut bus = LoggingBus::new(initial.ram.iter().copied().collect::<Memory>());
+
+    let mut cpu = CPU::new(variant);
+    cpu.undocumented = Some(Undocumented::default());
+    cpu.set_registers(initial.registers);
+
+    match cpu.step(&mut bus) {
+        Ok(_) => (),
+        Err(EmulatorError::Jammed { opcode, .. }) if opcodes::lookup(variant, opcode).mnemonic == "JAM" => {
+            return Ok(None)
+        }
+        Err(error) => return Err(error.to_string()),
+    }
+
+    // B and bit 5 are not stored in the CPU, they always read back as set
+    let expected_registers = Registers { p: expected.registers.p | 0b00110000, ..expected.registers };
+    if cpu.registers() != expected_registers {
+        return Err(format!("registers {:02X?}, expected {:02X?}", cpu.registers(), expected_registers));
+    }
+
+    for (address, value) in expected.ram {
+        if bus.inner[address] != value {
+            return Err(format!("${:04X} is ${:02X}, expected ${:02X}", address, bus.inner[address], value));
+        }
+    }
+
+    if bus.log != expected_cycles {
+        return Err(format!("bus activity {:02X?}, expected {:02X?}", bus.log, expected_cycles));
+    }
+
+    Ok(Some(()))
+}
+
+fn run_file(variant: Variant, path: &Path) -> (usize, Vec<String>) {
+    let tests: Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+    let mut passed = 0;
+    let mut failures = Vec::new();
+
+    for test in tests.as_array().expect("expected an array of vectors") {
+        match run_vector(variant, test) {
+            Ok(Some(())) => passed += 1,
+            Ok(None) => (),
+            Err(reason) => {
+                failures.push(format!("{} \"{}\": {}", path.display(), test["name"].as_str().unwrap_or("?"), reason));
+
+                if failures.len() == MAX_REPORTED {
+                    break;
+                }
+            }
+        }
+    }
+
+    (passed, failures)
+}
+
+/// Runs every vector of the suite in `name`, recorded on `variant`.\
+/// Fails when the suite is missing or none of its vectors ran
+fn run_suite(name: &str, variant: Variant) {
+    // the suite keeps its files in a v1 subdirectory
+    let directory = fixtures().join(name);
+    let directory = if directory.join("v1").is_dir() { directory.join("v1") } else { directory };
+
+    let entries = fs::read_dir(&directory)
+        .unwrap_or_else(|error| panic!("cannot read {}: {}", directory.display(), error));
+
+    let mut files: Vec<PathBuf> = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect();
+    files.sort();
+
+    let mut passed = 0;
+    let mut failures = Vec::new();
+
+    for file in files {
+        let (file_passed, file_failures) = run_file(variant, &file);
+
+        passed += file_passed;
+        failures.extend(file_failures);
+    }
+
+    assert!(failures.is_empty(), "{} passed, {} failed:\n{}", passed, failures.len(), failures.join("\n"));
+    assert!(passed > 0, "no vectors ran from {}", directory.display());
+}
+
+#[test]
+fn nmos6502_vectors() {
+    run_suite("6502", Variant::Nmos6502);
+}
+
+#[test]
+#[ignore = "needs the single step suite in EMULATOR_6502_FIXTURES"]
+fn ricoh2a03_vectors() {
+    run_suite("nes6502", Variant::Ricoh2A03);
+}
+
+#[test]
+#[ignore = "needs the single step suite in EMULATOR_6502_FIXTURES"]
+fn cmos65c02_vectors() {
+    run_suite("synertek65c02", Variant::Cmos65C02);
+}
+
+#[test]
+#[ignore = "needs the single step suite in EMULATOR_6502_FIXTURES"]
+fn rockwell65c02_vectors() {
+    run_suite("rockwell65c02", Variant::Rockwell65C02);
+}
+
+#[test]
+#[ignore = "needs the single step suite in EMULATOR_6502_FIXTURES"]
+fn wdc65c02_vectors() {
+    run_suite("wdc65c02", Variant::Wdc65C02);
+}
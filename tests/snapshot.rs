@@ -0,0 +1,179 @@
+use emulator_6502::bus::Bus;
+use emulator_6502::cpu::{Undocumented, Variant, CPU};
+use emulator_6502::memory::{Memory, MemoryMapBuilder, RomWrites};
+use emulator_6502::snapshot::{Snapshot, SnapshotError, MAGIC, VERSION};
+use emulator_6502::{Byte, Word};
+
+mod common;
+
+/// A device counting the reads of its register
+struct Counter {
+    reads: Byte,
+}
+
+impl Bus for Counter {
+    fn read(&mut self, _: Word) -> Byte {
+        self.reads += 1;
+        self.reads
+    }
+
+    fn write(&mut self, _: Word, _: Byte) {}
+
+    fn peek(&self, _: Word) -> Byte {
+        self.reads
+    }
+
+    fn save_state(&self) -> Vec<Byte> {
+        vec![self.reads]
+    }
+
+    fn load_state(&mut self, state: &[Byte]) -> Result<(), SnapshotError> {
+        self.reads = state[0];
+        Ok(())
+    }
+}
+
+#[test]
+fn restored_run_matches_original() {
+    let (mut cpu, mut mem) = common::assembled(Variant::Wdc65C02, common::COUNTER);
+    cpu.run_for(100, &mut mem).unwrap();
+
+    let bytes = Snapshot::capture(&cpu, &mem).to_bytes();
+    assert_eq!(bytes[..8], MAGIC);
+
+    cpu.run_for(1000, &mut mem).unwrap();
+    let expected = (cpu.state(), mem.bytes);
+
+    let (mut other, mut other_mem) = (CPU::default(), Memory::new());
+    Snapshot::from_bytes(&bytes).unwrap().restore(&mut other, &mut other_mem).unwrap();
+    assert_eq!(other.variant, Variant::Wdc65C02);
+
+    other.run_for(1000, &mut other_mem).unwrap();
+    assert_eq!((other.state(), other_mem.bytes), expected);
+}
+
+#[test]
+fn interrupt_lines_and_settings_are_kept() {
+    let (mut cpu, mem) = common::assembled(Variant::Wdc65C02, common::COUNTER);
+    cpu.undocumented = Some(Undocumented { ane_magic: 0x11, lxa_magic: 0xFF });
+    cpu.set_irq_line(true);
+    cpu.trigger_nmi();
+
+    let snapshot = Snapshot::from_bytes(&Snapshot::capture(&cpu, &mem).to_bytes()).unwrap();
+    assert_eq!(snapshot.cpu, cpu.state());
+    assert!(snapshot.cpu.irq_line && snapshot.cpu.nmi_pending);
+
+    let mut other = CPU::default();
+    snapshot.restore(&mut other, &mut Memory::new()).unwrap();
+    assert!(other.nmi_pending());
+    assert_eq!(other.undocumented, cpu.undocumented);
+}
+
+#[test]
+fn invalid_data_is_rejected() {
+    let (cpu, mem) = common::assembled(Variant::Wdc65C02, common::COUNTER);
+    let bytes = Snapshot::capture(&cpu, &mem).to_bytes();
+
+    assert_eq!(Snapshot::from_bytes(b"6502"), Err(SnapshotError::BadMagic));
+    assert_eq!(Snapshot::from_bytes(b"NOTASNAPSHOT"), Err(SnapshotError::BadMagic));
+    assert_eq!(Snapshot::from_bytes(&bytes[..bytes.len() - 1]), Err(SnapshotError::Truncated));
+
+    let mut newer = bytes.clone();
+    newer[8..10].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    assert_eq!(Snapshot::from_bytes(&newer), Err(SnapshotError::UnsupportedVersion { version: VERSION + 1 }));
+
+    let mut older = bytes.clone();
+    older[8..10].copy_from_slice(&(VERSION - 1).to_le_bytes());
+    assert_eq!(Snapshot::from_bytes(&older), Err(SnapshotError::UnsupportedVersion { version: VERSION - 1 }));
+
+    // the variant comes after the magic, version, registers and cycle counter
+    let mut variant = bytes.clone();
+    variant[25] = 0xFF;
+    assert_eq!(
+        Snapshot::from_bytes(&variant),
+        Err(SnapshotError::Invalid { message: "unknown variant 255".to_string() })
+    );
+}
+
+#[test]
+fn memory_map_saves_ram_and_devices() {
+    let build = || {
+        MemoryMapBuilder::new()
+            .ram(0x0000..=0x07FF)
+            .device(0x4000..=0x4000, Box::new(Counter { reads: 0 }))
+            .rom(0xE000, vec![0xEA; 0x2000], RomWrites::Ignore)
+            .build()
+            .unwrap()
+    };
+
+    let mut map = build();
+    map.write(0x0123, 0x45);
+    map.read(0x4000);
+    map.read(0x4000);
+
+    let state = map.save_state();
+    map.write(0x0123, 0x00);
+    map.read(0x4000);
+
+    let mut other = build();
+    other.load_state(&state).unwrap();
+    assert_eq!((other.peek(0x0123), other.peek(0x4000)), (0x45, 2));
+
+    let mut smaller = MemoryMapBuilder::new().ram(0x0000..=0x00FF).build().unwrap();
+    assert!(matches!(smaller.load_state(&state), Err(SnapshotError::Mismatch { .. })));
+    assert!(matches!(Memory::new().load_state(&state), Err(SnapshotError::Mismatch { .. })));
+}
+
+/// A device that never accepts a state
+struct Sealed;
+
+impl Bus for Sealed {
+    fn read(&mut self, _: Word) -> Byte {
+        0
+    }
+
+    fn write(&mut self, _: Word, _: Byte) {}
+
+    fn peek(&self, _: Word) -> Byte {
+        0
+    }
+
+    fn load_state(&mut self, _: &[Byte]) -> Result<(), SnapshotError> {
+        Err(SnapshotError::Mismatch { message: "sealed".to_string() })
+    }
+}
+
+#[test]
+fn refused_device_state_leaves_map_alone() {
+    let mut map = MemoryMapBuilder::new()
+        .ram(0x0000..=0x07FF)
+        .device(0x4000..=0x4000, Box::new(Counter { reads: 0 }))
+        .device(0x4001..=0x4001, Box::new(Counter { reads: 0 }))
+        .build()
+        .unwrap();
+    map.write(0x0123, 0x45);
+    map.read(0x4000);
+    let state = map.save_state();
+
+    let mut other = MemoryMapBuilder::new()
+        .ram(0x0000..=0x07FF)
+        .device(0x4000..=0x4000, Box::new(Counter { reads: 7 }))
+        .device(0x4001..=0x4001, Box::new(Sealed))
+        .build()
+        .unwrap();
+
+    assert!(other.load_state(&state).is_err());
+    assert_eq!((other.peek(0x0123), other.peek(0x4000)), (0x00, 7));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let (mut cpu, mut mem) = common::assembled(Variant::Wdc65C02, common::COUNTER);
+    cpu.run_for(100, &mut mem).unwrap();
+
+    let snapshot = Snapshot::capture(&cpu, &mem);
+    let json = serde_json::to_string(&snapshot).unwrap();
+
+    assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+}
@@ -0,0 +1,118 @@
+use emulator_6502::consts::*;
+
+mod common;
+
+#[test]
+fn pha_writes_to_page_one() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    cpu.a = 0x99;
+    mem[0xE000] = PHA;
+
+    cpu.execute(3, &mut mem).unwrap();
+
+    // reset leaves the stack pointer at $FD
+    assert_eq!(mem[0x01FD], 0x99);
+    assert_eq!(mem[0x00FD], 0x00);
+    assert_eq!(cpu.sp, 0xFC);
+}
+
+#[test]
+fn pha_pla_round_trip() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    cpu.a = 0x99;
+    mem[0xE000] = PHA;
+    mem[0xE001] = LDA_IM;
+    mem[0xE002] = 0x00;
+    mem[0xE003] = PLA;
+
+    cpu.execute(9, &mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x99);
+    assert_eq!(cpu.sp, 0xFD);
+}
+
+#[test]
+fn php_plp_round_trip() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    mem[0xE000] = SEC;
+    mem[0xE001] = PHP;
+    mem[0xE002] = CLC;
+    mem[0xE003] = PLP;
+
+    cpu.execute(11, &mut mem).unwrap();
+
+    assert!(cpu.p.carry_flag());
+    assert_eq!(cpu.sp, 0xFD);
+}
+
+#[test]
+fn push_wraps_around() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    cpu.sp = 0x00;
+    cpu.a = 0x11;
+    mem[0xE000] = PHA;
+    mem[0xE001] = PHA;
+
+    cpu.execute(6, &mut mem).unwrap();
+
+    assert_eq!(mem[0x0100], 0x11);
+    assert_eq!(mem[0x01FF], 0x11);
+    assert_eq!(cpu.sp, 0xFE);
+}
+
+#[test]
+fn pull_wraps_around() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    cpu.sp = 0xFF;
+    mem[0x0100] = 0x42;
+    mem[0xE000] = PLA;
+
+    cpu.execute(4, &mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x42);
+    assert_eq!(cpu.sp, 0x00);
+}
+
+#[test]
+fn jsr_rts() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    mem[0xE000] = JSR;
+    mem[0xE001] = 0x00;
+    mem[0xE002] = 0xF0;
+    mem[0xF000] = RTS;
+
+    cpu.execute(6, &mut mem).unwrap();
+
+    assert_eq!(cpu.pc, 0xF000);
+    // return address - 1 is pushed high byte first
+    assert_eq!(mem[0x01FD], 0xE0);
+    assert_eq!(mem[0x01FC], 0x02);
+    assert_eq!(cpu.sp, 0xFB);
+
+    cpu.execute(6, &mut mem).unwrap();
+
+    assert_eq!(cpu.pc, 0xE003);
+    assert_eq!(cpu.sp, 0xFD);
+}
+
+#[test]
+fn stack_does_not_touch_zero_page() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    mem[0x00FD] = 0x12;
+    mem[0x00FC] = 0x34;
+    mem[0xE000] = JSR;
+    mem[0xE001] = 0x00;
+    mem[0xE002] = 0xF0;
+
+    cpu.execute(6, &mut mem).unwrap();
+
+    assert_eq!(mem[0x00FD], 0x12);
+    assert_eq!(mem[0x00FC], 0x34);
+}
@@ -0,0 +1,100 @@
+use emulator_6502::cpu::StepInfo;
+use emulator_6502::consts::*;
+use emulator_6502::error::EmulatorError;
+
+mod common;
+
+#[test]
+fn step_single_instruction() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    mem[0xE000] = LDA_IM;
+    mem[0xE001] = 0x99;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step, StepInfo { opcode: LDA_IM, address: 0xE000, cycles: 2, page_crossed: false, interrupt: None });
+    assert_eq!(cpu.a, 0x99);
+    assert_eq!(cpu.pc, 0xE002);
+    // 7 reset cycles + 2
+    assert_eq!(cpu.cycles, 9);
+}
+
+#[test]
+fn step_page_cross() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    cpu.x = 0x01;
+    mem[0xE000] = LDA_ABSX;
+    mem[0xE001] = 0xFF;
+    mem[0xE002] = 0x1F;
+    mem[0x2000] = 0x99;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 5);
+    assert!(step.page_crossed);
+    assert_eq!(cpu.a, 0x99);
+}
+
+#[test]
+fn step_branch_page_cross() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    cpu.pc = 0xE0F0;
+    mem[0xE0F0] = BNE;
+    mem[0xE0F1] = 0x20;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert!(step.page_crossed);
+    assert_eq!(cpu.pc, 0xE112);
+}
+
+#[test]
+fn run_for_overshoot() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    mem[0xE000] = LDA_IM;
+    mem[0xE001] = 0x01;
+    mem[0xE002] = LDA_ZP;
+    mem[0xE003] = 0x10;
+    mem[0x0010] = 0x99;
+
+    // LDA_IM takes 2 cycles and LDA_ZP 3, so a budget of 3 overshoots by 2
+    let left = cpu.run_for(3, &mut mem).unwrap();
+
+    assert_eq!(left, -2);
+    assert_eq!(cpu.a, 0x99);
+    assert_eq!(cpu.cycles, 7 + 5);
+}
+
+#[test]
+fn run_for_exact() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    mem[0xE000] = LDA_IM;
+    mem[0xE001] = 0x01;
+    mem[0xE002] = LDA_ZP;
+    mem[0xE003] = 0x10;
+
+    let left = cpu.run_for(2, &mut mem).unwrap();
+
+    assert_eq!(left, 0);
+    assert_eq!(cpu.pc, 0xE002);
+}
+
+#[test]
+fn run_for_large_budget() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+
+    mem[0xE000] = LDA_IM;
+    mem[0xE001] = 0x99;
+    mem[0xE002] = 0x02;
+
+    // a budget above i32::MAX still runs, up to the illegal opcode
+    let result = cpu.run_for(3_000_000_000, &mut mem);
+
+    assert_eq!(result, Err(EmulatorError::IllegalOpcode { opcode: 0x02, pc: 0xE002 }));
+    assert_eq!(cpu.a, 0x99);
+}
@@ -0,0 +1,83 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use emulator_6502::cpu::{Interrupt, Registers, Variant};
+use emulator_6502::trace::{NestestLogger, TraceEvent};
+use emulator_6502::consts::*;
+
+mod common;
+
+#[test]
+fn tracer_sees_state_before_each_instruction() {
+    let (mut cpu, mut mem) = common::setup(&[]);
+    mem[0xE000] = LDA_IM;
+    mem[0xE001] = 0x42;
+    mem[0xE002] = TAX;
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = events.clone();
+    cpu.tracer = Some(Box::new(move |event: &TraceEvent| sink.borrow_mut().push(*event)));
+
+    cpu.execute(4, &mut mem).unwrap();
+
+    let events = events.borrow();
+    assert_eq!(events.len(), 2);
+
+    assert_eq!(events[0].registers.pc, 0xE000);
+    assert_eq!(events[0].bytes, [LDA_IM, 0x42, TAX]);
+    assert_eq!(events[0].cycles, 7);
+
+    assert_eq!(events[1].opcode(), TAX);
+    assert_eq!(events[1].registers.a, 0x42);
+    assert_eq!(events[1].cycles, 9);
+}
+
+#[test]
+fn nestest_line() {
+    let event = TraceEvent {
+        registers: Registers { pc: 0xC000, sp: 0xFD, a: 0x00, x: 0x00, y: 0x00, p: 0x34 },
+        bytes: [JMP_ABS, 0xF5, 0xC5],
+        cycles: 7,
+        variant: Variant::Nmos6502,
+        interrupt: None,
+    };
+
+    assert_eq!(
+        NestestLogger::<Vec<u8>>::format(&event),
+        "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7"
+    );
+}
+
+#[test]
+fn interrupt_entry_is_traced() {
+    let (mut cpu, mut mem) = common::setup(&[NOP]);
+    mem[0xFFFA] = 0x00;
+    mem[0xFFFB] = 0xF0;
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = events.clone();
+    cpu.tracer = Some(Box::new(move |event: &TraceEvent| sink.borrow_mut().push(*event)));
+
+    cpu.trigger_nmi();
+    cpu.step(&mut mem).unwrap();
+
+    let events = events.borrow();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].interrupt, Some(Interrupt::Nmi));
+    assert_eq!(
+        NestestLogger::<Vec<u8>>::format(&events[0]),
+        "E000            *NMI                            A:00 X:00 Y:00 P:24 SP:FD CYC:7"
+    );
+}
+
+#[test]
+fn nestest_logger_writes_lines() {
+    let mut logger = NestestLogger::new(Vec::new());
+    let event = TraceEvent { registers: Registers::default(), bytes: [NOP, 0x00, 0x00], cycles: 0, variant: Variant::Nmos6502, interrupt: None };
+
+    emulator_6502::trace::Tracer::trace(&mut logger, &event);
+    emulator_6502::trace::Tracer::trace(&mut logger, &event);
+
+    let output = String::from_utf8(logger.into_inner()).unwrap();
+    assert_eq!(output.lines().count(), 2);
+}
@@ -0,0 +1,229 @@
+use emulator_6502::cpu::{Undocumented, Variant, CPU};
+use emulator_6502::memory::Memory;
+use emulator_6502::error::EmulatorError;
+use emulator_6502::consts::*;
+
+mod common;
+
+fn setup(program: &[u8]) -> (CPU, Memory) {
+    let (mut cpu, mem) = common::setup(program);
+    cpu.undocumented = Some(Undocumented::default());
+
+    (cpu, mem)
+}
+
+#[test]
+fn disabled_by_default() {
+    let (mut cpu, mut mem) = setup(&[LAX_ZP, 0x10]);
+    cpu.undocumented = None;
+
+    let result = cpu.step(&mut mem);
+
+    assert_eq!(result, Err(EmulatorError::IllegalOpcode { opcode: LAX_ZP, pc: 0xE000 }));
+    assert_eq!(cpu.pc, 0xE000);
+}
+
+#[test]
+fn not_executed_by_65c02() {
+    let (mut cpu, mut mem) = setup(&[SBX_IM, 0x10]);
+    cpu.variant = Variant::Cmos65C02;
+
+    cpu.x = 0x20;
+
+    // $CB is a single cycle NOP on the 65C02
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 1);
+    assert_eq!(cpu.x, 0x20);
+    assert_eq!(cpu.pc, 0xE001);
+}
+
+#[test]
+fn lax_loads_a_and_x() {
+    let (mut cpu, mut mem) = setup(&[LAX_ABSY, 0xFF, 0x20]);
+    cpu.y = 0x01;
+    mem[0x2100] = 0x80;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 5);
+    assert_eq!((cpu.a, cpu.x), (0x80, 0x80));
+    assert!(cpu.p.negative_flag());
+}
+
+#[test]
+fn sax_stores_a_and_x() {
+    let (mut cpu, mut mem) = setup(&[SAX_ZP, 0x10]);
+    cpu.a = 0b11110000;
+    cpu.x = 0b10101010;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(mem[0x0010], 0b10100000);
+}
+
+#[test]
+fn dcp_decrements_then_compares() {
+    let (mut cpu, mut mem) = setup(&[DCP_ABSY, 0x00, 0x20]);
+    cpu.a = 0x41;
+    mem[0x2000] = 0x42;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 7);
+    assert_eq!(mem[0x2000], 0x41);
+    assert!(cpu.p.zero_flag() && cpu.p.carry_flag());
+}
+
+#[test]
+fn isc_increments_then_subtracts() {
+    let (mut cpu, mut mem) = setup(&[ISC_INDX, 0x10]);
+    cpu.a = 0x10;
+    cpu.p.set_carry(true);
+    mem[0x0010] = 0x00;
+    mem[0x0011] = 0x20;
+    mem[0x2000] = 0x04;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 8);
+    assert_eq!(mem[0x2000], 0x05);
+    assert_eq!(cpu.a, 0x0B);
+}
+
+#[test]
+fn slo_shifts_then_ors() {
+    let (mut cpu, mut mem) = setup(&[SLO_ZP, 0x10]);
+    cpu.a = 0x01;
+    mem[0x0010] = 0x81;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.cycles, 5);
+    assert_eq!(mem[0x0010], 0x02);
+    assert_eq!(cpu.a, 0x03);
+    assert!(cpu.p.carry_flag());
+}
+
+#[test]
+fn rra_uses_carry_from_rotation() {
+    let (mut cpu, mut mem) = setup(&[RRA_ZP, 0x10]);
+    cpu.a = 0x10;
+    mem[0x0010] = 0x03;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(mem[0x0010], 0x01);
+    assert_eq!(cpu.a, 0x12);
+}
+
+#[test]
+fn anc_copies_negative_to_carry() {
+    let (mut cpu, mut mem) = setup(&[ANC_IM, 0x80]);
+    cpu.a = 0xFF;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x80);
+    assert!(cpu.p.carry_flag() && cpu.p.negative_flag());
+}
+
+#[test]
+fn arr_binary_flags() {
+    let (mut cpu, mut mem) = setup(&[ARR_IM, 0xFF]);
+    cpu.a = 0xC0;
+    cpu.p.set_carry(true);
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0xE0);
+    assert!(cpu.p.carry_flag() && !cpu.p.overflow_flag() && cpu.p.negative_flag());
+}
+
+#[test]
+fn sbx_subtracts_from_a_and_x() {
+    let (mut cpu, mut mem) = setup(&[SBX_IM, 0x02]);
+    cpu.a = 0x0F;
+    cpu.x = 0x03;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.x, 0x01);
+    assert!(cpu.p.carry_flag());
+}
+
+#[test]
+fn ane_and_lxa_use_magic_constants() {
+    let (mut cpu, mut mem) = setup(&[ANE_IM, 0xFF, LXA_IM, 0x0F]);
+    cpu.undocumented = Some(Undocumented { ane_magic: 0x00, lxa_magic: 0xFF });
+    cpu.a = 0x0F;
+    cpu.x = 0x3C;
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.a, 0x0C);
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!((cpu.a, cpu.x), (0x0F, 0x0F));
+}
+
+#[test]
+fn sha_page_cross_replaces_high_byte() {
+    let (mut cpu, mut mem) = setup(&[SHA_ABSY, 0xFF, 0x12, SHX_ABSY, 0x00, 0x12]);
+    cpu.a = 0xFF;
+    cpu.x = 0x03;
+    cpu.y = 0x01;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    // A & X & ($12 + 1) = $03, which also becomes the high byte of $1300
+    assert_eq!(step.cycles, 5);
+    assert_eq!(mem[0x0300], 0x03);
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(mem[0x1201], 0x03);
+}
+
+#[test]
+fn nops_read_operands() {
+    let cases: &[(&[u8], u32)] = &[
+        (&[NOP_IMP_1A], 2),
+        (&[NOP_IM_80, 0x00], 2),
+        (&[NOP_ZP_04, 0x00], 3),
+        (&[NOP_ZPX_14, 0x00], 4),
+        (&[NOP_ABS_0C, 0x00, 0x20], 4),
+        (&[NOP_ABSX_1C, 0x00, 0x20], 4),
+    ];
+
+    for (program, cycles) in cases {
+        let (mut cpu, mut mem) = setup(program);
+
+        assert_eq!(cpu.step(&mut mem).unwrap().cycles, *cycles, "opcode {:02X}", program[0]);
+        assert_eq!(cpu.pc, 0xE000 + program.len() as u16);
+    }
+}
+
+#[test]
+fn jam_halts_until_reset() {
+    let (mut cpu, mut mem) = setup(&[JAM_02]);
+
+    assert_eq!(cpu.step(&mut mem), Err(EmulatorError::Jammed { opcode: JAM_02, pc: 0xE000 }));
+    assert_eq!(cpu.step(&mut mem), Err(EmulatorError::Jammed { opcode: JAM_02, pc: 0xE000 }));
+
+    mem[0xE000] = NOP;
+    cpu.reset(&mut mem);
+
+    assert!(cpu.step(&mut mem).is_ok());
+}
+
+#[test]
+fn executed_by_2a03() {
+    let (mut cpu, mut mem) = setup(&[ARR_IM, 0xFF]);
+    cpu.variant = Variant::Ricoh2A03;
+    cpu.a = 0x0F;
+    cpu.p.set_decimal(true);
+
+    cpu.step(&mut mem).unwrap();
+
+    // binary result, no decimal fix up
+    assert_eq!(cpu.a, 0x07);
+}
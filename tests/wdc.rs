@@ -0,0 +1,113 @@
+use emulator_6502::cpu::{Interrupt, Variant, CPU};
+use emulator_6502::memory::Memory;
+use emulator_6502::consts::*;
+
+mod common;
+
+/// `common::setup_variant` with the IRQ handler at $3000
+fn setup(variant: Variant, program: &[u8]) -> (CPU, Memory) {
+    let (cpu, mut mem) = common::setup_variant(variant, program);
+    mem[0xFFFE] = 0x00;
+    mem[0xFFFF] = 0x30;
+
+    (cpu, mem)
+}
+
+#[test]
+fn rmb_and_smb() {
+    let (mut cpu, mut mem) = setup(Variant::Rockwell65C02, &[RMB0, 0x10, SMB7, 0x10]);
+    mem[0x0010] = 0x01;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 5);
+    assert_eq!(mem[0x0010], 0x00);
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(mem[0x0010], 0x80);
+}
+
+#[test]
+fn bbr_and_bbs() {
+    let (mut cpu, mut mem) = setup(Variant::Wdc65C02, &[BBS3, 0x10, 0x10, BBR3, 0x10, 0x10]);
+    mem[0x0010] = 0b00001000;
+
+    // bit 3 is set, BBS3 branches over nothing since the target is relative to the next instruction
+    let step = cpu.step(&mut mem).unwrap();
+    assert_eq!(step.cycles, 6);
+    assert_eq!(cpu.pc, 0xE013);
+
+    cpu.pc = 0xE003;
+    let step = cpu.step(&mut mem).unwrap();
+    assert_eq!(step.cycles, 5);
+    assert_eq!(cpu.pc, 0xE006);
+}
+
+#[test]
+fn bit_instructions_need_rockwell_or_wdc() {
+    let (mut cpu, mut mem) = setup(Variant::Cmos65C02, &[SMB0, 0x10]);
+
+    // a single byte NOP on the plain 65C02
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 1);
+    assert_eq!(mem[0x0010], 0x00);
+    assert_eq!(cpu.pc, 0xE001);
+}
+
+#[test]
+fn wai_waits_for_interrupt() {
+    let (mut cpu, mut mem) = setup(Variant::Wdc65C02, &[CLI, WAI, NOP]);
+
+    cpu.step(&mut mem).unwrap();
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 3);
+    assert!(cpu.waiting());
+
+    let step = cpu.step(&mut mem).unwrap();
+    assert_eq!((step.opcode, step.cycles), (WAI, 1));
+    assert_eq!(cpu.pc, 0xE002);
+
+    cpu.set_irq_line(true);
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.interrupt, Some(Interrupt::Irq));
+    assert!(!cpu.waiting());
+    assert_eq!(cpu.pc, 0x3000);
+}
+
+#[test]
+fn wai_resumes_on_masked_irq() {
+    let (mut cpu, mut mem) = setup(Variant::Wdc65C02, &[WAI, NOP]);
+
+    cpu.step(&mut mem).unwrap();
+    cpu.set_irq_line(true);
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(step.opcode, NOP);
+    assert_eq!(step.interrupt, None);
+    assert_eq!(cpu.pc, 0xE002);
+}
+
+#[test]
+fn stp_stops_until_reset() {
+    let (mut cpu, mut mem) = setup(Variant::Wdc65C02, &[STP]);
+
+    cpu.step(&mut mem).unwrap();
+    assert!(cpu.stopped());
+
+    cpu.trigger_nmi();
+    cpu.execute(10, &mut mem).unwrap();
+    assert_eq!(cpu.pc, 0xE001);
+
+    cpu.reset(&mut mem);
+    assert!(!cpu.stopped());
+    assert_eq!(cpu.pc, 0xE000);
+}
+
+#[test]
+fn wai_and_stp_need_wdc() {
+    let (mut cpu, mut mem) = setup(Variant::Rockwell65C02, &[WAI, STP]);
+
+    cpu.step(&mut mem).unwrap();
+    cpu.step(&mut mem).unwrap();
+
+    assert!(!cpu.waiting() && !cpu.stopped());
+    assert_eq!(cpu.pc, 0xE002);
+}
@@ -0,0 +1,178 @@
+use emulator_6502::cpu::Variant;
+use emulator_6502::consts::*;
+
+mod common;
+
+#[test]
+fn jmp_indirect_page_bug_on_nmos() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    mem[0xE000] = JMP_IND;
+    mem[0xE001] = 0xFF;
+    mem[0xE002] = 0x30;
+    mem[0x30FF] = 0x34;
+    mem[0x3000] = 0x12;
+    mem[0x3100] = 0x56;
+
+    assert_eq!(cpu.step(&mut mem).unwrap().cycles, 5);
+    assert_eq!(cpu.pc, 0x1234);
+}
+
+#[test]
+fn jmp_indirect_fixed_on_65c02() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[]);
+    mem[0xE000] = JMP_IND;
+    mem[0xE001] = 0xFF;
+    mem[0xE002] = 0x30;
+    mem[0x30FF] = 0x34;
+    mem[0x3000] = 0x12;
+    mem[0x3100] = 0x56;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.pc, 0x5634);
+}
+
+#[test]
+fn jmp_indirect_pointer_at_end_of_memory() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[]);
+    mem[0xE000] = JMP_IND;
+    mem[0xE001] = 0xFF;
+    mem[0xE002] = 0xFF;
+    mem[0xFFFF] = 0x34;
+    mem[0x0000] = 0x12;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.pc, 0x1234);
+}
+
+#[test]
+fn zero_page_x_wraps() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.x = 0xFF;
+    mem[0xE000] = LDA_ZPX;
+    mem[0xE001] = 0x80;
+    mem[0x007F] = 0x37;
+    mem[0x017F] = 0x99;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x37);
+}
+
+#[test]
+fn zero_page_y_wraps() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.y = 0x02;
+    mem[0xE000] = LDX_ZPY;
+    mem[0xE001] = 0xFF;
+    mem[0x0001] = 0x37;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.x, 0x37);
+}
+
+#[test]
+fn indexed_indirect_pointer_wraps_in_zero_page() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.x = 0x01;
+    mem[0xE000] = LDA_INDX;
+    mem[0xE001] = 0xFE;
+    mem[0x00FF] = 0x00;
+    mem[0x0000] = 0x30;
+    mem[0x0100] = 0x40;
+    mem[0x3000] = 0x37;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x37);
+}
+
+#[test]
+fn indirect_indexed_pointer_wraps_in_zero_page() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.y = 0x01;
+    mem[0xE000] = LDA_INDY;
+    mem[0xE001] = 0xFF;
+    mem[0x00FF] = 0x00;
+    mem[0x0000] = 0x30;
+    mem[0x0100] = 0x40;
+    mem[0x3001] = 0x37;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x37);
+}
+
+#[test]
+fn indirect_indexed_wraps_around_memory() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.y = 0x02;
+    mem[0xE000] = LDA_INDY;
+    mem[0xE001] = 0x10;
+    mem[0x0010] = 0xFF;
+    mem[0x0011] = 0xFF;
+    mem[0x0001] = 0x37;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x37);
+    assert!(step.page_crossed);
+}
+
+#[test]
+fn zero_page_indirect_pointer_wraps_in_zero_page() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Cmos65C02, &[]);
+    mem[0xE000] = LDA_ZPIND;
+    mem[0xE001] = 0xFF;
+    mem[0x00FF] = 0x00;
+    mem[0x0000] = 0x30;
+    mem[0x3000] = 0x37;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x37);
+}
+
+#[test]
+fn absolute_x_wraps_around_memory() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.x = 0x02;
+    mem[0xE000] = LDA_ABSX;
+    mem[0xE001] = 0xFF;
+    mem[0xE002] = 0xFF;
+    mem[0x0001] = 0x37;
+
+    let step = cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x37);
+    assert_eq!(step.cycles, 5);
+}
+
+#[test]
+fn absolute_y_store_wraps_around_memory() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.a = 0x37;
+    cpu.y = 0x01;
+    mem[0xE000] = STA_ABSY;
+    mem[0xE001] = 0xFF;
+    mem[0xE002] = 0xFF;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(mem[0x0000], 0x37);
+}
+
+#[test]
+fn program_counter_wraps_around_memory() {
+    let (mut cpu, mut mem) = common::setup_variant(Variant::Nmos6502, &[]);
+    cpu.pc = 0xFFFF;
+    mem[0xFFFF] = LDA_IM;
+    mem[0x0000] = 0x37;
+
+    cpu.step(&mut mem).unwrap();
+
+    assert_eq!(cpu.a, 0x37);
+    assert_eq!(cpu.pc, 0x0001);
+}